@@ -0,0 +1,313 @@
+//! One function per subcommand. Each returns the report printed by `main`.
+
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anchor_spl::associated_token::get_associated_token_address;
+use anchor_spl::associated_token::spl_associated_token_account::instruction::create_associated_token_account_idempotent;
+use anchor_spl::token::ID as TOKEN_PROGRAM_ID;
+use anyhow::{anyhow, bail, Context, Result};
+use rwa_raffle::{Raffle, RaffleStatus};
+use rwa_sdk::raffle::{self as ix, PrizeMetadata, ProgrammablePrize, RaffleConfig};
+use rwa_sdk::{pda, permit, Permit};
+use serde_json::{json, Map, Value};
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signer;
+
+use crate::rpc::Ctx;
+
+type Report = Map<String, Value>;
+
+fn report(value: Value) -> Report {
+    match value {
+        Value::Object(map) => map,
+        _ => unreachable!("reports are JSON objects"),
+    }
+}
+
+fn status_name(status: u8) -> &'static str {
+    match status {
+        s if s == RaffleStatus::Selling as u8 => "selling",
+        s if s == RaffleStatus::Drawing as u8 => "drawing",
+        s if s == RaffleStatus::Completed as u8 => "completed",
+        s if s == RaffleStatus::Refunding as u8 => "refunding",
+        _ => "unknown",
+    }
+}
+
+fn now() -> i64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs() as i64).unwrap_or(0)
+}
+
+fn create_ata_ix(ctx: &Ctx, owner: &Pubkey, mint: &Pubkey) -> Instruction {
+    create_associated_token_account_idempotent(&ctx.payer.pubkey(), owner, mint, &TOKEN_PROGRAM_ID)
+}
+
+fn require_organizer(ctx: &Ctx, raffle: &Raffle) -> Result<()> {
+    if raffle.organizer != ctx.payer.pubkey() {
+        bail!("keypair {} is not the raffle organizer ({})", ctx.payer.pubkey(), raffle.organizer);
+    }
+    Ok(())
+}
+
+/// Backend permit, either the full `/api/permits/raffle/create` response or its `permit` object.
+fn read_permit(path: &Path) -> Result<(Permit, [u8; 64])> {
+    let raw = std::fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+    let json: Value = serde_json::from_str(&raw)?;
+    let p = json.get("permit").unwrap_or(&json);
+
+    let nonce_hex = p["nonce"].as_str().ok_or_else(|| anyhow!("permit.nonce missing"))?;
+    if nonce_hex.len() != 32 {
+        bail!("permit.nonce must be 16 bytes of hex");
+    }
+    let mut nonce = [0u8; 16];
+    for (i, byte) in nonce.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&nonce_hex[2 * i..2 * i + 2], 16).context("permit.nonce is not hex")?;
+    }
+    let expiry_unix_ts = p["expiry"].as_i64().ok_or_else(|| anyhow!("permit.expiry missing"))?;
+    let signature: [u8; 64] = bs58::decode(p["signature"].as_str().ok_or_else(|| anyhow!("permit.signature missing"))?)
+        .into_vec()?
+        .try_into()
+        .map_err(|_| anyhow!("permit.signature must be 64 bytes"))?;
+    Ok((Permit { nonce, expiry_unix_ts }, signature))
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn create(
+    ctx: &Ctx,
+    mint: &Pubkey,
+    config: &RaffleConfig,
+    permit_path: Option<&Path>,
+    prize_collection_mint: Option<Pubkey>,
+    refund_mode: u8,
+    prize_metadata: &PrizeMetadata,
+    mrft_collection: Option<Pubkey>,
+) -> Result<Report> {
+    let organizer = ctx.payer.pubkey();
+    let mrft_collection = mrft_collection.unwrap_or_default();
+    let raffle = pda::find_raffle(mint, &organizer).0;
+    let escrow = get_associated_token_address(&raffle, mint);
+
+    let mut ixs = vec![create_ata_ix(ctx, &raffle, mint)];
+    match permit_path {
+        Some(path) => {
+            let (p, signature) = read_permit(path)?;
+            prize_metadata.validate().map_err(|e| anyhow!("invalid prize metadata: {e}"))?;
+            let msg = permit::raffle_prize_permit_message(&organizer, &p, config, prize_metadata, &mrft_collection);
+            ixs.push(permit::ed25519_verify_ix(&rwa_raffle::BACKEND_SIGNER, &signature, &msg));
+            ixs.push(ix::initialize_raffle_with_permit(
+                &organizer,
+                mint,
+                &escrow,
+                config,
+                &p,
+                &prize_collection_mint.unwrap_or_default(),
+                refund_mode,
+                prize_metadata,
+                &mrft_collection,
+            ));
+        }
+        None => ixs.push(ix::initialize_raffle(&organizer, mint, &escrow, config)),
+    }
+    let signature = ctx.send(&ixs)?;
+
+    Ok(report(json!({
+        "raffle": raffle.to_string(),
+        "escrow": escrow.to_string(),
+        "slots": pda::find_slots(&raffle).0.to_string(),
+        "signature": signature.to_string(),
+    })))
+}
+
+pub fn set_prize(
+    ctx: &Ctx,
+    raffle_key: &Pubkey,
+    prize_mint: &Pubkey,
+    programmable: Option<&ProgrammablePrize>,
+) -> Result<Report> {
+    let raffle = ctx.fetch_raffle(raffle_key)?;
+    require_organizer(ctx, &raffle)?;
+    let organizer = ctx.payer.pubkey();
+    let prize_escrow = get_associated_token_address(raffle_key, prize_mint);
+
+    let signature = ctx.send(&[
+        create_ata_ix(ctx, raffle_key, prize_mint),
+        ix::set_prize_nft(
+            &organizer,
+            raffle_key,
+            prize_mint,
+            &get_associated_token_address(&organizer, prize_mint),
+            &prize_escrow,
+            programmable,
+        ),
+    ])?;
+
+    Ok(report(json!({
+        "raffle": raffle_key.to_string(),
+        "prize_mint": prize_mint.to_string(),
+        "prize_escrow": prize_escrow.to_string(),
+        "signature": signature.to_string(),
+    })))
+}
+
+pub fn status(ctx: &Ctx, raffle_key: &Pubkey) -> Result<Report> {
+    let r = ctx.fetch_raffle(raffle_key)?;
+    Ok(report(json!({
+        "raffle": raffle_key.to_string(),
+        "version": r.version,
+        "status": status_name(r.status),
+        "organizer": r.organizer.to_string(),
+        "mint": r.mint.to_string(),
+        "escrow": r.escrow.to_string(),
+        "escrow_balance": ctx.token_amount(&r.escrow),
+        "required_tickets": r.required_tickets,
+        "tickets_sold": r.tickets_sold,
+        "deadline": r.deadline,
+        "reveal_time": r.reveal_time_unix_ts,
+        "presale_end": (r.presale_end_unix_ts > 0).then_some(r.presale_end_unix_ts),
+        "gate_mode": r.gate_mode,
+        "gate_mint": (r.gate_mode != 0).then(|| r.gate_mint.to_string()),
+        "auto_draw": r.auto_draw,
+        "ticket_mode": r.ticket_mode,
+        "refund_mode": r.refund_mode,
+        "draw_requested": r.draw_requested,
+        "winner_ticket": r.winner_ticket,
+        "prize_set": r.prize_set,
+        "prize_mint": r.prize_set.then(|| r.prize_mint.to_string()),
+        "prize_claimed": r.prize_claimed,
+        "proceeds_collected": r.proceeds_collected,
+        "tickets_refunded": r.tickets_refunded,
+        "open_tickets": r.open_tickets,
+        "slots_closed": r.slots_closed,
+        "crank_reserve": r.crank_reserve,
+        "settled": r.is_settled(),
+    })))
+}
+
+pub fn draw(ctx: &Ctx, raffle_key: &Pubkey) -> Result<Report> {
+    let raffle = ctx.fetch_raffle(raffle_key)?;
+    if raffle.status != RaffleStatus::Drawing as u8 {
+        bail!("raffle is {}, draws need it to be drawing", status_name(raffle.status));
+    }
+    if !raffle.is_reveal_due(now()) {
+        bail!("reveal time {} not reached", raffle.reveal_time_unix_ts.unwrap_or_default());
+    }
+    let signature = ctx.send(&[ix::request_draw(&ctx.payer.pubkey(), raffle_key)])?;
+
+    Ok(report(json!({
+        "raffle": raffle_key.to_string(),
+        "bounty_expected": !raffle.draw_requested,
+        "signature": signature.to_string(),
+    })))
+}
+
+pub fn settle(ctx: &Ctx, raffle_key: &Pubkey, winner_ticket: u64) -> Result<Report> {
+    let raffle = ctx.fetch_raffle(raffle_key)?;
+    if winner_ticket == 0 || winner_ticket > raffle.required_tickets {
+        bail!("winner ticket must be in 1..={}", raffle.required_tickets);
+    }
+    let signature = ctx.send(&[ix::settle_draw(&ctx.payer.pubkey(), raffle_key, winner_ticket)])?;
+
+    Ok(report(json!({
+        "raffle": raffle_key.to_string(),
+        "winner_ticket": winner_ticket,
+        "signature": signature.to_string(),
+    })))
+}
+
+pub fn refund_crank(ctx: &Ctx, raffle_key: &Pubkey, batch_size: usize) -> Result<Report> {
+    let keeper = ctx.payer.pubkey();
+    let mut raffle = ctx.fetch_raffle(raffle_key)?;
+    let mut signatures = Vec::new();
+
+    if raffle.status == RaffleStatus::Selling as u8 {
+        if now() <= raffle.deadline {
+            bail!("deadline {} has not passed", raffle.deadline);
+        }
+        signatures.push(ctx.send(&[ix::start_refunding(&keeper, raffle_key)])?.to_string());
+        raffle = ctx.fetch_raffle(raffle_key)?;
+    }
+    if raffle.status != RaffleStatus::Refunding as u8 {
+        bail!("raffle is {}, not refunding", status_name(raffle.status));
+    }
+
+    let pending: Vec<_> = ctx.fetch_tickets(raffle_key)?.into_iter().filter(|(_, t)| !t.refunded).collect();
+    let usdc_refund = raffle.refund_mode == 0;
+    for chunk in pending.chunks(batch_size) {
+        let mut ixs = Vec::new();
+        let mut entries = Vec::new();
+        for (key, ticket) in chunk {
+            if usdc_refund {
+                ixs.push(create_ata_ix(ctx, &ticket.owner, &raffle.mint));
+                entries.push((*key, Some(get_associated_token_address(&ticket.owner, &raffle.mint))));
+            } else {
+                entries.push((*key, None));
+            }
+        }
+        ixs.push(ix::refund_batch(&keeper, raffle_key, &raffle.mint, &raffle.escrow, &entries));
+        signatures.push(ctx.send(&ixs)?.to_string());
+    }
+
+    Ok(report(json!({
+        "raffle": raffle_key.to_string(),
+        "tickets_refunded": pending.len(),
+        "signatures": signatures,
+    })))
+}
+
+pub fn collect(ctx: &Ctx, raffle_key: &Pubkey) -> Result<Report> {
+    let raffle = ctx.fetch_raffle(raffle_key)?;
+    require_organizer(ctx, &raffle)?;
+    if raffle.status != RaffleStatus::Completed as u8 {
+        bail!("raffle is {}, proceeds can only be collected once completed", status_name(raffle.status));
+    }
+    if raffle.proceeds_collected {
+        bail!("proceeds already collected");
+    }
+    let organizer = ctx.payer.pubkey();
+    let organizer_ata = get_associated_token_address(&organizer, &raffle.mint);
+    let amount = ctx.token_amount(&raffle.escrow);
+
+    let signature = ctx.send(&[
+        create_ata_ix(ctx, &organizer, &raffle.mint),
+        ix::collect_proceeds(&organizer, &raffle.mint, &organizer_ata, &raffle.escrow),
+    ])?;
+
+    Ok(report(json!({
+        "raffle": raffle_key.to_string(),
+        "amount": amount,
+        "destination": organizer_ata.to_string(),
+        "signature": signature.to_string(),
+    })))
+}
+
+pub fn close(ctx: &Ctx, raffle_key: &Pubkey) -> Result<Report> {
+    let caller = ctx.payer.pubkey();
+    let raffle = ctx.fetch_raffle(raffle_key)?;
+    if !raffle.is_settled() {
+        bail!("raffle is not settled (status {})", status_name(raffle.status));
+    }
+
+    let mut signatures = Vec::new();
+    let tickets = ctx.fetch_tickets(raffle_key)?;
+    for chunk in tickets.chunks(8) {
+        let mut ixs = Vec::new();
+        for (_, t) in chunk {
+            ixs.push(ix::close_ticket(&caller, raffle_key, &t.owner, t.start, &t.payer));
+        }
+        signatures.push(ctx.send(&ixs)?.to_string());
+    }
+    let mut ixs = Vec::new();
+    if !raffle.slots_closed {
+        ixs.push(ix::close_slots(&caller, raffle_key, &raffle.organizer));
+    }
+    ixs.push(ix::close_raffle(&caller, &raffle.mint, &raffle.organizer, &raffle.escrow));
+    signatures.push(ctx.send(&ixs)?.to_string());
+
+    Ok(report(json!({
+        "raffle": raffle_key.to_string(),
+        "tickets_closed": tickets.len(),
+        "signatures": signatures,
+    })))
+}
@@ -0,0 +1,222 @@
+//! `rwa-organizer`: drive a raffle through its lifecycle from the command line.
+//!
+//! Every command prints a flat key/value report; `--output json` prints the same
+//! report as a JSON object so runbooks can pipe it into `jq`.
+
+mod commands;
+mod rpc;
+
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Result};
+use clap::{Parser, Subcommand, ValueEnum};
+use rwa_sdk::raffle::{PrizeCreator, PrizeMetadata, ProgrammablePrize};
+use solana_sdk::pubkey::Pubkey;
+
+#[derive(Parser)]
+#[command(name = "rwa-organizer", version, about = "Organizer CLI for the rwa_raffle lifecycle")]
+struct Cli {
+    /// RPC endpoint: `localhost`, `devnet`, `mainnet` or a full URL
+    #[arg(short = 'u', long, global = true, default_value = "localhost")]
+    url: String,
+
+    /// Organizer / fee payer keypair file
+    #[arg(short = 'k', long, global = true, default_value = "~/.config/solana/id.json")]
+    keypair: String,
+
+    /// Output format
+    #[arg(short = 'o', long, global = true, value_enum, default_value_t = Output::Human)]
+    output: Output,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Output {
+    Human,
+    Json,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Create a raffle (and its escrow ATA) for `mint`
+    Create {
+        /// Escrow (stable coin) mint, e.g. USDC
+        #[arg(long)]
+        mint: Pubkey,
+        #[arg(long)]
+        required_tickets: u64,
+        /// Sale deadline (unix seconds)
+        #[arg(long)]
+        deadline: i64,
+        /// Move to Drawing automatically when the last ticket sells
+        #[arg(long)]
+        auto_draw: bool,
+        /// 0=disabled, 1=accept_without_burn, 2=require_burn
+        #[arg(long, default_value_t = 0)]
+        ticket_mode: u8,
+        /// Scheduled reveal (unix seconds); no draw before this time
+        #[arg(long)]
+        reveal_time: Option<i64>,
+        /// Lamports moved into the raffle PDA to pay keeper bounties
+        #[arg(long, default_value_t = 0)]
+        crank_reserve: u64,
+        /// Backend permit JSON (`/api/permits/raffle/create` response); uses
+        /// `initialize_raffle_with_permit` when given
+        #[arg(long)]
+        permit: Option<PathBuf>,
+        /// Permit path only: collection mint for post-mint prizes
+        #[arg(long)]
+        prize_collection_mint: Option<Pubkey>,
+        /// Permit path only: 0=USDC refund, 1=MRFT mint, 2=both
+        #[arg(long, default_value_t = 0)]
+        refund_mode: u8,
+        /// Permit path only: name of the post-mint prize NFT (max 32 bytes)
+        #[arg(long, default_value = "")]
+        prize_name: String,
+        /// Permit path only: symbol of the post-mint prize NFT (max 10 bytes)
+        #[arg(long, default_value = "")]
+        prize_symbol: String,
+        /// Permit path only: metadata URI of the post-mint prize NFT, e.g. its `metadata/nfts` JSON
+        #[arg(long, default_value = "")]
+        prize_uri: String,
+        /// Permit path only: royalties of the post-mint prize NFT in basis points
+        #[arg(long, default_value_t = 0)]
+        prize_seller_fee_bps: u16,
+        /// Permit path only: prize creator as `ADDRESS:SHARE`; repeat for up to 5 creators
+        /// whose shares add up to 100
+        #[arg(long = "prize-creator", value_parser = parse_creator)]
+        prize_creators: Vec<PrizeCreator>,
+        /// Permit path only: MRFT collection `join_with_ticket` accepts (default: the platform's)
+        #[arg(long)]
+        mrft_collection: Option<Pubkey>,
+    },
+    /// Escrow the prize NFT into the raffle
+    SetPrize {
+        #[arg(long)]
+        raffle: Pubkey,
+        #[arg(long)]
+        prize_mint: Pubkey,
+        /// The prize is a Metaplex programmable NFT (pNFT)
+        #[arg(long)]
+        pnft: bool,
+        /// pNFT authorization rule set, if the prize has one
+        #[arg(long, requires = "pnft")]
+        rule_set: Option<Pubkey>,
+    },
+    /// Show raffle state and escrow balance
+    Status {
+        #[arg(long)]
+        raffle: Pubkey,
+    },
+    /// Request the draw (permissionless; pays the keeper bounty once)
+    Draw {
+        #[arg(long)]
+        raffle: Pubkey,
+    },
+    /// Record the winning ticket (signed by the platform authority)
+    Settle {
+        #[arg(long)]
+        raffle: Pubkey,
+        #[arg(long)]
+        winner_ticket: u64,
+    },
+    /// Move an expired raffle to Refunding and refund every open ticket
+    RefundCrank {
+        #[arg(long)]
+        raffle: Pubkey,
+        /// Tickets per refund_batch transaction
+        #[arg(long, default_value_t = 4)]
+        batch_size: usize,
+    },
+    /// Collect USDC proceeds from a completed raffle
+    Collect {
+        #[arg(long)]
+        raffle: Pubkey,
+    },
+    /// Close tickets, slots, escrow and the raffle once it is settled
+    Close {
+        #[arg(long)]
+        raffle: Pubkey,
+    },
+}
+
+fn main() {
+    let cli = Cli::parse();
+    let output = cli.output;
+    if let Err(err) = run(cli) {
+        match output {
+            Output::Json => println!("{}", serde_json::json!({ "error": format!("{err:#}") })),
+            Output::Human => eprintln!("error: {err:#}"),
+        }
+        std::process::exit(1);
+    }
+}
+
+fn run(cli: Cli) -> Result<()> {
+    let ctx = rpc::Ctx::new(&cli.url, &cli.keypair, cli.output)?;
+    let report = match cli.command {
+        Command::Create {
+            mint,
+            required_tickets,
+            deadline,
+            auto_draw,
+            ticket_mode,
+            reveal_time,
+            crank_reserve,
+            permit,
+            prize_collection_mint,
+            refund_mode,
+            prize_name,
+            prize_symbol,
+            prize_uri,
+            prize_seller_fee_bps,
+            prize_creators,
+            mrft_collection,
+        } => {
+            let config = rwa_sdk::raffle::RaffleConfig {
+                required_tickets,
+                deadline_unix_ts: deadline,
+                auto_draw,
+                ticket_mode,
+                reveal_time_unix_ts: reveal_time,
+                crank_reserve_lamports: crank_reserve,
+            };
+            let prize_metadata = PrizeMetadata {
+                name: prize_name,
+                symbol: prize_symbol,
+                uri: prize_uri,
+                seller_fee_basis_points: prize_seller_fee_bps,
+                creators: prize_creators,
+            };
+            commands::create(
+                &ctx,
+                &mint,
+                &config,
+                permit.as_deref(),
+                prize_collection_mint,
+                refund_mode,
+                &prize_metadata,
+                mrft_collection,
+            )?
+        }
+        Command::SetPrize { raffle, prize_mint, pnft, rule_set } => {
+            let programmable = pnft.then_some(ProgrammablePrize { rule_set });
+            commands::set_prize(&ctx, &raffle, &prize_mint, programmable.as_ref())?
+        }
+        Command::Status { raffle } => commands::status(&ctx, &raffle)?,
+        Command::Draw { raffle } => commands::draw(&ctx, &raffle)?,
+        Command::Settle { raffle, winner_ticket } => commands::settle(&ctx, &raffle, winner_ticket)?,
+        Command::RefundCrank { raffle, batch_size } => commands::refund_crank(&ctx, &raffle, batch_size.max(1))?,
+        Command::Collect { raffle } => commands::collect(&ctx, &raffle)?,
+        Command::Close { raffle } => commands::close(&ctx, &raffle)?,
+    };
+    ctx.print(&report);
+    Ok(())
+}
+
+fn parse_creator(value: &str) -> Result<PrizeCreator> {
+    let (address, share) = value.split_once(':').ok_or_else(|| anyhow!("expected ADDRESS:SHARE"))?;
+    Ok(PrizeCreator { address: address.parse()?, share: share.parse()? })
+}
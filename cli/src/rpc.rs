@@ -0,0 +1,106 @@
+//! RPC, keypair and output plumbing shared by every command.
+
+use anchor_lang::AccountDeserialize;
+use anyhow::{anyhow, Context, Result};
+use rwa_raffle::{Raffle, Ticket};
+use serde_json::{Map, Value};
+use solana_account_decoder_client_types::UiAccountEncoding;
+use solana_client::rpc_client::RpcClient;
+use solana_client::rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig};
+use solana_client::rpc_filter::{Memcmp, RpcFilterType};
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{read_keypair_file, Keypair, Signature, Signer};
+use solana_sdk::transaction::Transaction;
+
+use crate::Output;
+
+pub struct Ctx {
+    pub client: RpcClient,
+    pub payer: Keypair,
+    output: Output,
+}
+
+impl Ctx {
+    pub fn new(url: &str, keypair_path: &str, output: Output) -> Result<Self> {
+        let url = match url {
+            "localhost" | "l" => "http://127.0.0.1:8899",
+            "devnet" | "d" => "https://api.devnet.solana.com",
+            "mainnet" | "mainnet-beta" | "m" => "https://api.mainnet-beta.solana.com",
+            other => other,
+        };
+        let path = match keypair_path.strip_prefix("~/") {
+            Some(rest) => format!("{}/{}", std::env::var("HOME").unwrap_or_default(), rest),
+            None => keypair_path.to_string(),
+        };
+        let payer = read_keypair_file(&path).map_err(|e| anyhow!("reading keypair {path}: {e}"))?;
+        Ok(Self {
+            client: RpcClient::new_with_commitment(url.to_string(), CommitmentConfig::confirmed()),
+            payer,
+            output,
+        })
+    }
+
+    pub fn send(&self, ixs: &[Instruction]) -> Result<Signature> {
+        let blockhash = self.client.get_latest_blockhash()?;
+        let tx = Transaction::new_signed_with_payer(ixs, Some(&self.payer.pubkey()), &[&self.payer], blockhash);
+        self.client.send_and_confirm_transaction(&tx).context("transaction failed")
+    }
+
+    pub fn fetch_raffle(&self, raffle: &Pubkey) -> Result<Raffle> {
+        let data = self.client.get_account_data(raffle).with_context(|| format!("fetching raffle {raffle}"))?;
+        Raffle::try_deserialize(&mut data.as_slice())
+            .map_err(|e| anyhow!("decoding raffle {raffle}: {e} (legacy layouts need `migrate_raffle`)"))
+    }
+
+    /// Every Ticket account of `raffle` (memcmp on `Ticket.raffle`, right after the discriminator).
+    pub fn fetch_tickets(&self, raffle: &Pubkey) -> Result<Vec<(Pubkey, Ticket)>> {
+        let config = RpcProgramAccountsConfig {
+            filters: Some(vec![
+                RpcFilterType::DataSize((8 + Ticket::LEN) as u64),
+                RpcFilterType::Memcmp(Memcmp::new_base58_encoded(8, raffle.as_ref())),
+            ]),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let accounts = self.client.get_program_accounts_with_config(&rwa_raffle::ID, config)?;
+        accounts
+            .into_iter()
+            .map(|(key, account)| {
+                let ticket = Ticket::try_deserialize(&mut account.data.as_slice())
+                    .map_err(|e| anyhow!("decoding ticket {key}: {e}"))?;
+                Ok((key, ticket))
+            })
+            .collect()
+    }
+
+    /// Raw token amount held by `token_account`, or 0 if it doesn't exist.
+    pub fn token_amount(&self, token_account: &Pubkey) -> u64 {
+        self.client
+            .get_token_account_balance(token_account)
+            .ok()
+            .and_then(|b| b.amount.parse().ok())
+            .unwrap_or(0)
+    }
+
+    pub fn print(&self, report: &Map<String, Value>) {
+        match self.output {
+            Output::Json => println!("{}", serde_json::to_string_pretty(report).unwrap_or_default()),
+            Output::Human => {
+                let width = report.keys().map(|k| k.len()).max().unwrap_or(0);
+                for (key, value) in report {
+                    let value = match value {
+                        Value::String(s) => s.clone(),
+                        Value::Null => "-".to_string(),
+                        other => other.to_string(),
+                    };
+                    println!("{key:<width$}  {value}");
+                }
+            }
+        }
+    }
+}
@@ -38,7 +38,14 @@ app.use(express.json());
  *   required_tickets: number,
  *   deadline: number (unix timestamp),
  *   auto_draw: boolean,
- *   ticket_mode: number (0-2)
+ *   ticket_mode: number (0-2),
+ *   reveal_time?: number (unix timestamp, scheduled reveal),
+ *   prize_metadata?: {               // what claim_prize_mint gives the minted prize
+ *     name: string, symbol: string, uri: string,
+ *     seller_fee_basis_points: number,
+ *     creators: { address: string (pubkey), share: number }[]
+ *   },
+ *   mrft_collection?: string (pubkey) // MRFTs join_with_ticket accepts; default = platform MRFTs
  * }
  * 
  * Response:
@@ -50,14 +57,45 @@ app.use(express.json());
  *   }
  * }
  */
+// Limits match rwa_raffle::PrizeMetadata::validate (Metaplex's own limits)
+const PrizeMetadataSchema = z.object({
+  name: z.string().refine((s) => Buffer.byteLength(s) <= 32),
+  symbol: z.string().refine((s) => Buffer.byteLength(s) <= 10),
+  uri: z.string().refine((s) => Buffer.byteLength(s) <= 200),
+  seller_fee_basis_points: z.number().int().min(0).max(10000),
+  creators: z
+    .array(z.object({ address: z.string(), share: z.number().int().min(0).max(100) }))
+    .max(5)
+    .refine((cs) => cs.length === 0 || cs.reduce((sum, c) => sum + c.share, 0) === 100),
+});
+
 const CreateRafflePermitSchema = z.object({
   organizer: z.string(),
   required_tickets: z.number().int().positive(),
   deadline: z.number().int(),
   auto_draw: z.boolean(),
   ticket_mode: z.number().int().min(0).max(2),
+  reveal_time: z.number().int().positive().optional(),
+  prize_metadata: PrizeMetadataSchema.default({ name: '', symbol: '', uri: '', seller_fee_basis_points: 0, creators: [] }),
+  mrft_collection: z.string().optional(),
 });
 
+/** sha256 of the Borsh-encoded `PrizeMetadata`, as in `rwa_raffle::prize_metadata_hash`. */
+async function prizeMetadataHash(m: z.infer<typeof PrizeMetadataSchema>): Promise<Buffer> {
+  const u32 = (n: number) => Buffer.from(new Uint32Array([n]).buffer);
+  const str = (s: string) => Buffer.concat([u32(Buffer.byteLength(s)), Buffer.from(s, 'utf8')]);
+  const bytes = Buffer.concat([
+    str(m.name),
+    str(m.symbol),
+    str(m.uri),
+    Buffer.from(new Uint16Array([m.seller_fee_basis_points]).buffer),
+    u32(m.creators.length),
+    ...m.creators.map((c) => Buffer.concat([Buffer.from(bs58.decode(c.address)), Buffer.from([c.share])])),
+  ]);
+  const crypto = await import('crypto');
+  return crypto.createHash('sha256').update(bytes).digest();
+}
+
 app.post('/api/permits/raffle/create', async (req, res) => {
   try {
     const body = CreateRafflePermitSchema.parse(req.body);
@@ -73,7 +111,7 @@ app.post('/api/permits/raffle/create', async (req, res) => {
     const expiry = Math.floor(Date.now() / 1000) + 3600; // 1 hour expiry
     const programId = Buffer.from(bs58.decode(process.env.RWA_RAFFLE_PROGRAM_ID!));
     
-    // Build message: b"RWA_RAFFLE_PERMIT" || organizer || nonce || expiry || required_tickets || deadline || program_id || auto_draw || ticket_mode
+    // Build message: b"RWA_RAFFLE_PERMIT" || organizer || nonce || expiry || required_tickets || deadline || program_id || auto_draw || ticket_mode || reveal_time || prize_metadata_hash || mrft_collection
     const message = Buffer.concat([
       Buffer.from('RWA_RAFFLE_PERMIT'),
       Buffer.from(bs58.decode(body.organizer)),
@@ -84,6 +122,9 @@ app.post('/api/permits/raffle/create', async (req, res) => {
       programId,
       Buffer.from([body.auto_draw ? 1 : 0]),
       Buffer.from([body.ticket_mode]),
+      Buffer.from(new BigInt64Array([BigInt(body.reveal_time ?? 0)]).buffer),
+      await prizeMetadataHash(body.prize_metadata),
+      body.mrft_collection ? Buffer.from(bs58.decode(body.mrft_collection)) : Buffer.alloc(32),
     ]);
     
     const signature = nacl.sign.detached(message, BACKEND_KEYPAIR.secretKey);
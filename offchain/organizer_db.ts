@@ -85,14 +85,17 @@ export interface RafflePermitRequest {
     deadlineUnixTs: bigint;
   };
   autoDraw?: boolean;           // Optional config flag
-  ticketMode?: number;          // 0=disabled, 1=require_burn, 2=accept_without_burn
+  ticketMode?: number;          // 0=disabled, 1=accept_without_burn, 2=require_burn
+  revealTimeUnixTs?: bigint;    // Optional scheduled reveal (0/undefined = none)
+  prizeMetadataHash?: Uint8Array; // sha256 of the Borsh PrizeMetadata (initialize_raffle_with_permit)
 }
 
 /**
  * Build canonical BINARY permit message for signing (wallet signs this bytestring)
  * Layout:
  *   b"RWA_RAFFLE_PERMIT" || organizer(32) || nonce(16) || expiry(i64 LE) ||
- *   required_tickets(u64 LE) || deadline(i64 LE) || program_id(32) || auto_draw(u8) || ticket_mode(u8)
+ *   required_tickets(u64 LE) || deadline(i64 LE) || program_id(32) || auto_draw(u8) || ticket_mode(u8) ||
+ *   reveal_time(i64 LE, 0 = none) || prize_metadata_hash(32, when given)
  */
 export function buildPermitMessage(req: RafflePermitRequest, programId: string): Uint8Array {
   const organizerPk = new PublicKey(req.organizer);
@@ -100,7 +103,7 @@ export function buildPermitMessage(req: RafflePermitRequest, programId: string):
   const nonceBytes = Buffer.from(req.nonce.replace(/-/g, ""), "hex").subarray(0, 16);
   const prefix = Buffer.from("RWA_RAFFLE_PERMIT", "ascii"); // 17 bytes
   const buf = Buffer.alloc(
-    prefix.length + 32 + 16 + 8 + 8 + 8 + 32 + 1 + 1
+    prefix.length + 32 + 16 + 8 + 8 + 8 + 32 + 1 + 1 + 8
   );
   let off = 0;
   prefix.copy(buf, off); off += prefix.length;
@@ -112,7 +115,8 @@ export function buildPermitMessage(req: RafflePermitRequest, programId: string):
   programPk.toBuffer().copy(buf, off); off += 32;
   buf.writeUInt8(req.autoDraw ? 1 : 0, off); off += 1;
   buf.writeUInt8((req.ticketMode ?? 0) & 0xff, off); off += 1;
-  return buf;
+  buf.writeBigInt64LE(BigInt(req.revealTimeUnixTs ?? 0), off); off += 8;
+  return req.prizeMetadataHash ? Buffer.concat([buf, Buffer.from(req.prizeMetadataHash)]) : buf;
 }
 
 // NOTE: We DO NOT sign on the backend. The organizer's wallet must sign this message via signMessage.
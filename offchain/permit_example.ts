@@ -90,7 +90,7 @@ function main() {
     console.log(`     permitNonce: Buffer.from("${Buffer.from(nonce).toString("hex")}", "hex"),`);
     console.log(`     permitExpiryUnixTs: ${permitRequest.expiryUnixTs}n,`);
     console.log(`     autoDraw: true,`);
-    console.log(`     ticketMode: 1, // 0=disabled, 1=require_burn, 2=accept_without_burn`);
+    console.log(`     ticketMode: 1, // 0=disabled, 1=accept_without_burn, 2=require_burn`);
     console.log(`   });`);
     console.log("");
 
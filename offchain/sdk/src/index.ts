@@ -245,17 +245,22 @@ export class MogateSDK {
     const signatureBytes = bs58.decode(signature);
     const publicKeyBytes = publicKey.toBytes();
 
-    // Ed25519 instruction data format
+    // Ed25519 instruction data format (single signature, everything inline):
+    // num_signatures || padding || then u16 LE offsets; instruction index 0xffff = this ix
+    const u16 = (v: number) => Buffer.from([v & 0xff, (v >> 8) & 0xff]);
+    const sigOffset = 16;
+    const pkOffset = sigOffset + 64;
+    const msgOffset = pkOffset + 32;
     const data = Buffer.concat([
       Buffer.from([1]), // num_signatures
       Buffer.from([0]), // padding
-      Buffer.from([0, 0]), // signature_offset (u16)
-      Buffer.from([0, 64]), // signature_length (u16)
-      Buffer.from([0, 64]), // public_key_offset (u16)
-      Buffer.from([0, 32]), // public_key_length (u16)
-      Buffer.from([0, 96]), // message_offset (u16)
-      Buffer.from([message.length >> 8, message.length & 0xff]), // message_length (u16)
-      Buffer.from([0xff, 0xff]), // message_instruction_index (u16, 0xffff = current ix)
+      u16(sigOffset), // signature_offset
+      u16(0xffff), // signature_instruction_index
+      u16(pkOffset), // public_key_offset
+      u16(0xffff), // public_key_instruction_index
+      u16(msgOffset), // message_data_offset
+      u16(message.length), // message_data_size
+      u16(0xffff), // message_instruction_index
       signatureBytes,
       publicKeyBytes,
       message,
@@ -276,6 +281,8 @@ export class MogateSDK {
     deadline: number;
     autoDraw: boolean;
     ticketMode: number;
+    revealTime?: number;
+    prizeMetadataHash?: Buffer; // sha256 of the Borsh PrizeMetadata; initialize_raffle_with_permit only
   }): Buffer {
     return Buffer.concat([
       Buffer.from('RWA_RAFFLE_PERMIT'),
@@ -287,6 +294,8 @@ export class MogateSDK {
       this.getProgramId('raffle').toBuffer(),
       Buffer.from([params.autoDraw ? 1 : 0]),
       Buffer.from([params.ticketMode]),
+      Buffer.from(new BigInt64Array([BigInt(params.revealTime ?? 0)]).buffer),
+      params.prizeMetadataHash ?? Buffer.alloc(0),
     ]);
   }
 
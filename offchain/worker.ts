@@ -18,8 +18,9 @@ import {
   Commitment,
   Logs,
 } from "@solana/web3.js";
+import { createHash } from "crypto";
 import { readFileSync } from "fs";
-import { BorshCoder, EventParser } from "@coral-xyz/anchor";
+import { BorshCoder, EventParser, utils } from "@coral-xyz/anchor";
 
 // ============================================================================
 // CONFIGURATION
@@ -54,9 +55,12 @@ interface ThresholdReachedEvent {
 
 interface RefundTicketsRequestedEvent {
   raffle: PublicKey;
+  ticket: PublicKey;
   owner: PublicKey;
   start: bigint;
   count: bigint;
+  refundMode: number;
+  usdcValuePerTicket: bigint;
 }
 
 interface WinnerSelectedEvent {
@@ -135,37 +139,118 @@ async function handleWinnerSelected(
 // EVENT PARSING
 // ============================================================================
 
-function parseAnchorEvent(logs: string[]): { name: string; data: any } | null {
-  // Anchor events are logged as base64-encoded data with "Program data: " prefix
-  for (const log of logs) {
-    if (log.includes("Program data: ")) {
-      try {
-        const dataStr = log.split("Program data: ")[1];
-        const data = Buffer.from(dataStr, "base64");
-        
-        // First 8 bytes are the event discriminator
-        const discriminator = data.subarray(0, 8);
-        const eventData = data.subarray(8);
-
-        // TODO: Use Anchor IDL to properly decode events
-        // For now, we'll do basic pattern matching on discriminator
-        
-        // Event discriminators (computed from event name hash)
-        // You can get these from the IDL or by logging them
-        const THRESHOLD_REACHED_DISC = "threshold_reached"; // placeholder
-        const REFUND_TICKETS_REQUESTED_DISC = "refund_tickets_requested"; // placeholder
-        const WINNER_SELECTED_DISC = "winner_selected"; // placeholder
-
-        // Basic decoding (replace with proper Anchor deserialization)
-        return { name: "unknown", data: eventData };
-      } catch (error) {
-        console.error("Failed to parse event:", error);
+// rwa_raffle emits events via self-CPI (`emit_cpi!`): each event is an inner
+// instruction to PROGRAM_ID whose data is EVENT_IX_TAG || event discriminator || borsh payload.
+// Prefer this over log parsing; logs can be truncated or dropped by RPC providers.
+const EVENT_IX_TAG = Buffer.from("e445a52e51cb9a1d", "hex");
+
+async function fetchCpiEvents(connection: Connection, signature: string): Promise<Buffer[]> {
+  const tx = await connection.getTransaction(signature, {
+    commitment: "confirmed",
+    maxSupportedTransactionVersion: 0,
+  });
+  if (!tx?.meta?.innerInstructions) return [];
+  const keys = tx.transaction.message.getAccountKeys({
+    accountKeysFromLookups: tx.meta.loadedAddresses,
+  });
+  const events: Buffer[] = [];
+  for (const inner of tx.meta.innerInstructions) {
+    for (const ix of inner.instructions) {
+      if (!keys.get(ix.programIdIndex)?.equals(PROGRAM_ID)) continue;
+      const data = Buffer.from(utils.bytes.bs58.decode(ix.data));
+      if (data.subarray(0, 8).equals(EVENT_IX_TAG)) {
+        events.push(data.subarray(8)); // discriminator(8) || payload
       }
     }
   }
+  return events;
+}
+
+function parseLogEvents(logs: string[]): Buffer[] {
+  // Legacy: Anchor events logged as base64-encoded data with "Program data: " prefix
+  const events: Buffer[] = [];
+  for (const log of logs) {
+    if (log.startsWith("Program data: ")) {
+      events.push(Buffer.from(log.slice("Program data: ".length), "base64"));
+    }
+  }
+  return events;
+}
+
+// Anchor event discriminator: sha256("event:<Name>")[..8]
+function eventDiscriminator(name: string): Buffer {
+  return createHash("sha256").update(`event:${name}`).digest().subarray(0, 8);
+}
+
+const THRESHOLD_REACHED_DISC = eventDiscriminator("ThresholdReached");
+const REFUND_TICKETS_REQUESTED_DISC = eventDiscriminator("RefundTicketsRequested");
+const WINNER_SELECTED_DISC = eventDiscriminator("WinnerSelected");
+
+// Borsh reader for the fixed-size fields of the events above
+class BorshReader {
+  private offset = 0;
+  constructor(private readonly buf: Buffer) {}
+
+  pubkey(): PublicKey {
+    const key = new PublicKey(this.buf.subarray(this.offset, this.offset + 32));
+    this.offset += 32;
+    return key;
+  }
+
+  u64(): bigint {
+    const value = this.buf.readBigUInt64LE(this.offset);
+    this.offset += 8;
+    return value;
+  }
+
+  u8(): number {
+    return this.buf.readUInt8(this.offset++);
+  }
+}
+
+type RaffleEvent =
+  | { name: "ThresholdReached"; data: ThresholdReachedEvent }
+  | { name: "RefundTicketsRequested"; data: RefundTicketsRequestedEvent }
+  | { name: "WinnerSelected"; data: WinnerSelectedEvent };
+
+// Decode discriminator(8) || borsh payload; null for events the worker doesn't handle.
+function decodeEvent(data: Buffer): RaffleEvent | null {
+  const disc = data.subarray(0, 8);
+  const r = new BorshReader(data.subarray(8));
+  if (disc.equals(THRESHOLD_REACHED_DISC)) {
+    return { name: "ThresholdReached", data: { raffle: r.pubkey(), supply: r.u64() } };
+  }
+  if (disc.equals(REFUND_TICKETS_REQUESTED_DISC)) {
+    return {
+      name: "RefundTicketsRequested",
+      data: {
+        raffle: r.pubkey(),
+        ticket: r.pubkey(),
+        owner: r.pubkey(),
+        start: r.u64(),
+        count: r.u64(),
+        refundMode: r.u8(),
+        usdcValuePerTicket: r.u64(),
+      },
+    };
+  }
+  if (disc.equals(WINNER_SELECTED_DISC)) {
+    return { name: "WinnerSelected", data: { raffle: r.pubkey(), winnerTicket: r.u64() } };
+  }
   return null;
 }
 
+async function routeEvent(connection: Connection, event: RaffleEvent) {
+  switch (event.name) {
+    case "ThresholdReached":
+      return handleThresholdReached(connection, event.data);
+    case "RefundTicketsRequested":
+      return handleRefundTicketsRequested(connection, event.data);
+    case "WinnerSelected":
+      return handleWinnerSelected(connection, event.data);
+  }
+}
+
 // ============================================================================
 // MAIN WORKER LOOP
 // ============================================================================
@@ -184,27 +269,25 @@ async function main() {
     PROGRAM_ID,
     async (logs: Logs) => {
       try {
-        // Parse events from logs
-        const event = parseAnchorEvent(logs.logs);
-        
-        if (!event) {
-          // No event found, skip
-          return;
+        // Events arrive as self-CPI inner instructions; the log notification only
+        // tells us which transaction to fetch. Program logs are the fallback when the
+        // transaction can't be fetched or carries no CPI events (pre-upgrade builds).
+        let raw: Buffer[] = [];
+        try {
+          raw = await fetchCpiEvents(connection, logs.signature);
+        } catch (error) {
+          console.error(`Failed to fetch ${logs.signature}, falling back to logs:`, error);
+        }
+        if (raw.length === 0) {
+          raw = parseLogEvents(logs.logs);
         }
 
-        // Route to appropriate handler
-        // TODO: Implement proper event discrimination and deserialization
-        console.log(`📨 Event received: ${event.name}`);
-        
-        // For now, just log all events
-        // In production, uncomment and implement proper routing:
-        // if (event.name === "ThresholdReached") {
-        //   await handleThresholdReached(connection, event.data);
-        // } else if (event.name === "RefundTicketsRequested") {
-        //   await handleRefundTicketsRequested(connection, event.data);
-        // } else if (event.name === "WinnerSelected") {
-        //   await handleWinnerSelected(connection, event.data);
-        // }
+        for (const data of raw) {
+          const event = decodeEvent(data);
+          if (!event) continue;
+          console.log(`📨 Event received: ${event.name} (${logs.signature})`);
+          await routeEvent(connection, event);
+        }
       } catch (error) {
         console.error("Error handling logs:", error);
       }
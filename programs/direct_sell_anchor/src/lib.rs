@@ -4,8 +4,8 @@ use anchor_spl::token::{self as token, Mint, TokenAccount, TransferChecked, Toke
 // NOTE: Anchor IDL-compatible variant
 declare_id!("DE9rqqvye7rExak5cjYdkBup5wR9PRYMrbZw17xPooCt");
 
-const LISTING_SEED: &[u8] = b"listing";
-const CONFIG_SEED: &[u8] = b"config";
+pub const LISTING_SEED: &[u8] = b"listing";
+pub const CONFIG_SEED: &[u8] = b"config";
 const BACKEND_SIGNER: Pubkey = pubkey!("2mdvoXMrxTPyqq9ETxAf7YLgLU7GHdefR88SLvQ5xC7r");
 
 // Platform fee: 250 basis points = 2.5%
@@ -56,25 +56,7 @@ pub mod direct_sell_anchor {
             if loaded.is_err() { break; }
             let ix = loaded.unwrap();
             if ix.program_id == ed25519_program::id() {
-                let data = ix.data.as_slice();
-                if data.len() < 16 { idx += 1; continue; }
-                let num = data[0] as usize;
-                if num != 1 { idx += 1; continue; }
-                let sig_off = u16::from_le_bytes([data[2], data[3]]) as usize;
-                let sig_len = u16::from_le_bytes([data[4], data[5]]) as usize;
-                let pk_off = u16::from_le_bytes([data[6], data[7]]) as usize;
-                let pk_len = u16::from_le_bytes([data[8], data[9]]) as usize;
-                let msg_off = u16::from_le_bytes([data[10], data[11]]) as usize;
-                let msg_len = u16::from_le_bytes([data[12], data[13]]) as usize;
-                let msg_acc_idx = u16::from_le_bytes([data[14], data[15]]);
-
-                if msg_acc_idx != u16::MAX { idx += 1; continue; }
-                if pk_len != 32 || sig_len != 64 { idx += 1; continue; }
-                if pk_off.checked_add(pk_len).unwrap_or(usize::MAX) > data.len() { idx += 1; continue; }
-                if msg_off.checked_add(msg_len).unwrap_or(usize::MAX) > data.len() { idx += 1; continue; }
-
-                let pk_bytes = &data[pk_off..pk_off+pk_len];
-                let msg_bytes = &data[msg_off..msg_off+msg_len];
+                let Some((pk_bytes, msg_bytes)) = ed25519_ix_parts(&ix.data) else { idx += 1; continue; };
 
                 if pk_bytes == BACKEND_SIGNER.as_ref() && msg_bytes == expected_msg.as_slice() {
                     found = true;
@@ -479,25 +461,7 @@ pub mod direct_sell_anchor {
                 if loaded.is_err() { break; }
                 let ix = loaded.unwrap();
                 if ix.program_id == ed25519_program::id() {
-                    let data = ix.data.as_slice();
-                    if data.len() < 16 { idx += 1; continue; }
-                    let num = data[0] as usize;
-                    if num != 1 { idx += 1; continue; }
-                    let sig_off = u16::from_le_bytes([data[2], data[3]]) as usize;
-                    let sig_len = u16::from_le_bytes([data[4], data[5]]) as usize;
-                    let pk_off = u16::from_le_bytes([data[6], data[7]]) as usize;
-                    let pk_len = u16::from_le_bytes([data[8], data[9]]) as usize;
-                    let msg_off = u16::from_le_bytes([data[10], data[11]]) as usize;
-                    let msg_len = u16::from_le_bytes([data[12], data[13]]) as usize;
-                    let msg_acc_idx = u16::from_le_bytes([data[14], data[15]]);
-
-                    if msg_acc_idx != u16::MAX { idx += 1; continue; }
-                    if pk_len != 32 || sig_len != 64 { idx += 1; continue; }
-                    if pk_off.checked_add(pk_len).unwrap_or(usize::MAX) > data.len() { idx += 1; continue; }
-                    if msg_off.checked_add(msg_len).unwrap_or(usize::MAX) > data.len() { idx += 1; continue; }
-
-                    let pk_bytes = &data[pk_off..pk_off+pk_len];
-                    let msg_bytes = &data[msg_off..msg_off+msg_len];
+                    let Some((pk_bytes, msg_bytes)) = ed25519_ix_parts(&ix.data) else { idx += 1; continue; };
 
                     if pk_bytes == BACKEND_SIGNER.as_ref() && msg_bytes == expected_msg.as_slice() {
                         found = true;
@@ -516,6 +480,20 @@ pub mod direct_sell_anchor {
     }
 }
 
+// Backend key and permit message of an ed25519 precompile instruction (one signature,
+// everything inline). Header: count, padding, then seven u16 offsets (signature, key and
+// message offsets, each followed by its instruction index; the message also has a size).
+#[cfg(not(feature = "test-bypass"))]
+fn ed25519_ix_parts(data: &[u8]) -> Option<(&[u8], &[u8])> {
+    if data.len() < 16 || data[0] != 1 { return None; }
+    let field = |at: usize| u16::from_le_bytes([data[at], data[at + 1]]);
+    if field(4) != u16::MAX || field(8) != u16::MAX || field(14) != u16::MAX { return None; }
+    let (pk_off, msg_off, msg_len) = (field(6) as usize, field(10) as usize, field(12) as usize);
+    let pk_bytes = data.get(pk_off..pk_off + 32)?;
+    let msg_bytes = data.get(msg_off..msg_off + msg_len)?;
+    Some((pk_bytes, msg_bytes))
+}
+
 // Internal helper for create_listing (with permit)
 #[cfg(not(feature = "test-bypass"))]
 fn create_listing_internal(
@@ -897,3 +875,28 @@ pub enum DirectSellError {
     #[msg("Invalid Pyth price")] InvalidPythPrice,
     #[msg("Invalid fee basis points (max 1000 = 10%)")] InvalidFeeBps,
 }
+
+#[cfg(all(test, not(feature = "test-bypass")))]
+mod tests {
+    use super::*;
+    use solana_ed25519_program::new_ed25519_instruction_with_signature;
+
+    #[test]
+    fn ed25519_ix_parts_reads_the_precompile_layout() {
+        let message = b"permit";
+        let ix = new_ed25519_instruction_with_signature(message, &[7u8; 64], &BACKEND_SIGNER.to_bytes());
+        assert_eq!(ed25519_ix_parts(&ix.data), Some((BACKEND_SIGNER.as_ref(), &message[..])));
+
+        // Signature, key or message taken from another instruction
+        for at in [4, 8, 14] {
+            let mut data = ix.data.clone();
+            data[at..at + 2].copy_from_slice(&0u16.to_le_bytes());
+            assert_eq!(ed25519_ix_parts(&data), None);
+        }
+        // Several signatures, or a message running past the data
+        let mut data = ix.data.clone();
+        data[0] = 2;
+        assert_eq!(ed25519_ix_parts(&data), None);
+        assert_eq!(ed25519_ix_parts(&ix.data[..ix.data.len() - 1]), None);
+    }
+}
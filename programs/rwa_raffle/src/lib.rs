@@ -1,8 +1,11 @@
 use anchor_lang::prelude::*;
 use anchor_spl::token_interface::{self as token, Mint, TokenAccount, TransferChecked};
 use anchor_spl::token::Token;
+use anchor_spl::token_2022::Token2022;
+use anchor_spl::associated_token::AssociatedToken;
 use arcium_anchor::prelude::*;
 use anchor_lang::solana_program::instruction::AccountMeta;
+use anchor_lang::solana_program::hash::hashv;
 // Temporarily commented out - Light SDK incompatible with rustc 1.79.0 // 
 // use light_sdk::{
 //     account::LightAccount,
@@ -16,15 +19,25 @@ use anchor_lang::solana_program::instruction::AccountMeta;
 
 #[cfg(feature = "bubblegum")]
 use mpl_bubblegum::programs::MPL_BUBBLEGUM_ID;
-#[cfg(feature = "bubblegum")]
-use spl_account_compression::{program::SplAccountCompression, Noop};
+
+// Programs Bubblegum drives; only ever passed through to it
+pub const SPL_ACCOUNT_COMPRESSION_ID: Pubkey = pubkey!("cmtDvXumGCrqC1Age74AVPhSRVXJMd8PJS91L8KbNCK");
+pub const SPL_NOOP_ID: Pubkey = pubkey!("noopb9bkMVfRPU8AsbpTUg8AQkHtKwMYZiFUjNRtMmV");
+pub const MPL_TOKEN_METADATA_ID: Pubkey = pubkey!("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
 
 // NOTE: Devnet program id
 declare_id!("RWAwMRRTxAsRhaWPSj4MS2yrVAPbySsCDQAf9s5dyjr");
 
-const RAFFLE_SEED: &[u8] = b"raffle";
-const TICKET_SEED: &[u8] = b"ticket";
-const SLOTS_SEED: &[u8] = b"slots";
+pub const RAFFLE_SEED: &[u8] = b"raffle";
+
+/// Current `Raffle` account layout version (see `migrate_raffle`).
+pub const RAFFLE_VERSION: u8 = 2;
+pub const TICKET_SEED: &[u8] = b"ticket";
+pub const SLOTS_SEED: &[u8] = b"slots";
+pub const ALLOCATION_SEED: &[u8] = b"allocation";
+pub const TICKET_MINT_SEED: &[u8] = b"ticket_mint";
+/// Symbol of tokenized ticket NFTs (see `tokenize_ticket`).
+pub const TICKET_NFT_SYMBOL: &str = "RTIX";
 
 // pub const LIGHT_CPI_SIGNER: CpiSigner =
 //     derive_light_cpi_signer!("RWAwMRRTxAsRhaWPSj4MS2yrVAPbySsCDQAf9s5dyjr");
@@ -43,7 +56,45 @@ pub const BACKEND_SIGNER: Pubkey = pubkey!("2mdvoXMrxTPyqq9ETxAf7YLgLU7GHdefR88S
 pub const PRIZE_COLLECTION_MINT: Pubkey = pubkey!("Co11ectionMintAddress1111111111111111111111");
 
 // Collection authority PDA seed
-const COLLECTION_AUTHORITY_SEED: &[u8] = b"collection_authority";
+pub const COLLECTION_AUTHORITY_SEED: &[u8] = b"collection_authority";
+
+// Refund tickets (MRFTs, see docs/REFUND_TICKET_SPEC.md). The tree authority PDA is the
+// Bubblegum tree delegate of every MRFT tree and the delegated authority of the MRFT collection.
+// TODO: Replace with the MRFT collection mint once it is created
+pub const MRFT_COLLECTION_MINT: Pubkey = pubkey!("MRFTCo11ectionMint1111111111111111111111111");
+pub const MRFT_TREE_AUTHORITY_SEED: &[u8] = b"mrft_tree_authority";
+pub const MRFT_NAME: &str = "MOGA Raffle Refund Ticket";
+pub const MRFT_SYMBOL: &str = "MRFT";
+pub const MRFT_METADATA_URI: &str = "https://mogate.io/mrft";
+pub const MRFT_SELLER_FEE_BASIS_POINTS: u16 = 0;            // royalties off until marketplace testing
+
+// MRFT buy-back (`buy_back_mrfts`): the platform refund vault is the `[REFUND_VAULT_SEED]` PDA's
+// token account. Past the grace period after the failed raffle's deadline, a haircut of
+// `MRFT_HAIRCUT_BPS_PER_DAY` accrues up to `MRFT_MAX_HAIRCUT_BPS`; set the rate to 0 to disable it.
+pub const REFUND_VAULT_SEED: &[u8] = b"refund_vault";
+// Platform authority (`PlatformConfig`): withdraws what the refund vault keeps (haircuts, swept backing)
+pub const PLATFORM_CONFIG_SEED: &[u8] = b"platform_config";
+pub const MRFT_HAIRCUT_GRACE_SECS: i64 = 90 * 24 * 60 * 60;
+pub const MRFT_HAIRCUT_BPS_PER_DAY: u64 = 5;
+pub const MRFT_MAX_HAIRCUT_BPS: u64 = 1_000;
+// Backing of MRFTs still unredeemed this long after the failed raffle's deadline can be swept
+// into the refund vault (`sweep_mrft_backing`) so the raffle can be closed.
+pub const MRFT_BACKING_SWEEP_DELAY_SECS: i64 = 365 * 24 * 60 * 60;
+
+// Metaplex limits for `PrizeMetadata` (see `claim_prize_mint`)
+pub const MAX_PRIZE_NAME_LEN: usize = 32;
+pub const MAX_PRIZE_SYMBOL_LEN: usize = 10;
+pub const MAX_PRIZE_URI_LEN: usize = 200;
+pub const MAX_PRIZE_CREATORS: usize = 5;
+
+// Keeper bounties paid from the per-raffle crank reserve (lamports held by the raffle PDA)
+pub const KEEPER_BOUNTY_LAMPORTS: u64 = 10_000;             // draw request / refund transition
+pub const KEEPER_REFUND_BOUNTY_LAMPORTS: u64 = 2_000;       // per Ticket account refunded by refund_batch
+
+// Winner claim window: a winner who hasn't called `claim_win` within it can be forfeited
+// (`forfeit_winner`) and the raffle redrawn without them, at most `MAX_WINNER_FORFEITS` times.
+pub const DEFAULT_CLAIM_WINDOW_SECS: i64 = 30 * 24 * 60 * 60;
+pub const MAX_WINNER_FORFEITS: usize = 4;
 
 #[cfg_attr(not(feature = "arcium"), program)]
 #[cfg_attr(feature = "arcium", arcium_program)]
@@ -58,10 +109,13 @@ pub mod rwa_raffle {
         required_tickets: u64,
         deadline_unix_ts: i64,
         auto_draw: bool,
-        ticket_mode: u8, // 0=disabled, 1=accept_without_burn, 2=require_burn
+        ticket_mode: u8, // MRFT joins: 0=disabled, 1=accept_without_burn, 2=require_burn
+        reveal_time_unix_ts: Option<i64>, // Scheduled reveal: no draw before this time
+        crank_reserve_lamports: u64,  // Funds keeper bounties for permissionless cranks
     ) -> Result<()> {
         require!(required_tickets > 0, RaffleError::InvalidAmount);
         require!(deadline_unix_ts > Clock::get()?.unix_timestamp, RaffleError::InvalidDeadline);
+        validate_reveal_time(reveal_time_unix_ts, deadline_unix_ts, Clock::get()?.unix_timestamp)?;
 
         // Get keys before mutable borrow
         let raffle_key = ctx.accounts.raffle.key();
@@ -70,6 +124,7 @@ pub mod rwa_raffle {
         let escrow_key = ctx.accounts.escrow_ata.key();
 
         let raffle = &mut ctx.accounts.raffle;
+        raffle.version = RAFFLE_VERSION;
         raffle.organizer = organizer_key;
         raffle.mint = mint_key;
         raffle.escrow = escrow_key;
@@ -82,18 +137,36 @@ pub mod rwa_raffle {
         raffle.proceeds_collected = false;
         raffle.auto_draw = auto_draw;
         raffle.ticket_mode = ticket_mode;
+        raffle.reveal_time_unix_ts = reveal_time_unix_ts;
+        raffle.crank_reserve = crank_reserve_lamports;
+        raffle.draw_requested = false;
+        raffle.claim_window_secs = DEFAULT_CLAIM_WINDOW_SECS;
         raffle.bump = ctx.bumps.raffle;
 
         // Basic invariants for escrow
         require_keys_eq!(ctx.accounts.escrow_ata.mint, mint_key);
         require_keys_eq!(ctx.accounts.escrow_ata.owner, raffle_key);
 
-        emit!(RaffleInitialized {
+        fund_crank_reserve(
+            &ctx.accounts.organizer,
+            &ctx.accounts.raffle.to_account_info(),
+            &ctx.accounts.system_program,
+            crank_reserve_lamports,
+        )?;
+
+        emit_cpi!(RaffleInitialized {
             raffle: raffle_key,
             organizer: organizer_key,
             mint: mint_key,
+            escrow: escrow_key,
             required_tickets,
             deadline_unix_ts,
+            auto_draw,
+            ticket_mode,
+            refund_mode: 0,
+            prize_collection_mint: Pubkey::default(),
+            reveal_time_unix_ts,
+            crank_reserve_lamports,
         });
 
         // Initialize RaffleSlots PDA for per-slot state
@@ -201,6 +274,7 @@ pub struct CollectProceeds<'info> {
         Ok(())
     }
 
+#[event_cpi]
 #[derive(Accounts)]
 #[instruction(required_tickets: u64, deadline_unix_ts: i64, permit_nonce: [u8; 16], permit_expiry_unix_ts: i64, auto_draw: bool, ticket_mode: u8)]
 pub struct InitializeRaffleWithPermit<'info> {
@@ -244,10 +318,18 @@ pub struct InitializeRaffleWithPermit<'info> {
         permit_nonce: [u8; 16],
         permit_expiry_unix_ts: i64,
         auto_draw: bool,
-        ticket_mode: u8,              // 0=disabled, 1=accept_without_burn, 2=require_burn
+        ticket_mode: u8,              // MRFT joins: 0=disabled, 1=accept_without_burn, 2=require_burn
         prize_collection_mint: Pubkey, // Collection mint for post-mint prizes (organizer provides)
         refund_mode: u8,              // 0=USDC refund, 1=MRFT mint, 2=both (user choice)
+        reveal_time_unix_ts: Option<i64>, // Scheduled reveal: no draw before this time
+        crank_reserve_lamports: u64,  // Funds keeper bounties for permissionless cranks
+        prize_metadata: PrizeMetadata, // Name/symbol/URI/creators for post-mint prizes
+        mrft_collection: Pubkey,      // MRFTs join_with_ticket accepts (default = MRFT_COLLECTION_MINT)
     ) -> Result<()> {
+        validate_reveal_time(reveal_time_unix_ts, deadline_unix_ts, Clock::get()?.unix_timestamp)?;
+        prize_metadata.validate()?;
+        let prize_metadata_digest = prize_metadata_hash(&prize_metadata);
+
         // Get keys before mutable borrow
         let raffle_key = ctx.accounts.raffle.key();
         let organizer_key = ctx.accounts.organizer.key();
@@ -255,6 +337,7 @@ pub struct InitializeRaffleWithPermit<'info> {
         let escrow_key = ctx.accounts.escrow_ata.key();
         
         let raffle = &mut ctx.accounts.raffle;
+        raffle.version = RAFFLE_VERSION;
         raffle.organizer = organizer_key;
         raffle.mint = mint_key;
         raffle.escrow = escrow_key;
@@ -269,17 +352,30 @@ pub struct InitializeRaffleWithPermit<'info> {
         raffle.ticket_mode = ticket_mode;
         raffle.prize_collection_mint = prize_collection_mint;
         raffle.refund_mode = refund_mode;
+        raffle.reveal_time_unix_ts = reveal_time_unix_ts;
+        raffle.crank_reserve = crank_reserve_lamports;
+        raffle.draw_requested = false;
+        raffle.prize_metadata = prize_metadata;
+        raffle.mrft_collection = mrft_collection;
+        raffle.claim_window_secs = DEFAULT_CLAIM_WINDOW_SECS;
         raffle.bump = ctx.bumps.raffle;
 
         require_keys_eq!(ctx.accounts.escrow_ata.mint, mint_key);
         require_keys_eq!(ctx.accounts.escrow_ata.owner, raffle_key);
 
-        emit!(RaffleInitialized {
+        emit_cpi!(RaffleInitialized {
             raffle: raffle_key,
             organizer: organizer_key,
             mint: mint_key,
+            escrow: escrow_key,
             required_tickets,
             deadline_unix_ts,
+            auto_draw,
+            ticket_mode,
+            refund_mode,
+            prize_collection_mint,
+            reveal_time_unix_ts,
+            crank_reserve_lamports,
         });
 
         // Initialize RaffleSlots PDA for per-slot state
@@ -295,7 +391,8 @@ pub struct InitializeRaffleWithPermit<'info> {
 
         // Build canonical permit message (binary):
         // b"RWA_RAFFLE_PERMIT" || organizer(32) || nonce(16) || expiry(i64) ||
-        // required_tickets(u64) || deadline(i64) || program_id(32) || auto_draw(u8) || ticket_mode(u8)
+        // required_tickets(u64) || deadline(i64) || program_id(32) || auto_draw(u8) || ticket_mode(u8) ||
+        // reveal_time(i64, 0 = no scheduled reveal) || prize_metadata_hash(32) || mrft_collection(32)
         let mut expected_msg: Vec<u8> = b"RWA_RAFFLE_PERMIT".to_vec();
         expected_msg.extend_from_slice(raffle.organizer.as_ref());
         expected_msg.extend_from_slice(&permit_nonce);
@@ -305,6 +402,9 @@ pub struct InitializeRaffleWithPermit<'info> {
         expected_msg.extend_from_slice(crate::ID.as_ref());
         expected_msg.push(if raffle.auto_draw { 1 } else { 0 });
         expected_msg.push(raffle.ticket_mode);
+        expected_msg.extend_from_slice(&reveal_time_unix_ts.unwrap_or(0).to_le_bytes());
+        expected_msg.extend_from_slice(&prize_metadata_digest);
+        expected_msg.extend_from_slice(mrft_collection.as_ref());
 
         // Scan instructions sysvar for an ed25519 verify ix that matches
         use anchor_lang::solana_program::{ed25519_program, sysvar::instructions};
@@ -319,28 +419,7 @@ pub struct InitializeRaffleWithPermit<'info> {
             let ix = loaded.unwrap();
             if ix.program_id == ed25519_program::id() {
                 // Parse ed25519 instruction data (single signature format)
-                let data = ix.data.as_slice();
-                if data.len() < 16 { idx += 1; continue; }
-                let num = data[0] as usize;
-                if num != 1 { idx += 1; continue; }
-                // Offsets are u16 LE starting at byte 2
-                let sig_off = u16::from_le_bytes([data[2], data[3]]) as usize;
-                let sig_len = u16::from_le_bytes([data[4], data[5]]) as usize;
-                let pk_off = u16::from_le_bytes([data[6], data[7]]) as usize;
-                let pk_len = u16::from_le_bytes([data[8], data[9]]) as usize;
-                let msg_off = u16::from_le_bytes([data[10], data[11]]) as usize;
-                let msg_len = u16::from_le_bytes([data[12], data[13]]) as usize;
-                let msg_acc_idx = u16::from_le_bytes([data[14], data[15]]);
-
-                // We only accept inline message (not from another account)
-                if msg_acc_idx != u16::MAX { idx += 1; continue; }
-                // Bounds checks
-                if pk_len != 32 || sig_len != 64 { idx += 1; continue; }
-                if pk_off.checked_add(pk_len).unwrap_or(usize::MAX) > data.len() { idx += 1; continue; }
-                if msg_off.checked_add(msg_len).unwrap_or(usize::MAX) > data.len() { idx += 1; continue; }
-
-                let pk_bytes = &data[pk_off..pk_off+pk_len];
-                let msg_bytes = &data[msg_off..msg_off+msg_len];
+                let Some((pk_bytes, msg_bytes)) = ed25519_ix_parts(&ix.data) else { idx += 1; continue; };
 
                 if pk_bytes == BACKEND_SIGNER.as_ref() && msg_bytes == expected_msg.as_slice() {
                     found = true;
@@ -350,6 +429,13 @@ pub struct InitializeRaffleWithPermit<'info> {
             idx += 1;
         }
         require!(found, RaffleError::PermitInvalid);
+
+        fund_crank_reserve(
+            &ctx.accounts.organizer,
+            &ctx.accounts.raffle.to_account_info(),
+            &ctx.accounts.system_program,
+            crank_reserve_lamports,
+        )?;
         Ok(())
     }
 
@@ -442,24 +528,40 @@ pub struct InitializeRaffleWithPermit<'info> {
     // }
 
     /// [LEGACY] Deposit raw token amount (no swap; assumes payer holds the escrow mint, e.g. USDC) and
-    /// receive a ticket range [start, start+count-1]. For devnet/legacy tests. Clients must pass
-    /// `start_index = raffle.next_ticket_index` observed just before sending the transaction.
+    /// receive a ticket range [start, start+count-1]. For devnet/legacy tests. `start_index` must
+    /// begin a free ticket range in `RaffleSlots`; slot joins can reserve tickets anywhere, so
+    /// clients read the slots account just before sending and pass `first_free_run(count)`.
+    /// `raffle.next_ticket_index` tracks the lowest free ticket and is a valid start whenever
+    /// nothing was joined past it.
+    ///
+    /// The payer funds the deposit while `beneficiary` owns the ticket (pass the payer's own key
+    /// for a regular purchase). Presale allocations and entry gates apply to the beneficiary, and
+    /// win claims, refunds and ticket rent all follow the owner.
     pub fn deposit(
         ctx: Context<Deposit>,
         amount: u64,
         start_index: u64,
+        beneficiary: Pubkey,
     ) -> Result<()> {
         let clock = Clock::get()?;
         let raffle = &mut ctx.accounts.raffle;
         require!(raffle.status == RaffleStatus::Selling as u8, RaffleError::RaffleNotSelling);
         require!(clock.unix_timestamp <= raffle.deadline, RaffleError::PastDeadline);
         require!(amount > 0, RaffleError::InvalidAmount);
-        require!(start_index == raffle.next_ticket_index, RaffleError::ConcurrentDeposit);
+        require!(raffle.is_prize_secured(), RaffleError::PrizeNotEscrowed);
         let unit = 10u64.pow(ctx.accounts.mint.decimals as u32);
         require!(amount % unit == 0, RaffleError::MustDepositWholeTokens);
         let tickets = amount / unit;
         require!(tickets > 0, RaffleError::InvalidAmount);
         require!(raffle.tickets_sold.saturating_add(tickets) <= raffle.required_tickets, RaffleError::OverSubscription);
+        consume_allocation(raffle, &mut ctx.accounts.allocation, tickets, clock.unix_timestamp)?;
+        check_gate(raffle, &beneficiary, ctx.accounts.gate_token_account.as_ref(), ctx.accounts.gate_metadata.as_ref())?;
+
+        // Range deposits share ticket numbers with slot joins, so reserve them in the bitmap too.
+        // Joins can take tickets past `next_ticket_index`, so any free range is accepted.
+        let slots = &mut ctx.accounts.slots;
+        slots.reserve_deposit(start_index, tickets, beneficiary)?;
+        raffle.next_ticket_index = slots.next_free(raffle.next_ticket_index);
 
         // Transfer tokens from payer into escrow
         let cpi_accounts = TransferChecked {
@@ -474,30 +576,38 @@ pub struct InitializeRaffleWithPermit<'info> {
         // Mint ticket record (in ticket units)
         let ticket = &mut ctx.accounts.ticket;
         ticket.raffle = raffle.key();
-        ticket.owner = ctx.accounts.payer.key();
-        ticket.start = raffle.next_ticket_index;
+        ticket.owner = beneficiary;
+        ticket.start = start_index;
         ticket.count = tickets; // 1 whole token == 1 ticket
         ticket.refunded = false;
         ticket.claimed_win = false;
         ticket.bump = ctx.bumps.ticket;
+        ticket.payer = ctx.accounts.payer.key();
+        raffle.open_tickets = raffle.open_tickets.checked_add(ticket.count).ok_or(RaffleError::Overflow)?;
 
         // Update raffle counters
         raffle.tickets_sold = raffle.tickets_sold.checked_add(tickets).ok_or(RaffleError::Overflow)?;
-        raffle.next_ticket_index = raffle.next_ticket_index.checked_add(tickets).ok_or(RaffleError::Overflow)?;
 
-        emit!(Deposited {
+        emit_cpi!(Deposited {
             raffle: raffle.key(),
+            ticket: ticket.key(),
             owner: ticket.owner,
+            payer: ctx.accounts.payer.key(),
             start: ticket.start,
             count: ticket.count,
+            slots: Vec::new(), // range deposit: slots are [start, start + count)
+            amount_paid: amount,
             tickets_sold: raffle.tickets_sold,
         });
 
         // Auto-transition to Drawing when threshold reached
         if raffle.tickets_sold == raffle.required_tickets {
             raffle.status = RaffleStatus::Drawing as u8;
-            emit!(ThresholdReached { raffle: raffle.key(), supply: raffle.required_tickets });
-            if raffle.auto_draw { emit!(RandomnessRequested { raffle: raffle.key(), supply: raffle.required_tickets }); }
+            emit_cpi!(ThresholdReached { raffle: raffle.key(), supply: raffle.required_tickets });
+            // Scheduled reveals wait for `request_draw` once the reveal time is reached
+            if raffle.auto_draw && raffle.is_reveal_due(clock.unix_timestamp) {
+                emit_cpi!(RandomnessRequested { raffle: raffle.key(), supply: raffle.required_tickets });
+            }
         }
 
         Ok(())
@@ -536,16 +646,14 @@ pub struct InitializeRaffleWithPermit<'info> {
         require!(clock.unix_timestamp <= raffle.deadline, RaffleError::PastDeadline);
         require!(!slots.is_empty(), RaffleError::InvalidAmount);
         require!(slots.len() as u64 + raffle.tickets_sold <= raffle.required_tickets, RaffleError::OverSubscription);
+        require!(raffle.is_prize_secured(), RaffleError::PrizeNotEscrowed);
 
-        // 1. Validate slots are free
-        let slots_acc = &mut ctx.accounts.slots;
-        for &slot_idx in &slots {
-            require!(slot_idx < slots_acc.required_slots, RaffleError::InvalidSlot);
-            let byte_idx = (slot_idx / 8) as usize;
-            let bit_idx = slot_idx % 8;
-            let is_taken = (slots_acc.bitmap[byte_idx] & (1 << bit_idx)) != 0;
-            require!(!is_taken, RaffleError::SlotAlreadyTaken);
-        }
+        // 1. Reserve the slot run (rejects out-of-range, taken, duplicate and scattered slots)
+        let (start, count) = slot_run_to_range(&slots)?;
+        ctx.accounts.slots.reserve_range(start, count, ctx.accounts.payer.key())?;
+        raffle.next_ticket_index = ctx.accounts.slots.next_free(raffle.next_ticket_index);
+        consume_allocation(raffle, &mut ctx.accounts.allocation, count, clock.unix_timestamp)?;
+        check_gate(raffle, &ctx.accounts.payer.key(), ctx.accounts.gate_token_account.as_ref(), ctx.accounts.gate_metadata.as_ref())?;
 
         // 2. Get MOGA/USD price from Pyth oracle and validate slippage
         #[cfg(feature = "pyth-jupiter")]
@@ -663,38 +771,36 @@ pub struct InitializeRaffleWithPermit<'info> {
             ctx.accounts.usdc_mint.decimals,
         )?;
 
-        // 5. Reserve slots in bitmap
-        for &slot_idx in &slots {
-            let byte_idx = (slot_idx / 8) as usize;
-            let bit_idx = slot_idx % 8;
-            slots_acc.bitmap[byte_idx] |= 1 << bit_idx;
-            slots_acc.owners[slot_idx as usize] = ctx.accounts.payer.key();
-        }
-
-        // 6. Mint ticket record
+        // 5. Mint ticket record
         let ticket = &mut ctx.accounts.ticket;
         ticket.raffle = raffle.key();
         ticket.owner = ctx.accounts.payer.key();
-        ticket.start = slots[0] as u64 + 1; // 1-based ticket numbers
-        ticket.count = slots.len() as u64;
+        ticket.start = start; // 1-based ticket numbers
+        ticket.count = count;
         ticket.refunded = false;
         ticket.claimed_win = false;
         ticket.bump = ctx.bumps.ticket;
+        ticket.payer = ctx.accounts.payer.key();
+        raffle.open_tickets = raffle.open_tickets.checked_add(ticket.count).ok_or(RaffleError::Overflow)?;
 
-        // 7. Update raffle counters
+        // 6. Update raffle counters
         raffle.tickets_sold = raffle.tickets_sold.checked_add(slots.len() as u64).ok_or(RaffleError::Overflow)?;
 
-        emit!(Deposited {
+        emit_cpi!(Deposited {
             raffle: raffle.key(),
+            ticket: ticket.key(),
             owner: ticket.owner,
+            payer: ctx.accounts.payer.key(),
             start: ticket.start,
             count: ticket.count,
+            slots: slots.clone(),
+            amount_paid: usdc_amount,
             tickets_sold: raffle.tickets_sold,
         });
 
         if raffle.tickets_sold == raffle.required_tickets {
             raffle.status = RaffleStatus::Drawing as u8;
-            emit!(ThresholdReached { raffle: raffle.key(), supply: raffle.required_tickets });
+            emit_cpi!(ThresholdReached { raffle: raffle.key(), supply: raffle.required_tickets });
         }
 
         Ok(())
@@ -703,9 +809,14 @@ pub struct InitializeRaffleWithPermit<'info> {
     /// **Join raffle with MRFT (refund ticket NFTs).**
     ///
     /// # What it does
-    /// - Validates MRFT NFTs belong to the approved collection
-    /// - Burns MRFT NFTs via Bubblegum tree authority
-    /// - Reserves requested slots in `RaffleSlots`
+    /// - Rebuilds each MRFT's leaf from `mrfts` (see `mrft_metadata`) in the raffle's
+    ///   accepted MRFT collection, so only genuine MRFTs worth one ticket match
+    /// - `ticket_mode == 2` (require_burn): burns the MRFTs via Bubblegum
+    /// - `ticket_mode == 1` (accept_without_burn): moves the MRFTs to the tree authority,
+    ///   which takes them out of circulation without destroying them
+    /// - Moves the USDC backing the MRFTs from `origin_raffle`'s escrow into this raffle's,
+    ///   so both escrows stay fully backed (see `migrate_mrft_backing`)
+    /// - Reserves requested slots in `RaffleSlots` (one MRFT per slot)
     /// - Mints ticket record
     ///
     /// # When to call
@@ -713,142 +824,273 @@ pub struct InitializeRaffleWithPermit<'info> {
     /// - Raffle is in `Selling` status and before deadline
     ///
     /// # Security
-    /// - Collection verification prevents fake tickets
-    /// - Burn authority check via Bubblegum
+    /// - Bubblegum checks every rebuilt leaf against its proof (remaining accounts, `proof_len`
+    ///   nodes per MRFT in order), so the MRFTs must be the payer's and in the collection
+    /// - The tree authority signs as leaf delegate while it still is one; otherwise the payer
+    ///   signs as leaf owner
     /// - Slot reservation prevents double-booking
     ///
     /// # Feature-gated
     /// - Requires `bubblegum` feature flag
-    /// - TODO: Implement Bubblegum burn CPI logic
+    /// - All MRFTs of one call must live in `merkle_tree` and come from `origin_raffle`
     #[cfg(feature = "bubblegum")]
-    pub fn join_with_ticket(
-        ctx: Context<JoinWithTicket>,
+    pub fn join_with_ticket<'info>(
+        ctx: Context<'_, '_, 'info, 'info, JoinWithTicket<'info>>,
         slots: Vec<u32>,
-        _nft_proofs: Vec<u8>, // Bubblegum proofs (placeholder)
+        mrfts: Vec<MrftLeaf>,
     ) -> Result<()> {
         let clock = Clock::get()?;
         let raffle = &mut ctx.accounts.raffle;
         require!(raffle.status == RaffleStatus::Selling as u8, RaffleError::RaffleNotSelling);
         require!(clock.unix_timestamp <= raffle.deadline, RaffleError::PastDeadline);
+        require!(raffle.ticket_mode == 1 || raffle.ticket_mode == 2, RaffleError::MrftNotAccepted);
         require!(!slots.is_empty(), RaffleError::InvalidAmount);
+        require!(mrfts.len() == slots.len(), RaffleError::TicketBurnRequired);
         require!(slots.len() as u64 + raffle.tickets_sold <= raffle.required_tickets, RaffleError::OverSubscription);
+        require!(raffle.is_prize_secured(), RaffleError::PrizeNotEscrowed);
+
+        // 1. Reserve the slot run (rejects out-of-range, taken, duplicate and scattered slots)
+        let (start, count) = slot_run_to_range(&slots)?;
+        ctx.accounts.slots.reserve_range(start, count, ctx.accounts.payer.key())?;
+        raffle.next_ticket_index = ctx.accounts.slots.next_free(raffle.next_ticket_index);
+        consume_allocation(raffle, &mut ctx.accounts.allocation, count, clock.unix_timestamp)?;
+        check_gate(raffle, &ctx.accounts.payer.key(), ctx.accounts.gate_token_account.as_ref(), ctx.accounts.gate_metadata.as_ref())?;
+
+        // 2. Verify the MRFTs and take them out of circulation
+        let origin_key = ctx.accounts.origin_raffle.key();
+        require!(mrfts.iter().all(|mrft| mrft.raffle == origin_key), RaffleError::InvalidMrft);
+        let unit = 10u64.pow(ctx.accounts.mint.decimals as u32);
+        retire_mrfts(
+            &ctx.accounts.bubblegum,
+            ctx.accounts.payer.to_account_info(),
+            &mrfts,
+            ctx.remaining_accounts,
+            &raffle.accepted_mrft_collection(),
+            unit,
+            raffle.ticket_mode == 2,
+        )?;
 
-        // 1. Validate slots are free
-        let slots_acc = &mut ctx.accounts.slots;
-        for &slot_idx in &slots {
-            require!(slot_idx < slots_acc.required_slots, RaffleError::InvalidSlot);
-            let byte_idx = (slot_idx / 8) as usize;
-            let bit_idx = slot_idx % 8;
-            let is_taken = (slots_acc.bitmap[byte_idx] & (1 << bit_idx)) != 0;
-            require!(!is_taken, RaffleError::SlotAlreadyTaken);
-        }
-
-        // 2. Verify MRFT collection and burn NFTs when required
-        if raffle.ticket_mode == 2 {
-            #[cfg(feature = "bubblegum")]
-            {
-                // Bubblegum burn CPI implementation
-                use mpl_bubblegum::instructions::BurnCpiBuilder;
-                use anchor_lang::solana_program::program::invoke;
-                
-                // Parse proof data (format: root[32] || data_hash[32] || creator_hash[32] || nonce[8] || index[4])
-                require!(_nft_proofs.len() >= 108, RaffleError::InvalidProof);
-                
-                let mut root = [0u8; 32];
-                let mut data_hash = [0u8; 32];
-                let mut creator_hash = [0u8; 32];
-                root.copy_from_slice(&_nft_proofs[0..32]);
-                data_hash.copy_from_slice(&_nft_proofs[32..64]);
-                creator_hash.copy_from_slice(&_nft_proofs[64..96]);
-                let nonce = u64::from_le_bytes(_nft_proofs[96..104].try_into().unwrap());
-                let index = u32::from_le_bytes(_nft_proofs[104..108].try_into().unwrap());
-                
-                // Verify collection mint matches expected MRFT collection
-                // TODO: Add collection_mint to raffle config or hardcode expected collection
-                // require_keys_eq!(ctx.accounts.collection_mint.key(), EXPECTED_MRFT_COLLECTION);
-                
-                // Burn the compressed NFT
-                BurnCpiBuilder::new(&ctx.accounts.bubblegum_program)
-                    .tree_config(&ctx.accounts.tree_config)
-                    .leaf_owner(&ctx.accounts.payer)
-                    .leaf_delegate(&ctx.accounts.payer)
-                    .merkle_tree(&ctx.accounts.merkle_tree)
-                    .log_wrapper(&ctx.accounts.log_wrapper)
-                    .compression_program(&ctx.accounts.compression_program)
-                    .system_program(&ctx.accounts.system_program)
-                    .root(root)
-                    .data_hash(data_hash)
-                    .creator_hash(creator_hash)
-                    .nonce(nonce)
-                    .index(index)
-                    .invoke()?;
-            }
-            #[cfg(not(feature = "bubblegum"))]
-            {
-                // Without bubblegum feature, just verify proofs are provided
-                require!(!_nft_proofs.is_empty(), RaffleError::TicketBurnRequired);
-            }
-        }
-
-        // 4. Reserve slots in bitmap
-        for &slot_idx in &slots {
-            let byte_idx = (slot_idx / 8) as usize;
-            let bit_idx = slot_idx % 8;
-            slots_acc.bitmap[byte_idx] |= 1 << bit_idx;
-            slots_acc.owners[slot_idx as usize] = ctx.accounts.payer.key();
-        }
+        // 3. Bring their USDC backing over from the origin raffle's escrow
+        let amount = migrate_mrft_backing(
+            &mut ctx.accounts.origin_raffle,
+            &ctx.accounts.origin_escrow,
+            &ctx.accounts.escrow_ata,
+            &ctx.accounts.mint,
+            &ctx.accounts.token_program,
+            count,
+        )?;
 
-        // 5. Mint ticket record
+        // 4. Mint ticket record
         let ticket = &mut ctx.accounts.ticket;
         ticket.raffle = raffle.key();
         ticket.owner = ctx.accounts.payer.key();
-        ticket.start = slots[0] as u64 + 1; // 1-based ticket numbers
-        ticket.count = slots.len() as u64;
+        ticket.start = start; // 1-based ticket numbers
+        ticket.count = count;
         ticket.refunded = false;
         ticket.claimed_win = false;
         ticket.bump = ctx.bumps.ticket;
+        ticket.payer = ctx.accounts.payer.key();
+        raffle.open_tickets = raffle.open_tickets.checked_add(ticket.count).ok_or(RaffleError::Overflow)?;
 
-        // 6. Update raffle counters
+        // 5. Update raffle counters (the migrated USDC funds these tickets like a deposit)
         raffle.tickets_sold = raffle.tickets_sold.checked_add(slots.len() as u64).ok_or(RaffleError::Overflow)?;
 
-        emit!(Deposited {
+        emit_cpi!(MrftBackingMigrated { origin_raffle: origin_key, raffle: raffle.key(), ticket: ticket.key(), count, amount });
+
+        emit_cpi!(Deposited {
             raffle: raffle.key(),
+            ticket: ticket.key(),
             owner: ticket.owner,
+            payer: ctx.accounts.payer.key(),
             start: ticket.start,
             count: ticket.count,
+            slots: slots.clone(),
+            amount_paid: 0, // paid with MRFTs; their backing is in `MrftBackingMigrated`
             tickets_sold: raffle.tickets_sold,
         });
 
         if raffle.tickets_sold == raffle.required_tickets {
             raffle.status = RaffleStatus::Drawing as u8;
-            emit!(ThresholdReached { raffle: raffle.key(), supply: raffle.required_tickets });
+            emit_cpi!(ThresholdReached { raffle: raffle.key(), supply: raffle.required_tickets });
         }
 
         Ok(())
     }
 
-    /// Organizer requests randomness (offchain worker watches event and uses Arcium).
+    /// **Sell MRFTs back to the platform for USDC.**
+    ///
+    /// # What it does
+    /// - Burns the holder's MRFTs (same leaf rebuild and proofs as `join_with_ticket`)
+    /// - Moves their backing from `origin_raffle`'s escrow into the platform refund vault
+    /// - Pays the holder `usdc_value` per MRFT from the vault, minus the time-based haircut
+    ///   (`mrft_buyback_haircut`), which stays in the vault until the platform authority
+    ///   withdraws it (`withdraw_refund_vault`)
+    ///
+    /// This gives every MRFT a hard price floor on secondary markets.
+    ///
+    /// # Feature-gated
+    /// - Requires `bubblegum` feature flag
+    /// - All MRFTs of one call must live in `merkle_tree` and come from `origin_raffle`
+    #[cfg(feature = "bubblegum")]
+    pub fn buy_back_mrfts<'info>(
+        ctx: Context<'_, '_, 'info, 'info, BuyBackMrfts<'info>>,
+        mrfts: Vec<MrftLeaf>,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        require!(!mrfts.is_empty(), RaffleError::InvalidAmount);
+        let origin_key = ctx.accounts.origin_raffle.key();
+        require!(mrfts.iter().all(|mrft| mrft.raffle == origin_key), RaffleError::InvalidMrft);
+
+        // 1. Burn the MRFTs (the vault only buys platform MRFTs)
+        let unit = 10u64.pow(ctx.accounts.mint.decimals as u32);
+        retire_mrfts(
+            &ctx.accounts.bubblegum,
+            ctx.accounts.holder.to_account_info(),
+            &mrfts,
+            ctx.remaining_accounts,
+            &MRFT_COLLECTION_MINT,
+            unit,
+            true,
+        )?;
+
+        // 2. Their backing leaves the failed raffle for the vault
+        let count = mrfts.len() as u64;
+        let backing = migrate_mrft_backing(
+            &mut ctx.accounts.origin_raffle,
+            &ctx.accounts.origin_escrow,
+            &ctx.accounts.refund_vault,
+            &ctx.accounts.mint,
+            &ctx.accounts.token_program,
+            count,
+        )?;
+
+        // 3. Pay the holder from the vault
+        let age = clock.unix_timestamp.saturating_sub(ctx.accounts.origin_raffle.deadline);
+        let haircut = mrft_buyback_haircut(backing, age);
+        let amount = backing - haircut;
+        let seeds: &[&[u8]] = &[REFUND_VAULT_SEED, &[ctx.bumps.vault_authority]];
+        token::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.refund_vault.to_account_info(),
+                    to: ctx.accounts.holder_ata.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    authority: ctx.accounts.vault_authority.to_account_info(),
+                },
+                &[seeds],
+            ),
+            amount,
+            ctx.accounts.mint.decimals,
+        )?;
+
+        emit_cpi!(MrftsBoughtBack { origin_raffle: origin_key, holder: ctx.accounts.holder.key(), count, amount, haircut });
+        Ok(())
+    }
+
+    /// **Set up the platform authority; only the program's upgrade authority may call it, once.**
+    pub fn initialize_platform_config(ctx: Context<InitializePlatformConfig>, authority: Pubkey) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.authority = authority;
+        config.bump = ctx.bumps.config;
+        emit_cpi!(PlatformAuthoritySet { authority });
+        Ok(())
+    }
+
+    /// **Hand the platform authority to another wallet (current authority only).**
+    pub fn set_platform_authority(ctx: Context<SetPlatformAuthority>, new_authority: Pubkey) -> Result<()> {
+        ctx.accounts.config.authority = new_authority;
+        emit_cpi!(PlatformAuthoritySet { authority: new_authority });
+        Ok(())
+    }
+
+    /// **Withdraw from the platform refund vault (platform authority only).**
+    ///
+    /// The vault keeps the `buy_back_mrfts` haircuts and the backing swept by
+    /// `sweep_mrft_backing`; this is the only way out for them. Topping the vault up needs
+    /// no instruction: any token transfer to it will do.
+    pub fn withdraw_refund_vault(ctx: Context<WithdrawRefundVault>, amount: u64) -> Result<()> {
+        require!(amount > 0, RaffleError::InvalidAmount);
+        let seeds: &[&[u8]] = &[REFUND_VAULT_SEED, &[ctx.bumps.vault_authority]];
+        token::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.refund_vault.to_account_info(),
+                    to: ctx.accounts.destination.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    authority: ctx.accounts.vault_authority.to_account_info(),
+                },
+                &[seeds],
+            ),
+            amount,
+            ctx.accounts.mint.decimals,
+        )?;
+
+        emit_cpi!(RefundVaultWithdrawn { destination: ctx.accounts.destination.key(), amount });
+        Ok(())
+    }
+
+    /// Request randomness (offchain worker watches event and uses Arcium).
+    /// Permissionless: any keeper may call once the raffle is `Drawing`. The first
+    /// request pays `KEEPER_BOUNTY_LAMPORTS` from the crank reserve; later calls only
+    /// re-emit the event. With a scheduled reveal, this is rejected until `reveal_time_unix_ts`.
     pub fn request_draw(ctx: Context<RequestDraw>) -> Result<()> {
+        let clock = Clock::get()?;
         let raffle = &mut ctx.accounts.raffle;
-        require!(ctx.accounts.organizer.key() == raffle.organizer, RaffleError::Unauthorized);
         require!(raffle.status == RaffleStatus::Drawing as u8, RaffleError::WrongStatus);
-        emit!(RandomnessRequested { raffle: raffle.key(), supply: raffle.required_tickets });
+        require!(raffle.is_reveal_due(clock.unix_timestamp), RaffleError::RevealTimeNotReached);
+        emit_cpi!(RandomnessRequested { raffle: raffle.key(), supply: raffle.required_tickets });
+
+        if !raffle.draw_requested {
+            raffle.draw_requested = true;
+            let amount = pay_keeper_bounty(raffle, &ctx.accounts.caller.to_account_info(), KEEPER_BOUNTY_LAMPORTS)?;
+            emit_cpi!(KeeperBountyPaid { raffle: raffle.key(), keeper: ctx.accounts.caller.key(), amount });
+        }
+        Ok(())
+    }
+
+    /// Move an unfilled raffle from `Selling` to `Refunding` once the deadline has passed.
+    /// Permissionless crank; pays `KEEPER_BOUNTY_LAMPORTS` from the crank reserve.
+    pub fn start_refunding(ctx: Context<StartRefunding>) -> Result<()> {
+        let clock = Clock::get()?;
+        let raffle = &mut ctx.accounts.raffle;
+        require!(raffle.status == RaffleStatus::Selling as u8, RaffleError::WrongStatus);
+        require!(clock.unix_timestamp > raffle.deadline, RaffleError::NotRefundableYet);
+        raffle.status = RaffleStatus::Refunding as u8;
+        emit_cpi!(RefundingStarted { raffle: raffle.key(), tickets_sold: raffle.tickets_sold });
+
+        let amount = pay_keeper_bounty(raffle, &ctx.accounts.caller.to_account_info(), KEEPER_BOUNTY_LAMPORTS)?;
+        emit_cpi!(KeeperBountyPaid { raffle: raffle.key(), keeper: ctx.accounts.caller.key(), amount });
         Ok(())
     }
 
     /// Offchain settles with winner ticket index in [1..required_tokens].
+    /// Only the platform authority (`PlatformConfig`) may settle, as the draw oracle.
     /// Proof verification via Arcium will be added in a later iteration.
+    /// On a redraw (see `forfeit_winner`) the ticket's owner in `slots` must not be one of
+    /// the `forfeited_owners`; the winner then has `claim_window_secs` to call `claim_win`.
     pub fn settle_draw(ctx: Context<SettleDraw>, winner_ticket: u64) -> Result<()> {
+        let clock = Clock::get()?;
         let raffle = &mut ctx.accounts.raffle;
         require!(raffle.status == RaffleStatus::Drawing as u8, RaffleError::WrongStatus);
+        require!(raffle.is_reveal_due(clock.unix_timestamp), RaffleError::RevealTimeNotReached);
         require!(winner_ticket >= 1 && winner_ticket <= raffle.required_tickets, RaffleError::InvalidWinner);
+        if !raffle.forfeited_owners.is_empty() {
+            let owner = ctx.accounts.slots.owner_of(winner_ticket).ok_or(RaffleError::InvalidWinner)?;
+            require!(!raffle.forfeited_owners.contains(&owner), RaffleError::ExcludedWinner);
+        }
         raffle.winner_ticket = winner_ticket;
+        raffle.winner_selected_at = clock.unix_timestamp;
         raffle.status = RaffleStatus::Completed as u8;
-        emit!(WinnerSelected { raffle: raffle.key(), winner_ticket });
+        emit_cpi!(WinnerSelected { raffle: raffle.key(), winner_ticket });
         Ok(())
     }
 
     /// Users can claim refund after deadline if threshold not met.
+    /// MRFT-refund raffles (`refund_mode != 0`) mint one MRFT per ticket number in this
+    /// transaction when the `mrft` accounts are passed (see `mint_mrfts`); otherwise they
+    /// emit `RefundTicketsRequested` for the off-chain worker.
     pub fn claim_refund(ctx: Context<ClaimRefund>) -> Result<()> {
         let clock = Clock::get()?;
         let raffle = &mut ctx.accounts.raffle;
@@ -859,22 +1101,67 @@ pub struct InitializeRaffleWithPermit<'info> {
 
         let ticket = &mut ctx.accounts.ticket;
         require!(ticket.raffle == raffle.key(), RaffleError::WrongRaffle);
-        require!(ticket.owner == ctx.accounts.payer.key(), RaffleError::Unauthorized);
+        let holder = ctx.accounts.payer.key();
+        authorize_ticket_holder(ticket, &holder, ctx.accounts.holder_ticket_account.as_ref())?;
         require!(!ticket.refunded, RaffleError::AlreadyRefunded);
 
-        // Mark refunded and emit refund ticket request event for offchain minting.
         ticket.refunded = true;
-        emit!(RefundTicketsRequested {
-            raffle: raffle.key(),
-            owner: ticket.owner,
-            start: ticket.start,
-            count: ticket.count,
-        });
+        raffle.tickets_refunded = raffle.tickets_refunded.checked_add(ticket.count).ok_or(RaffleError::Overflow)?;
+        if raffle.refund_mode == 0 {
+            // USDC refund straight from escrow
+            let amount = refund_ticket_usdc(
+                raffle,
+                ticket,
+                &ctx.accounts.mint,
+                &ctx.accounts.escrow_ata,
+                &ctx.accounts.payer_ata,
+                &ctx.accounts.token_program,
+            )?;
+            emit_cpi!(Refunded { raffle: raffle.key(), ticket: ticket.key(), owner: holder, count: ticket.count, amount });
+        } else if ctx.accounts.mrft.bubblegum_program.is_some() {
+            // Mint one MRFT per ticket number right away
+            let unit = 10u64.pow(ctx.accounts.mint.decimals as u32);
+            let merkle_tree = mint_mrfts(
+                &raffle.key(),
+                ticket,
+                unit,
+                ctx.accounts.payer.to_account_info(),
+                &ctx.accounts.mrft,
+            )?;
+            emit_cpi!(RefundTicketsMinted {
+                raffle: raffle.key(),
+                ticket: ticket.key(),
+                owner: holder,
+                start: ticket.start,
+                count: ticket.count,
+                merkle_tree,
+                usdc_value_per_ticket: unit,
+            });
+        } else {
+            // Emit refund ticket request event for offchain MRFT minting.
+            let unit = 10u64.pow(ctx.accounts.mint.decimals as u32);
+            emit_cpi!(RefundTicketsRequested {
+                raffle: raffle.key(),
+                ticket: ticket.key(),
+                owner: holder,
+                start: ticket.start,
+                count: ticket.count,
+                refund_mode: raffle.refund_mode,
+                usdc_value_per_ticket: unit,
+            });
+        }
         Ok(())
     }
 
-    /// Batch-mark tickets as refunded and emit refund ticket requests for offchain minting.
-    /// Caller passes Ticket accounts as remaining_accounts; safe to call by anyone.
+    /// Batch-refund tickets; safe to call by anyone (keeper crank).
+    ///
+    /// - `refund_mode == 0`: remaining_accounts are `[ticket, owner_usdc_ata]` pairs and each
+    ///   ticket is paid back in USDC from escrow.
+    /// - otherwise: remaining_accounts are Ticket accounts; tickets are marked refunded and
+    ///   refund ticket requests are emitted for offchain minting.
+    ///
+    /// The caller earns `KEEPER_REFUND_BOUNTY_LAMPORTS` per `Ticket` account refunded (not per
+    /// ticket number it covers), paid from the crank reserve.
     pub fn refund_batch<'info>(ctx: Context<'_, '_, 'info, 'info, RefundBatch<'info>>) -> Result<()> {
         let clock = Clock::get()?;
         let raffle = &mut ctx.accounts.raffle;
@@ -883,69 +1170,368 @@ pub struct InitializeRaffleWithPermit<'info> {
         // Enter refunding state
         raffle.status = RaffleStatus::Refunding as u8;
 
-        for acc in ctx.remaining_accounts.iter() {
-            let mut ticket: Account<Ticket> = Account::try_from(acc)?;
+        let usdc_refund = raffle.refund_mode == 0;
+        let stride = if usdc_refund { 2 } else { 1 };
+        require!(ctx.remaining_accounts.len().is_multiple_of(stride), RaffleError::InvalidRemainingAccounts);
+
+        let mut refunded: u64 = 0;
+        for accs in ctx.remaining_accounts.chunks(stride) {
+            let mut ticket: Account<Ticket> = Account::try_from(&accs[0])?;
             if ticket.raffle != raffle.key() { continue; }
             if ticket.refunded { continue; }
+            // Tokenized tickets refund whoever holds the NFT, which only `claim_refund` can prove
+            if ticket.owner == ticket_mint_address(&raffle.key(), ticket.start) { continue; }
             ticket.refunded = true;
-            emit!(RefundTicketsRequested {
-                raffle: raffle.key(),
-                owner: ticket.owner,
-                start: ticket.start,
-                count: ticket.count,
-            });
+            raffle.tickets_refunded = raffle.tickets_refunded.checked_add(ticket.count).ok_or(RaffleError::Overflow)?;
+
+            if usdc_refund {
+                let owner_ata: InterfaceAccount<TokenAccount> = InterfaceAccount::try_from(&accs[1])?;
+                require_keys_eq!(owner_ata.owner, ticket.owner, RaffleError::Unauthorized);
+                let amount = refund_ticket_usdc(
+                    raffle,
+                    &ticket,
+                    &ctx.accounts.mint,
+                    &ctx.accounts.escrow_ata,
+                    &owner_ata,
+                    &ctx.accounts.token_program,
+                )?;
+                emit_cpi!(Refunded { raffle: raffle.key(), ticket: ticket.key(), owner: ticket.owner, count: ticket.count, amount });
+            } else {
+                emit_cpi!(RefundTicketsRequested {
+                    raffle: raffle.key(),
+                    ticket: ticket.key(),
+                    owner: ticket.owner,
+                    start: ticket.start,
+                    count: ticket.count,
+                    refund_mode: raffle.refund_mode,
+                    usdc_value_per_ticket: 10u64.pow(ctx.accounts.mint.decimals as u32),
+                });
+            }
+            ticket.exit(&crate::ID)?;
+            refunded += 1;
+        }
+
+        if refunded > 0 {
+            let bounty = KEEPER_REFUND_BOUNTY_LAMPORTS.checked_mul(refunded).ok_or(RaffleError::Overflow)?;
+            let amount = pay_keeper_bounty(raffle, &ctx.accounts.caller.to_account_info(), bounty)?;
+            emit_cpi!(KeeperBountyPaid { raffle: raffle.key(), keeper: ctx.accounts.caller.key(), amount });
         }
         Ok(())
     }
 
     /// Winner can mark claim on-chain; offchain RWA delivery handled externally.
+    /// Must happen within the raffle's claim window, after which the win can be forfeited.
     pub fn claim_win(ctx: Context<ClaimWin>) -> Result<()> {
+        let clock = Clock::get()?;
         let raffle = &mut ctx.accounts.raffle;
         require!(raffle.status == RaffleStatus::Completed as u8, RaffleError::WrongStatus);
+        require!(!raffle.is_claim_window_expired(clock.unix_timestamp), RaffleError::ClaimWindowExpired);
         let ticket = &mut ctx.accounts.ticket;
         require!(ticket.raffle == raffle.key(), RaffleError::WrongRaffle);
-        require!(ticket.owner == ctx.accounts.winner.key(), RaffleError::Unauthorized);
+        let winner = ctx.accounts.winner.key();
+        authorize_ticket_holder(ticket, &winner, ctx.accounts.holder_ticket_account.as_ref())?;
         require!(!ticket.claimed_win, RaffleError::AlreadyClaimedWin);
 
-        let start = ticket.start;
-        let end = ticket.start.checked_add(ticket.count).ok_or(RaffleError::Overflow)?.saturating_sub(1);
-        require!(raffle.winner_ticket >= start && raffle.winner_ticket <= end, RaffleError::NotWinningTicket);
+        require!(ticket.covers(raffle.winner_ticket), RaffleError::NotWinningTicket);
 
         ticket.claimed_win = true;
-        emit!(WinClaimed { raffle: raffle.key(), owner: ticket.owner, winner_ticket: raffle.winner_ticket });
+        raffle.win_claimed = true;
+        emit_cpi!(WinClaimed { raffle: raffle.key(), ticket: ticket.key(), owner: winner, winner_ticket: raffle.winner_ticket });
+        Ok(())
+    }
+
+    /// **Forfeit a win that wasn't claimed within the claim window and reopen the draw.**
+    ///
+    /// Permissionless crank, paid `KEEPER_BOUNTY_LAMPORTS` from the crank reserve. Records the
+    /// winning ticket's owner in `slots` in `forfeited_owners` and puts the raffle back into
+    /// `Drawing`, so the redraw takes the usual `request_draw` → `settle_draw` path and
+    /// `settle_draw` rejects their tickets. Ticket accounts can't move while a redraw is
+    /// pending, so the forfeited owner can't dodge the exclusion.
+    ///
+    /// A tokenized ticket is owned by its ticket mint in `slots`, and the NFT can change
+    /// hands with a plain token transfer, so its exclusion is per ticket: the mint is recorded
+    /// and only that ticket's range sits out the redraw, while its holder's other tickets stay
+    /// in. Raffles without a claim window (`claim_window_secs == 0`) never forfeit.
+    pub fn forfeit_winner(ctx: Context<ForfeitWinner>) -> Result<()> {
+        let clock = Clock::get()?;
+        let raffle = &mut ctx.accounts.raffle;
+        require!(raffle.status == RaffleStatus::Completed as u8, RaffleError::WrongStatus);
+        require!(!raffle.win_claimed, RaffleError::AlreadyClaimedWin);
+        require!(raffle.is_claim_window_expired(clock.unix_timestamp), RaffleError::ClaimWindowOpen);
+        require!(raffle.forfeited_owners.len() < MAX_WINNER_FORFEITS, RaffleError::TooManyForfeits);
+
+        let winner_ticket = raffle.winner_ticket;
+        let owner = ctx.accounts.slots.owner_of(winner_ticket).ok_or(RaffleError::InvalidWinner)?;
+        raffle.forfeited_owners.push(owner);
+        raffle.winner_ticket = 0;
+        raffle.winner_selected_at = 0;
+        raffle.status = RaffleStatus::Drawing as u8;
+        raffle.draw_requested = false;
+        emit_cpi!(WinnerForfeited { raffle: raffle.key(), winner_ticket, owner });
+
+        let amount = pay_keeper_bounty(raffle, &ctx.accounts.caller.to_account_info(), KEEPER_BOUNTY_LAMPORTS)?;
+        emit_cpi!(KeeperBountyPaid { raffle: raffle.key(), keeper: ctx.accounts.caller.key(), amount });
         Ok(())
     }
 
     /// Set the prize NFT by escrowing a pre-minted NFT into the raffle. This path is used
-    /// for `PreEscrow` prize mode. Future update: enforce Metaplex Verified Collection so
-    /// organizers can only escrow NFTs from their approved collection.
+    /// for `PreEscrow` prize mode.
+    ///
+    /// With the `metaplex` feature the prize must be a 1/1 from the approved collection: its
+    /// metadata (`prize_metadata`) names `raffle.prize_collection_mint` (or
+    /// `PRIZE_COLLECTION_MINT` when unset) as its verified collection, and its master edition
+    /// (`prize_master_edition`) allows no prints. Builds without Metaplex only check decimals.
+    ///
+    /// Programmable NFTs (pNFTs) move through Token Metadata's `TransferV1`, so they also
+    /// need the `pnft` accounts (token records, optional rule set); see `transfer_prize`.
+    ///
+    /// Only allowed while the raffle is selling, so a prize can't appear after the draw.
     pub fn set_prize_nft(ctx: Context<SetPrizeNft>) -> Result<()> {
         let raffle = &mut ctx.accounts.raffle;
         require!(ctx.accounts.organizer.key() == raffle.organizer, RaffleError::Unauthorized);
+        require!(raffle.status == RaffleStatus::Selling as u8, RaffleError::PrizeLocked);
         require!(!raffle.prize_set, RaffleError::PrizeAlreadySet);
         require!(ctx.accounts.prize_mint.decimals == 0, RaffleError::PrizeMustBeNft);
+        verify_prize_nft(
+            raffle,
+            &ctx.accounts.prize_mint.key(),
+            ctx.accounts.prize_metadata.as_ref(),
+            ctx.accounts.prize_master_edition.as_ref(),
+        )?;
         require_keys_eq!(ctx.accounts.prize_escrow.owner, raffle.key());
         require_keys_eq!(ctx.accounts.prize_escrow.mint, ctx.accounts.prize_mint.key());
 
-        let cpi_accounts = TransferChecked {
-            from: ctx.accounts.organizer_prize_ata.to_account_info(),
-            to: ctx.accounts.prize_escrow.to_account_info(),
-            mint: ctx.accounts.prize_mint.to_account_info(),
-            authority: ctx.accounts.organizer.to_account_info(),
-        };
-        let cpi_program = ctx.accounts.token_program.to_account_info();
-        token::transfer_checked(CpiContext::new(cpi_program, cpi_accounts), 1, 0)?;
+        transfer_prize(
+            PrizeMove {
+                from: ctx.accounts.organizer_prize_ata.to_account_info(),
+                from_owner: ctx.accounts.organizer.to_account_info(),
+                to: ctx.accounts.prize_escrow.to_account_info(),
+                to_owner: raffle.to_account_info(),
+                mint: ctx.accounts.prize_mint.to_account_info(),
+                payer: ctx.accounts.organizer.to_account_info(),
+                token_program: ctx.accounts.token_program.to_account_info(),
+            },
+            ctx.accounts.prize_metadata.as_ref(),
+            ctx.accounts.prize_master_edition.as_ref(),
+            &ctx.accounts.pnft,
+            &[],
+        )?;
 
         raffle.prize_mint = ctx.accounts.prize_mint.key();
         raffle.prize_escrow = ctx.accounts.prize_escrow.key();
         raffle.prize_set = true;
         raffle.prize_claimed = false;
 
-        emit!(PrizeSet { raffle: raffle.key(), prize_mint: raffle.prize_mint });
+        emit_cpi!(PrizeSet { raffle: raffle.key(), prize_mint: raffle.prize_mint });
+        Ok(())
+    }
+
+    /// **Escrow a compressed (Bubblegum) NFT prize.**
+    ///
+    /// Lets cheap prizes such as travel credits be raffled without a full mint. The organizer
+    /// transfers the leaf to the raffle PDA or, with `delegate`, keeps it and makes the raffle
+    /// PDA its leaf delegate. Bubblegum checks the leaf against `root` with the proof path
+    /// passed as remaining accounts, so the stored hashes match a real leaf; `claim_prize`
+    /// moves it to the winner. The leaf must not have a delegate other than the organizer.
+    ///
+    /// `raffle.prize_mint` records the cNFT's asset id. A delegated prize is void if the
+    /// organizer moves it, so it doesn't secure prize-backed raffles.
+    #[cfg(feature = "bubblegum")]
+    pub fn set_prize_cnft<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SetPrizeCnft<'info>>,
+        root: [u8; 32],
+        data_hash: [u8; 32],
+        creator_hash: [u8; 32],
+        nonce: u64,
+        index: u32,
+        delegate: bool,
+    ) -> Result<()> {
+        use mpl_bubblegum::instructions::{DelegateCpiBuilder, TransferCpiBuilder};
+        use mpl_bubblegum::utils::get_asset_id;
+
+        let raffle_info = ctx.accounts.raffle.to_account_info();
+        let raffle = &mut ctx.accounts.raffle;
+        require!(raffle.status == RaffleStatus::Selling as u8, RaffleError::PrizeLocked);
+        require!(!raffle.prize_set, RaffleError::PrizeAlreadySet);
+
+        let organizer = ctx.accounts.organizer.to_account_info();
+        let proof: Vec<(&AccountInfo<'info>, bool, bool)> =
+            ctx.remaining_accounts.iter().map(|node| (node, false, false)).collect();
+        if delegate {
+            DelegateCpiBuilder::new(&ctx.accounts.bubblegum_program)
+                .tree_config(&ctx.accounts.tree_config)
+                .leaf_owner(&organizer)
+                .previous_leaf_delegate(&organizer)
+                .new_leaf_delegate(&raffle_info)
+                .merkle_tree(&ctx.accounts.merkle_tree)
+                .log_wrapper(&ctx.accounts.log_wrapper)
+                .compression_program(&ctx.accounts.compression_program)
+                .system_program(&ctx.accounts.system_program)
+                .root(root)
+                .data_hash(data_hash)
+                .creator_hash(creator_hash)
+                .nonce(nonce)
+                .index(index)
+                .add_remaining_accounts(&proof)
+                .invoke()?;
+        } else {
+            TransferCpiBuilder::new(&ctx.accounts.bubblegum_program)
+                .tree_config(&ctx.accounts.tree_config)
+                .leaf_owner(&organizer, true)
+                .leaf_delegate(&organizer, false)
+                .new_leaf_owner(&raffle_info)
+                .merkle_tree(&ctx.accounts.merkle_tree)
+                .log_wrapper(&ctx.accounts.log_wrapper)
+                .compression_program(&ctx.accounts.compression_program)
+                .system_program(&ctx.accounts.system_program)
+                .root(root)
+                .data_hash(data_hash)
+                .creator_hash(creator_hash)
+                .nonce(nonce)
+                .index(index)
+                .add_remaining_accounts(&proof)
+                .invoke()?;
+        }
+
+        let merkle_tree = ctx.accounts.merkle_tree.key();
+        raffle.prize_cnft = CompressedPrize { merkle_tree, nonce, index, data_hash, creator_hash, delegated: delegate };
+        raffle.prize_mint = get_asset_id(&merkle_tree, nonce);
+        raffle.prize_escrow = Pubkey::default();
+        raffle.prize_set = true;
+        raffle.prize_claimed = false;
+
+        emit_cpi!(PrizeSet { raffle: raffle.key(), prize_mint: raffle.prize_mint });
+        Ok(())
+    }
+
+    // Sale terms: `set_allowlist`, `set_gate`, `set_prize_backed` and `set_claim_window` are
+    // organizer-only and only allowed while the raffle is selling and before the first
+    // ticket is sold (each has its own `*Locked` error), so every buyer enters under the same
+    // terms. Send them in the same transaction as `initialize_raffle`.
+
+    /// **Open the raffle with an allowlist presale.**
+    ///
+    /// Until `presale_end_unix_ts`, deposits and joins need an `Allocation` registered
+    /// against `allowlist_root`; afterwards the raffle is public. The presale must end by
+    /// the deadline.
+    pub fn set_allowlist(ctx: Context<SetAllowlist>, allowlist_root: [u8; 32], presale_end_unix_ts: i64) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let raffle = &mut ctx.accounts.raffle;
+        require!(raffle.status == RaffleStatus::Selling as u8, RaffleError::RaffleNotSelling);
+        require!(raffle.tickets_sold == 0, RaffleError::AllowlistLocked);
+        require!(allowlist_root != [0u8; 32], RaffleError::InvalidAllowlistProof);
+        require!(presale_end_unix_ts > now && presale_end_unix_ts <= raffle.deadline, RaffleError::InvalidPresaleEnd);
+
+        raffle.allowlist_root = allowlist_root;
+        raffle.presale_end_unix_ts = presale_end_unix_ts;
+        emit_cpi!(AllowlistSet { raffle: raffle.key(), allowlist_root, presale_end_unix_ts });
+        Ok(())
+    }
+
+    /// **Gate entries on a token holding.**
+    ///
+    /// - `gate_mode == 1`: the payer's `gate_mint` token account holds at least
+    ///   `gate_min_amount` (e.g. a minimum MOGA balance)
+    /// - `gate_mode == 2`: the payer holds an NFT whose Metaplex metadata lists `gate_mint`
+    ///   as its verified collection (requires the `metaplex` feature)
+    ///
+    /// Entries pass the token account (and, for collections, its metadata) as
+    /// `gate_token_account` / `gate_metadata`. `gate_mode == 0` lifts the gate.
+    pub fn set_gate(ctx: Context<SetGate>, gate_mode: u8, gate_mint: Pubkey, gate_min_amount: u64) -> Result<()> {
+        let raffle = &mut ctx.accounts.raffle;
+        require!(raffle.status == RaffleStatus::Selling as u8, RaffleError::RaffleNotSelling);
+        require!(raffle.tickets_sold == 0, RaffleError::GateLocked);
+        match gate_mode {
+            0 => {}
+            1 => require!(gate_min_amount > 0, RaffleError::InvalidGate),
+            2 => require!(cfg!(feature = "metaplex"), RaffleError::InvalidGate),
+            _ => return err!(RaffleError::InvalidGate),
+        }
+        if gate_mode != 0 {
+            require!(gate_mint != Pubkey::default(), RaffleError::InvalidGate);
+        }
+
+        raffle.gate_mode = gate_mode;
+        raffle.gate_mint = gate_mint;
+        raffle.gate_min_amount = gate_min_amount;
+        emit_cpi!(GateSet { raffle: raffle.key(), gate_mode, gate_mint, gate_min_amount });
+        Ok(())
+    }
+
+    /// **Require the prize to be secured before any ticket is sold.**
+    ///
+    /// While `prize_backed` is on, deposits and joins fail until `set_prize_nft` has
+    /// escrowed the prize, unless the raffle mints its prize after the draw: then the
+    /// `prize_collection_mint` given at creation must be a Metaplex collection NFT, checked
+    /// here against its metadata (`collection_metadata`).
+    pub fn set_prize_backed(ctx: Context<SetPrizeBacked>, prize_backed: bool) -> Result<()> {
+        let raffle = &mut ctx.accounts.raffle;
+        require!(raffle.status == RaffleStatus::Selling as u8, RaffleError::RaffleNotSelling);
+        require!(raffle.tickets_sold == 0, RaffleError::PrizeBackingLocked);
+        if prize_backed && !raffle.prize_set && raffle.prize_collection_mint != Pubkey::default() {
+            verify_prize_collection(&raffle.prize_collection_mint, ctx.accounts.collection_metadata.as_ref())?;
+        }
+
+        raffle.prize_backed = prize_backed;
+        emit_cpi!(PrizeBackingSet { raffle: raffle.key(), prize_backed });
+        Ok(())
+    }
+
+    /// **Set how long a drawn winner has to call `claim_win`** (0 = no deadline, the win can
+    /// never be forfeited). New raffles start with `DEFAULT_CLAIM_WINDOW_SECS`.
+    pub fn set_claim_window(ctx: Context<SetClaimWindow>, claim_window_secs: i64) -> Result<()> {
+        let raffle = &mut ctx.accounts.raffle;
+        require!(raffle.status == RaffleStatus::Selling as u8, RaffleError::RaffleNotSelling);
+        require!(raffle.tickets_sold == 0, RaffleError::ClaimWindowLocked);
+        require!(claim_window_secs >= 0, RaffleError::InvalidClaimWindow);
+
+        raffle.claim_window_secs = claim_window_secs;
+        emit_cpi!(ClaimWindowSet { raffle: raffle.key(), claim_window_secs });
+        Ok(())
+    }
+
+    /// **Register a presale allocation by proving `(payer, max_allocation)` is in the allowlist.**
+    ///
+    /// Leaves are `allowlist_leaf(wallet, max_allocation)`; `proof` lists the sibling hashes
+    /// from the leaf up (pairs are hashed sorted, see `allowlist_node`). The payer funds
+    /// the `Allocation` PDA, which tracks how many tickets of the allocation are used.
+    pub fn register_allowlist(ctx: Context<RegisterAllowlist>, max_allocation: u64, proof: Vec<[u8; 32]>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let raffle = &ctx.accounts.raffle;
+        require!(raffle.status == RaffleStatus::Selling as u8, RaffleError::RaffleNotSelling);
+        require!(raffle.is_presale(now), RaffleError::PresaleEnded);
+        require!(max_allocation > 0, RaffleError::InvalidAmount);
+        let leaf = allowlist_leaf(&ctx.accounts.payer.key(), max_allocation);
+        require!(verify_allowlist_proof(&raffle.allowlist_root, leaf, &proof), RaffleError::InvalidAllowlistProof);
+
+        let allocation = &mut ctx.accounts.allocation;
+        allocation.raffle = raffle.key();
+        allocation.wallet = ctx.accounts.payer.key();
+        allocation.allowlist_root = raffle.allowlist_root;
+        allocation.max_allocation = max_allocation;
+        allocation.used = 0;
+        allocation.bump = ctx.bumps.allocation;
+
+        emit_cpi!(AllowlistRegistered { raffle: raffle.key(), wallet: allocation.wallet, max_allocation });
+        Ok(())
+    }
+
+    /// **Close an `Allocation` once the presale is over; rent goes back to the wallet.**
+    /// Permissionless, like `close_ticket`.
+    pub fn close_allocation(ctx: Context<CloseAllocation>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let raffle = &ctx.accounts.raffle;
+        require!(raffle.status != RaffleStatus::Selling as u8 || !raffle.is_presale(now), RaffleError::PresaleActive);
         Ok(())
     }
 
-    pub fn claim_prize(ctx: Context<ClaimPrize>) -> Result<()> {
+    /// Hand the escrowed prize to the winner. SPL prizes need `prize_mint`, `prize_escrow`
+    /// and `winner_prize_ata`; compressed prizes (`set_prize_cnft`) need the `cnft` accounts,
+    /// the tree's current `cnft_root` and the leaf's proof path as remaining accounts.
+    pub fn claim_prize<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ClaimPrize<'info>>,
+        cnft_root: Option<[u8; 32]>,
+    ) -> Result<()> {
         // Get account info before mutable borrow
         let raffle_account_info = ctx.accounts.raffle.to_account_info();
         
@@ -953,14 +1539,11 @@ pub struct InitializeRaffleWithPermit<'info> {
         require!(raffle.status == RaffleStatus::Completed as u8, RaffleError::WrongStatus);
         require!(raffle.prize_set, RaffleError::PrizeNotSet);
         require!(!raffle.prize_claimed, RaffleError::PrizeAlreadyClaimed);
-        require_keys_eq!(raffle.prize_mint, ctx.accounts.prize_mint.key());
-        require_keys_eq!(raffle.prize_escrow, ctx.accounts.prize_escrow.key());
-        require!(ctx.accounts.prize_mint.decimals == 0, RaffleError::PrizeMustBeNft);
 
         let ticket = &ctx.accounts.ticket;
         let raffle_key = raffle.key();
         require!(ticket.raffle == raffle_key, RaffleError::WrongRaffle);
-        require!(ticket.owner == ctx.accounts.winner.key(), RaffleError::Unauthorized);
+        authorize_ticket_holder(ticket, &ctx.accounts.winner.key(), ctx.accounts.holder_ticket_account.as_ref())?;
         require!(ticket.claimed_win, RaffleError::MustClaimWinFirst);
 
         let raffle_mint = raffle.mint;
@@ -974,100 +1557,403 @@ pub struct InitializeRaffleWithPermit<'info> {
             raffle_organizer.as_ref(),
             &[raffle_bump],
         ];
-        let signer = &[seeds];
-        let cpi_accounts = TransferChecked {
-            from: ctx.accounts.prize_escrow.to_account_info(),
-            to: ctx.accounts.winner_prize_ata.to_account_info(),
-            mint: ctx.accounts.prize_mint.to_account_info(),
-            authority: raffle_account_info,
-        };
-        let cpi_program = ctx.accounts.token_program.to_account_info();
-        token::transfer_checked(CpiContext::new_with_signer(cpi_program, cpi_accounts, signer), 1, 0)?;
+        if raffle.prize_cnft.is_set() {
+            let root = cnft_root.ok_or(RaffleError::InvalidPrizeAccounts)?;
+            transfer_compressed_prize(
+                &raffle.prize_cnft,
+                raffle_account_info,
+                ctx.accounts.winner.to_account_info(),
+                &ctx.accounts.cnft,
+                root,
+                ctx.remaining_accounts,
+                &[seeds],
+            )?;
+        } else {
+            let (Some(prize_mint), Some(prize_escrow), Some(winner_prize_ata)) = (
+                ctx.accounts.prize_mint.as_ref(),
+                ctx.accounts.prize_escrow.as_ref(),
+                ctx.accounts.winner_prize_ata.as_ref(),
+            ) else {
+                return err!(RaffleError::InvalidPrizeAccounts);
+            };
+            require_keys_eq!(raffle.prize_mint, prize_mint.key());
+            require_keys_eq!(raffle.prize_escrow, prize_escrow.key());
+            require!(prize_mint.decimals == 0, RaffleError::PrizeMustBeNft);
+            require_keys_eq!(winner_prize_ata.owner, ctx.accounts.winner.key(), RaffleError::InvalidPrizeAccounts);
+            require_keys_eq!(winner_prize_ata.mint, prize_mint.key(), RaffleError::InvalidPrizeAccounts);
+
+            transfer_prize(
+                PrizeMove {
+                    from: prize_escrow.to_account_info(),
+                    from_owner: raffle_account_info,
+                    to: winner_prize_ata.to_account_info(),
+                    to_owner: ctx.accounts.winner.to_account_info(),
+                    mint: prize_mint.to_account_info(),
+                    payer: ctx.accounts.winner.to_account_info(),
+                    token_program: ctx.accounts.token_program.to_account_info(),
+                },
+                ctx.accounts.prize_metadata.as_ref(),
+                ctx.accounts.prize_master_edition.as_ref(),
+                &ctx.accounts.pnft,
+                &[seeds],
+            )?;
+        }
 
         raffle.prize_claimed = true;
-        emit!(PrizeClaimed { raffle: raffle_key, winner: ctx.accounts.winner.key(), prize_mint: prize_mint_key });
+        emit_cpi!(PrizeClaimed { raffle: raffle_key, winner: ctx.accounts.winner.key(), prize_mint: prize_mint_key });
         Ok(())
     }
 
-    // /// **Join raffle with MOGA tokens using backend-signed permit.**
-    // ///
-    // /// # What it does
-    // /// - Verifies backend-signed permit (ed25519) for join authorization
-    // /// - Validates slots hash to prevent front-running
-    // /// - Checks permit expiry
-    // /// - Validates requested slots are free
-    // /// - Reserves slots and mints ticket record
-    // ///
-    // /// # Security
-    // /// - Backend must sign: raffle || organizer || payer || slots_hash || moga_mint || usdc_mint || nonce || expiry || program_id
-    // /// - Slots hash prevents race conditions and front-running
-    // /// - Permit expiry enforced on-chain
-    // #[cfg(feature = "pyth-jupiter")]
-    // pub fn join_with_moga_with_permit(
-    //     ctx: Context<JoinWithMogaWithPermit>,
-    //     slots: Vec<u32>,
-    //     max_moga_in: u64,
-    //     permit_nonce: [u8; 16],
-    //     permit_expiry_unix_ts: i64,
-    // ) -> Result<()> {
-    //     let clock = Clock::get()?;
-    //     let raffle = &mut ctx.accounts.raffle;
-    //     require!(raffle.status == RaffleStatus::Selling as u8, RaffleError::RaffleNotSelling);
-    //     require!(clock.unix_timestamp <= raffle.deadline, RaffleError::PastDeadline);
-    //     require!(!slots.is_empty(), RaffleError::InvalidAmount);
-    //     require!(slots.len() as u64 + raffle.tickets_sold <= raffle.required_tickets, RaffleError::OverSubscription);
+    /// **Close a settled ticket and return its rent to whoever paid it (`ticket.payer`).**
+    ///
+    /// Permissionless so keepers can sweep tickets after settlement. Only allowed once the
+    /// raffle is settled (prize claimed and proceeds collected, or every ticket refunded).
+    /// The rent goes back to the payer even when the ticket was gifted, transferred or
+    /// tokenized since.
+    pub fn close_ticket(ctx: Context<CloseTicket>) -> Result<()> {
+        let raffle = &mut ctx.accounts.raffle;
+        require!(raffle.is_settled(), RaffleError::RaffleNotSettled);
+        raffle.open_tickets = raffle.open_tickets.checked_sub(ctx.accounts.ticket.count).ok_or(RaffleError::Overflow)?;
+        Ok(())
+    }
 
-    //     // Verify permit expiry
-    //     require!(permit_expiry_unix_ts > clock.unix_timestamp, RaffleError::PermitExpired);
+    /// **Move a ticket range to another wallet before the draw is settled.**
+    ///
+    /// The ticket PDA is seeded by its owner, so the old account is closed (rent back to its
+    /// payer) and re-created under the recipient with the same range, paid by the sender. The matching
+    /// `RaffleSlots.owners` entries move too; win claims, prize claims and refunds then follow
+    /// the recipient. Allowed while the raffle is `Selling` or `Drawing`, but not during a
+    /// redraw (see `forfeit_winner`).
+    pub fn transfer_ticket(ctx: Context<TransferTicket>) -> Result<()> {
+        let raffle = &ctx.accounts.raffle;
+        require!(
+            raffle.status == RaffleStatus::Selling as u8 || raffle.status == RaffleStatus::Drawing as u8,
+            RaffleError::TicketNotTransferable
+        );
+        require!(raffle.forfeited_owners.is_empty(), RaffleError::TicketNotTransferable);
+        let from = ctx.accounts.owner.key();
+        let to = ctx.accounts.recipient.key();
+        require!(to != from && to != Pubkey::default(), RaffleError::InvalidRecipient);
 
-    //     // Compute slots hash for permit verification
-    //     let mut slots_bytes = Vec::with_capacity(slots.len() * 4);
-    //     for &slot in &slots {
-    //         slots_bytes.extend_from_slice(&slot.to_le_bytes());
-    //     }
-    //     let slots_hash = anchor_lang::solana_program::hash::hash(&slots_bytes);
+        let old = &ctx.accounts.ticket;
+        require!(!old.refunded && !old.claimed_win, RaffleError::TicketNotTransferable);
+        ctx.accounts.slots.transfer_range(old.start, old.count, &from, to)?;
 
-    //     // Build canonical permit message
-    //     let mut expected_msg: Vec<u8> = b"RWA_RAFFLE_JOIN_PERMIT".to_vec();
-    //     expected_msg.extend_from_slice(raffle.key().as_ref());
-    //     expected_msg.extend_from_slice(raffle.organizer.as_ref());
-    //     expected_msg.extend_from_slice(ctx.accounts.payer.key().as_ref());
-    //     expected_msg.extend_from_slice(slots_hash.as_ref());
-    //     expected_msg.extend_from_slice(ctx.accounts.moga_mint.key().as_ref());
-    //     expected_msg.extend_from_slice(ctx.accounts.usdc_mint.key().as_ref());
-    //     expected_msg.extend_from_slice(&permit_nonce);
-    //     expected_msg.extend_from_slice(&permit_expiry_unix_ts.to_le_bytes());
-    //     expected_msg.extend_from_slice(crate::ID.as_ref());
+        let ticket = &mut ctx.accounts.new_ticket;
+        ticket.raffle = raffle.key();
+        ticket.owner = to;
+        ticket.start = old.start;
+        ticket.count = old.count;
+        ticket.refunded = false;
+        ticket.claimed_win = false;
+        ticket.bump = ctx.bumps.new_ticket;
+        ticket.payer = from;
 
-    //     // Verify ed25519 signature from backend
-    //     use anchor_lang::solana_program::{ed25519_program, sysvar::instructions};
-    //     let ixn_acc = &ctx.accounts.instructions_sysvar;
-    //     let mut found = false;
-    //     let mut idx = 0;
+        emit_cpi!(TicketTransferred {
+            raffle: raffle.key(),
+            old_ticket: old.key(),
+            ticket: ticket.key(),
+            from,
+            to,
+            start: ticket.start,
+            count: ticket.count,
+        });
+        Ok(())
+    }
+
+    /// **Mint a ticket as a Token-2022 NFT so the entry can trade on marketplaces.**
+    ///
+    /// Creates a supply-1 mint at `[TICKET_MINT_SEED, raffle, start]` whose on-mint metadata
+    /// carries the raffle and ticket range, sends the NFT to the owner and re-keys the ticket
+    /// PDA under the mint (`ticket.owner = ticket_mint`, same for `RaffleSlots.owners`). From
+    /// then on `claim_win`, `claim_prize`, `claim_refund` and `close_ticket` authorize whoever
+    /// holds the NFT. Allowed while the raffle is `Selling` or `Drawing`, but not during a
+    /// redraw (see `forfeit_winner`).
+    pub fn tokenize_ticket(ctx: Context<TokenizeTicket>) -> Result<()> {
+        let raffle = &ctx.accounts.raffle;
+        require!(
+            raffle.status == RaffleStatus::Selling as u8 || raffle.status == RaffleStatus::Drawing as u8,
+            RaffleError::TicketNotTransferable
+        );
+        require!(raffle.forfeited_owners.is_empty(), RaffleError::TicketNotTransferable);
+        let old = &ctx.accounts.ticket;
+        require!(!old.refunded && !old.claimed_win, RaffleError::TicketNotTransferable);
+        let (start, count) = (old.start, old.count);
+        let owner = ctx.accounts.owner.key();
+        let ticket_mint = ctx.accounts.ticket_mint.key();
+        ctx.accounts.slots.transfer_range(start, count, &owner, ticket_mint)?;
+
+        mint_ticket_nft(ctx.accounts, start, count)?;
+
+        let ticket = &mut ctx.accounts.new_ticket;
+        ticket.raffle = ctx.accounts.raffle.key();
+        ticket.owner = ticket_mint;
+        ticket.start = start;
+        ticket.count = count;
+        ticket.refunded = false;
+        ticket.claimed_win = false;
+        ticket.bump = ctx.bumps.new_ticket;
+        ticket.payer = owner;
+
+        emit_cpi!(TicketTokenized {
+            raffle: ticket.raffle,
+            old_ticket: ctx.accounts.ticket.key(),
+            ticket: ticket.key(),
+            ticket_mint,
+            holder: owner,
+            start,
+            count,
+        });
+        Ok(())
+    }
+
+    /// **Close the `RaffleSlots` account of a settled raffle; rent goes back to the organizer.**
+    pub fn close_slots(ctx: Context<CloseSlots>) -> Result<()> {
+        let raffle = &mut ctx.accounts.raffle;
+        require!(raffle.is_settled(), RaffleError::RaffleNotSettled);
+        raffle.slots_closed = true;
+        Ok(())
+    }
+
+    /// **Close a settled raffle, its escrow token account and return all rent to the organizer.**
+    ///
+    /// # Requirements
+    /// - Raffle is settled (see `close_ticket`)
+    /// - Every ticket and the `RaffleSlots` account are already closed, so no stale ticket
+    ///   can ever be replayed against a new raffle at the same PDA
+    /// - Escrow holds no funds (nothing is still owed to buyers or the organizer)
+    ///
+    /// Any unspent crank reserve goes back to the organizer along with the rent.
+    pub fn close_raffle(ctx: Context<CloseRaffle>) -> Result<()> {
+        let raffle = &ctx.accounts.raffle;
+        require!(raffle.is_settled(), RaffleError::RaffleNotSettled);
+        require!(raffle.open_tickets == 0, RaffleError::TicketsStillOpen);
+        require!(raffle.slots_closed, RaffleError::SlotsStillOpen);
+        require!(ctx.accounts.escrow_ata.amount == 0, RaffleError::EscrowNotEmpty);
+
+        let seeds: &[&[u8]] = &[
+            RAFFLE_SEED,
+            raffle.mint.as_ref(),
+            raffle.organizer.as_ref(),
+            &[raffle.bump],
+        ];
+        token::close_account(CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            token::CloseAccount {
+                account: ctx.accounts.escrow_ata.to_account_info(),
+                destination: ctx.accounts.organizer.to_account_info(),
+                authority: raffle.to_account_info(),
+            },
+            &[seeds],
+        ))?;
+
+        emit_cpi!(RaffleClosed { raffle: raffle.key(), organizer: raffle.organizer });
+        Ok(())
+    }
+
+    /// **Move the backing of MRFTs nobody redeemed into the platform refund vault.**
+    ///
+    /// MRFT refunds (`refund_mode != 0`) leave the USDC in escrow until each MRFT is
+    /// redeemed, so a failed raffle could otherwise never empty it for `close_raffle`.
+    /// Once every ticket is refunded and `MRFT_BACKING_SWEEP_DELAY_SECS` have passed since
+    /// the deadline, any keeper may sweep the whole escrow balance to the refund vault.
+    /// The raffle's MRFTs then no longer redeem against it (`AlreadySwept`); the platform
+    /// honours them from the vault.
+    pub fn sweep_mrft_backing(ctx: Context<SweepMrftBacking>) -> Result<()> {
+        let clock = Clock::get()?;
+        let raffle = &mut ctx.accounts.raffle;
+        require!(raffle.status == RaffleStatus::Refunding as u8 && raffle.refund_mode != 0, RaffleError::WrongStatus);
+        require!(raffle.is_settled(), RaffleError::RaffleNotSettled);
+        require!(!raffle.mrft_backing_swept, RaffleError::AlreadySwept);
+        let sweep_ts = raffle.deadline.checked_add(MRFT_BACKING_SWEEP_DELAY_SECS).ok_or(RaffleError::Overflow)?;
+        require!(clock.unix_timestamp >= sweep_ts, RaffleError::SweepTooEarly);
+        raffle.mrft_backing_swept = true;
+
+        // Everything left, so stray transfers into the escrow can't keep it open either
+        let amount = ctx.accounts.escrow_ata.amount;
+        if amount > 0 {
+            let seeds: &[&[u8]] = &[
+                RAFFLE_SEED,
+                raffle.mint.as_ref(),
+                raffle.organizer.as_ref(),
+                &[raffle.bump],
+            ];
+            token::transfer_checked(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    TransferChecked {
+                        from: ctx.accounts.escrow_ata.to_account_info(),
+                        to: ctx.accounts.refund_vault.to_account_info(),
+                        mint: ctx.accounts.mint.to_account_info(),
+                        authority: raffle.to_account_info(),
+                    },
+                    &[seeds],
+                ),
+                amount,
+                ctx.accounts.mint.decimals,
+            )?;
+        }
+
+        emit_cpi!(MrftBackingSwept { raffle: raffle.key(), refund_vault: ctx.accounts.refund_vault.key(), amount });
+        Ok(())
+    }
+
+    /// **Check that the escrow holds exactly what the raffle counters say it owes.**
+    ///
+    /// Read-only and permissionless: monitoring simulates it and tests assert it after
+    /// every step. Emits `EscrowAudited` when `escrow_ata.amount` matches
+    /// `Raffle::expected_escrow`, fails with `EscrowMismatch` otherwise.
+    pub fn audit_raffle(ctx: Context<AuditRaffle>) -> Result<()> {
+        let raffle = &ctx.accounts.raffle;
+        let unit = 10u64.pow(ctx.accounts.mint.decimals as u32);
+        let expected = raffle.expected_escrow(unit)?;
+        let actual = ctx.accounts.escrow_ata.amount;
+        if actual != expected {
+            msg!("Escrow mismatch: expected {} actual {}", expected, actual);
+            return err!(RaffleError::EscrowMismatch);
+        }
+
+        emit_cpi!(EscrowAudited {
+            raffle: raffle.key(),
+            status: raffle.status,
+            tickets_sold: raffle.tickets_sold,
+            tickets_refunded: raffle.tickets_refunded,
+            expected,
+            actual,
+        });
+        Ok(())
+    }
+
+    /// **Upgrade a raffle account written with an older layout to `RAFFLE_VERSION`.**
+    ///
+    /// # What it does
+    /// - Detects the stored layout (legacy layouts by data length, versioned ones by `version`)
+    /// - Reallocs the account to `8 + Raffle::LEN`, with `payer` covering the extra rent
+    /// - Rewrites the data in the current layout; new fields get neutral defaults
+    ///
+    /// Permissionless: the conversion is deterministic and only the payer spends lamports.
+    ///
+    /// # Adding fields later
+    /// Freeze the current struct as `RaffleV<n>`, append the new fields to `Raffle`,
+    /// bump `RAFFLE_VERSION` and add a `From<RaffleV<n>>` arm below.
+    pub fn migrate_raffle(ctx: Context<MigrateRaffle>) -> Result<()> {
+        let raffle_info = ctx.accounts.raffle.to_account_info();
+        let (migrated, from_version) = {
+            let data = raffle_info.try_borrow_data()?;
+            require!(data.len() >= 8 && data[..8] == *Raffle::DISCRIMINATOR, RaffleError::UnknownRaffleLayout);
+            let mut body: &[u8] = &data[8..];
+            match body.len() {
+                RaffleV0::LEN => (Raffle::from(RaffleV0::deserialize(&mut body)?), 0),
+                RaffleV1::LEN => (Raffle::from(RaffleV1::deserialize(&mut body)?), 1),
+                Raffle::LEN if body[0] == RAFFLE_VERSION => return err!(RaffleError::AlreadyMigrated),
+                _ => return err!(RaffleError::UnknownRaffleLayout),
+            }
+        };
+
+        grow_account(&raffle_info, &ctx.accounts.payer, &ctx.accounts.system_program, 8 + Raffle::LEN)?;
+        let mut data = raffle_info.try_borrow_mut_data()?;
+        migrated.try_serialize(&mut &mut data[..])?;
+
+        emit_cpi!(RaffleMigrated { raffle: raffle_info.key(), from_version, to_version: RAFFLE_VERSION });
+        Ok(())
+    }
+
+    /// **Upgrade a ticket written before `Ticket.payer` existed.**
+    ///
+    /// Legacy tickets are recognised by their data length and get `payer = owner`, so
+    /// `close_ticket` refunds the wallet that bought them. Permissionless like
+    /// `migrate_raffle`; `payer` covers the extra rent.
+    ///
+    /// Refunds before versioning only flagged the ticket, so a refunded legacy ticket adds
+    /// its count to the (already migrated) raffle's `tickets_refunded` here. Migrating every
+    /// ticket of a legacy refunding raffle is what lets it reach `is_settled`.
+    pub fn migrate_ticket(ctx: Context<MigrateTicket>) -> Result<()> {
+        let ticket_info = ctx.accounts.ticket.to_account_info();
+        let migrated = {
+            let data = ticket_info.try_borrow_data()?;
+            require!(data.len() >= 8 && data[..8] == *Ticket::DISCRIMINATOR, RaffleError::UnknownTicketLayout);
+            let mut body: &[u8] = &data[8..];
+            match body.len() {
+                TicketV0::LEN => Ticket::from(TicketV0::deserialize(&mut body)?),
+                Ticket::LEN => return err!(RaffleError::AlreadyMigrated),
+                _ => return err!(RaffleError::UnknownTicketLayout),
+            }
+        };
+        let raffle = &mut ctx.accounts.raffle;
+        require!(migrated.raffle == raffle.key(), RaffleError::WrongRaffle);
+        if migrated.refunded {
+            raffle.tickets_refunded = raffle.tickets_refunded.checked_add(migrated.count).ok_or(RaffleError::Overflow)?;
+        }
+
+        grow_account(&ticket_info, &ctx.accounts.payer, &ctx.accounts.system_program, 8 + Ticket::LEN)?;
+        let mut data = ticket_info.try_borrow_mut_data()?;
+        migrated.try_serialize(&mut &mut data[..])?;
+
+        emit_cpi!(TicketMigrated { raffle: migrated.raffle, ticket: ticket_info.key(), payer: migrated.payer });
+        Ok(())
+    }
+
+    // /// **Join raffle with MOGA tokens using backend-signed permit.**
+    // ///
+    // /// # What it does
+    // /// - Verifies backend-signed permit (ed25519) for join authorization
+    // /// - Validates slots hash to prevent front-running
+    // /// - Checks permit expiry
+    // /// - Validates requested slots are free
+    // /// - Reserves slots and mints ticket record
+    // ///
+    // /// # Security
+    // /// - Backend must sign: raffle || organizer || payer || slots_hash || moga_mint || usdc_mint || nonce || expiry || program_id
+    // /// - Slots hash prevents race conditions and front-running
+    // /// - Permit expiry enforced on-chain
+    // #[cfg(feature = "pyth-jupiter")]
+    // pub fn join_with_moga_with_permit(
+    //     ctx: Context<JoinWithMogaWithPermit>,
+    //     slots: Vec<u32>,
+    //     max_moga_in: u64,
+    //     permit_nonce: [u8; 16],
+    //     permit_expiry_unix_ts: i64,
+    // ) -> Result<()> {
+    //     let clock = Clock::get()?;
+    //     let raffle = &mut ctx.accounts.raffle;
+    //     require!(raffle.status == RaffleStatus::Selling as u8, RaffleError::RaffleNotSelling);
+    //     require!(clock.unix_timestamp <= raffle.deadline, RaffleError::PastDeadline);
+    //     require!(!slots.is_empty(), RaffleError::InvalidAmount);
+    //     require!(slots.len() as u64 + raffle.tickets_sold <= raffle.required_tickets, RaffleError::OverSubscription);
+
+    //     // Verify permit expiry
+    //     require!(permit_expiry_unix_ts > clock.unix_timestamp, RaffleError::PermitExpired);
+
+    //     // Compute slots hash for permit verification
+    //     let mut slots_bytes = Vec::with_capacity(slots.len() * 4);
+    //     for &slot in &slots {
+    //         slots_bytes.extend_from_slice(&slot.to_le_bytes());
+    //     }
+    //     let slots_hash = anchor_lang::solana_program::hash::hash(&slots_bytes);
+
+    //     // Build canonical permit message
+    //     let mut expected_msg: Vec<u8> = b"RWA_RAFFLE_JOIN_PERMIT".to_vec();
+    //     expected_msg.extend_from_slice(raffle.key().as_ref());
+    //     expected_msg.extend_from_slice(raffle.organizer.as_ref());
+    //     expected_msg.extend_from_slice(ctx.accounts.payer.key().as_ref());
+    //     expected_msg.extend_from_slice(slots_hash.as_ref());
+    //     expected_msg.extend_from_slice(ctx.accounts.moga_mint.key().as_ref());
+    //     expected_msg.extend_from_slice(ctx.accounts.usdc_mint.key().as_ref());
+    //     expected_msg.extend_from_slice(&permit_nonce);
+    //     expected_msg.extend_from_slice(&permit_expiry_unix_ts.to_le_bytes());
+    //     expected_msg.extend_from_slice(crate::ID.as_ref());
+
+    //     // Verify ed25519 signature from backend
+    //     use anchor_lang::solana_program::{ed25519_program, sysvar::instructions};
+    //     let ixn_acc = &ctx.accounts.instructions_sysvar;
+    //     let mut found = false;
+    //     let mut idx = 0;
     //     loop {
     //         let loaded = instructions::load_instruction_at_checked(idx, ixn_acc);
     //         if loaded.is_err() { break; }
     //         let ix = loaded.unwrap();
     //         if ix.program_id == ed25519_program::id() {
-    //             let data = ix.data.as_slice();
-    //             if data.len() < 16 { idx += 1; continue; }
-    //             let num = data[0] as usize;
-    //             if num != 1 { idx += 1; continue; }
-    //             let sig_off = u16::from_le_bytes([data[2], data[3]]) as usize;
-    //             let sig_len = u16::from_le_bytes([data[4], data[5]]) as usize;
-    //             let pk_off = u16::from_le_bytes([data[6], data[7]]) as usize;
-    //             let pk_len = u16::from_le_bytes([data[8], data[9]]) as usize;
-    //             let msg_off = u16::from_le_bytes([data[10], data[11]]) as usize;
-    //             let msg_len = u16::from_le_bytes([data[12], data[13]]) as usize;
-    //             let msg_acc_idx = u16::from_le_bytes([data[14], data[15]]);
-
-    //             if msg_acc_idx != u16::MAX { idx += 1; continue; }
-    //             if pk_len != 32 || sig_len != 64 { idx += 1; continue; }
-    //             if pk_off.checked_add(pk_len).unwrap_or(usize::MAX) > data.len() { idx += 1; continue; }
-    //             if msg_off.checked_add(msg_len).unwrap_or(usize::MAX) > data.len() { idx += 1; continue; }
-
-    //             let pk_bytes = &data[pk_off..pk_off+pk_len];
-    //             let msg_bytes = &data[msg_off..msg_off+msg_len];
+    //             let Some((pk_bytes, msg_bytes)) = ed25519_ix_parts(&ix.data) else { idx += 1; continue; };
 
     //             if pk_bytes == BACKEND_SIGNER.as_ref() && msg_bytes == expected_msg.as_slice() {
     //                 found = true;
@@ -1187,7 +2073,7 @@ pub struct InitializeRaffleWithPermit<'info> {
 
         let ticket = &ctx.accounts.ticket;
         require!(ticket.raffle == raffle.key(), RaffleError::WrongRaffle);
-        require!(ticket.owner == ctx.accounts.winner.key(), RaffleError::Unauthorized);
+        authorize_ticket_holder(ticket, &ctx.accounts.winner.key(), ctx.accounts.holder_ticket_account.as_ref())?;
         require!(ticket.claimed_win, RaffleError::MustClaimWinFirst);
         
         // Verify collection mint matches raffle config
@@ -1196,18 +2082,18 @@ pub struct InitializeRaffleWithPermit<'info> {
             raffle.prize_collection_mint,
             RaffleError::InvalidCollectionMint
         );
+        require!(raffle.prize_metadata.is_set(), RaffleError::PrizeMetadataNotSet);
 
         // Metaplex Token Metadata CPI to mint NFT
         use mpl_token_metadata::instructions::{CreateV1CpiBuilder, VerifyCollectionV1CpiBuilder};
         use mpl_token_metadata::types::{TokenStandard, PrintSupply, Creator};
-        
-        // NFT metadata (should be stored in raffle config or passed as params)
-        let name = format!("RWA Prize #{}", raffle.key().to_string()[..8].to_string());
-        let symbol = "RWAP".to_string();
-        let uri = "https://arweave.net/placeholder".to_string(); // Should be from raffle config
-        
+
+        // NFT metadata as configured at creation (bound by the permit)
+        let PrizeMetadata { name, symbol, uri, seller_fee_basis_points, creators } = raffle.prize_metadata.clone();
+
         // Create NFT with metadata
-        CreateV1CpiBuilder::new(&ctx.accounts.token_metadata_program)
+        let mut create = CreateV1CpiBuilder::new(&ctx.accounts.token_metadata_program);
+        create
             .metadata(&ctx.accounts.prize_metadata)
             .master_edition(Some(&ctx.accounts.prize_master_edition))
             .mint(&ctx.accounts.prize_mint, true)
@@ -1220,15 +2106,24 @@ pub struct InitializeRaffleWithPermit<'info> {
             .name(name)
             .symbol(symbol)
             .uri(uri)
-            .seller_fee_basis_points(0)
+            .seller_fee_basis_points(seller_fee_basis_points)
             .token_standard(TokenStandard::NonFungible)
             .print_supply(PrintSupply::Zero)
             .collection(mpl_token_metadata::types::Collection {
                 verified: false,
                 key: ctx.accounts.collection_mint.key(),
-            })
-            .invoke()?;
-        
+            });
+        // Metaplex rejects an empty creator list; leave it unset instead
+        if !creators.is_empty() {
+            create.creators(
+                creators
+                    .iter()
+                    .map(|c| Creator { address: c.address, verified: false, share: c.share })
+                    .collect(),
+            );
+        }
+        create.invoke()?;
+
         // Verify collection (requires collection authority signature)
         VerifyCollectionV1CpiBuilder::new(&ctx.accounts.token_metadata_program)
             .authority(&ctx.accounts.collection_authority)
@@ -1242,7 +2137,7 @@ pub struct InitializeRaffleWithPermit<'info> {
             .invoke()?;
 
         raffle.prize_claimed = true;
-        emit!(PrizeClaimed { 
+        emit_cpi!(PrizeClaimed { 
             raffle: raffle.key(), 
             winner: ctx.accounts.winner.key(), 
             prize_mint: ctx.accounts.prize_mint.key() 
@@ -1258,11 +2153,12 @@ pub struct InitializeRaffleWithPermit<'info> {
     /// - Max 5 raffles per transaction to avoid compute limits
     ///
     /// # Parameters
-    /// - `configs`: Vec of (required_tickets, deadline, auto_draw, ticket_mode)
+    /// - `configs`: Vec of (required_tickets, deadline, auto_draw, ticket_mode, reveal_time_unix_ts,
+    ///   prize_metadata_hash, mrft_collection), each signed as in `initialize_raffle_with_permit`
     /// - `permit_data`: Vec of (nonce, expiry) for each raffle
     pub fn batch_create_raffles(
         ctx: Context<BatchCreateRaffles>,
-        configs: Vec<(u64, i64, bool, u8)>,
+        configs: Vec<BatchRaffleConfig>,
         permit_data: Vec<([u8; 16], i64)>,
     ) -> Result<()> {
         require!(configs.len() <= 5, RaffleError::BatchSizeExceeded);
@@ -1271,11 +2167,13 @@ pub struct InitializeRaffleWithPermit<'info> {
         let clock = Clock::get()?;
         
         // Verify all permits first
-        for (idx, (config, permit)) in configs.iter().zip(permit_data.iter()).enumerate() {
-            let (required_tickets, deadline, auto_draw, ticket_mode) = config;
+        for (config, permit) in configs.iter().zip(permit_data.iter()) {
+            let (required_tickets, deadline, auto_draw, ticket_mode, reveal_time_unix_ts, prize_metadata_digest, mrft_collection) =
+                config;
             let (nonce, expiry) = permit;
             
             require!(*expiry > clock.unix_timestamp, RaffleError::PermitExpired);
+            validate_reveal_time(*reveal_time_unix_ts, *deadline, clock.unix_timestamp)?;
             
             // Build permit message
             let mut expected_msg: Vec<u8> = b"RWA_RAFFLE_PERMIT".to_vec();
@@ -1287,6 +2185,9 @@ pub struct InitializeRaffleWithPermit<'info> {
             expected_msg.extend_from_slice(crate::ID.as_ref());
             expected_msg.push(*auto_draw as u8);
             expected_msg.push(*ticket_mode);
+            expected_msg.extend_from_slice(&reveal_time_unix_ts.unwrap_or(0).to_le_bytes());
+            expected_msg.extend_from_slice(prize_metadata_digest);
+            expected_msg.extend_from_slice(mrft_collection.as_ref());
             
             // Verify ed25519 signature (same logic as initialize_raffle_with_permit)
             use anchor_lang::solana_program::{ed25519_program, sysvar::instructions};
@@ -1298,25 +2199,7 @@ pub struct InitializeRaffleWithPermit<'info> {
                 if loaded.is_err() { break; }
                 let ix = loaded.unwrap();
                 if ix.program_id == ed25519_program::id() {
-                    let data = ix.data.as_slice();
-                    if data.len() < 16 { ix_idx += 1; continue; }
-                    let num = data[0] as usize;
-                    if num != 1 { ix_idx += 1; continue; }
-                    let sig_off = u16::from_le_bytes([data[2], data[3]]) as usize;
-                    let sig_len = u16::from_le_bytes([data[4], data[5]]) as usize;
-                    let pk_off = u16::from_le_bytes([data[6], data[7]]) as usize;
-                    let pk_len = u16::from_le_bytes([data[8], data[9]]) as usize;
-                    let msg_off = u16::from_le_bytes([data[10], data[11]]) as usize;
-                    let msg_len = u16::from_le_bytes([data[12], data[13]]) as usize;
-                    let msg_acc_idx = u16::from_le_bytes([data[14], data[15]]);
-
-                    if msg_acc_idx != u16::MAX { ix_idx += 1; continue; }
-                    if pk_len != 32 || sig_len != 64 { ix_idx += 1; continue; }
-                    if pk_off.checked_add(pk_len).unwrap_or(usize::MAX) > data.len() { ix_idx += 1; continue; }
-                    if msg_off.checked_add(msg_len).unwrap_or(usize::MAX) > data.len() { ix_idx += 1; continue; }
-
-                    let pk_bytes = &data[pk_off..pk_off+pk_len];
-                    let msg_bytes = &data[msg_off..msg_off+msg_len];
+                    let Some((pk_bytes, msg_bytes)) = ed25519_ix_parts(&ix.data) else { ix_idx += 1; continue; };
 
                     if pk_bytes == BACKEND_SIGNER.as_ref() && msg_bytes == expected_msg.as_slice() {
                         found = true;
@@ -1349,6 +2232,7 @@ pub struct InitializeRaffleWithPermit<'info> {
     // ) -> Result<()> {
     //     let raffle = &ctx.accounts.raffle;
     //     require!(raffle.status == RaffleStatus::Drawing as u8, RaffleError::WrongStatus);
+    //     require!(raffle.is_reveal_due(Clock::get()?.unix_timestamp), RaffleError::RevealTimeNotReached);
     //
     //     let args = vec![Argument::PlaintextU64(raffle.required_tickets)];
     //
@@ -1372,6 +2256,7 @@ pub struct InitializeRaffleWithPermit<'info> {
     //
     //     let raffle = &mut ctx.accounts.raffle;
     //     require!(raffle.status == RaffleStatus::Drawing as u8, RaffleError::WrongStatus);
+    //     require!(raffle.is_reveal_due(Clock::get()?.unix_timestamp), RaffleError::RevealTimeNotReached);
     //     require!(winner_ticket >= 1 && winner_ticket <= raffle.required_tickets, RaffleError::InvalidWinner);
     //     raffle.winner_ticket = winner_ticket;
     //     raffle.status = RaffleStatus::Completed as u8;
@@ -1380,8 +2265,9 @@ pub struct InitializeRaffleWithPermit<'info> {
     // }
 }
 
+#[event_cpi]
 #[derive(Accounts)]
-#[instruction(required_tickets: u64, deadline_unix_ts: i64, auto_draw: bool, ticket_mode: u8)]
+#[instruction(required_tickets: u64, deadline_unix_ts: i64, auto_draw: bool, ticket_mode: u8, reveal_time_unix_ts: Option<i64>)]
 pub struct InitializeRaffle<'info> {
     #[account(mut)]
     pub organizer: Signer<'info>,
@@ -1408,8 +2294,25 @@ pub struct InitializeRaffle<'info> {
     pub token_program: Program<'info, Token>,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct RefundBatch<'info> {
+    /// Keeper running the crank; receives the refund bounty.
+    #[account(mut)]
+    pub caller: Signer<'info>,
+    #[account(mut, has_one = mint, constraint = raffle.escrow == escrow_ata.key())]
+    pub raffle: Account<'info, Raffle>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(mut, constraint = escrow_ata.owner == raffle.key(), constraint = escrow_ata.mint == mint.key())]
+    pub escrow_ata: InterfaceAccount<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct StartRefunding<'info> {
+    /// Keeper running the crank; receives the bounty.
+    #[account(mut)]
     pub caller: Signer<'info>,
     #[account(mut)]
     pub raffle: Account<'info, Raffle>,
@@ -1498,8 +2401,9 @@ pub struct RefundBatch<'info> {
 //     pub token_program: Program<'info, Token>,
 // }
 
+#[event_cpi]
 #[derive(Accounts)]
-#[instruction(amount: u64, start_index: u64)]
+#[instruction(amount: u64, start_index: u64, beneficiary: Pubkey)]
 pub struct Deposit<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
@@ -1510,11 +2414,20 @@ pub struct Deposit<'info> {
     pub payer_ata: InterfaceAccount<'info, TokenAccount>,
     #[account(mut, constraint = escrow_ata.owner == raffle.key(), constraint = escrow_ata.mint == mint.key())]
     pub escrow_ata: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut, seeds = [SLOTS_SEED, raffle.key().as_ref()], bump)]
+    pub slots: Account<'info, RaffleSlots>,
+    /// Beneficiary's presale allocation; required while the raffle is in its allowlist presale.
+    #[account(mut, seeds = [ALLOCATION_SEED, raffle.key().as_ref(), beneficiary.as_ref()], bump = allocation.bump)]
+    pub allocation: Option<Account<'info, Allocation>>,
+    /// Beneficiary's token account proving the raffle's gate (gate mint, or a collection NFT).
+    pub gate_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    /// CHECK: Metaplex metadata of `gate_token_account.mint` for collection gates; checked in `check_gate`.
+    pub gate_metadata: Option<UncheckedAccount<'info>>,
     #[account(
         init,
         payer = payer,
         space = 8 + Ticket::LEN,
-        seeds = [TICKET_SEED, raffle.key().as_ref(), payer.key().as_ref(), &start_index.to_le_bytes()],
+        seeds = [TICKET_SEED, raffle.key().as_ref(), beneficiary.as_ref(), &start_index.to_le_bytes()],
         bump,
     )]
     pub ticket: Account<'info, Ticket>,
@@ -1524,6 +2437,7 @@ pub struct Deposit<'info> {
 
 /// Accounts for joining raffle with MOGA tokens (swap flow).
 #[cfg(feature = "pyth-jupiter")]
+#[event_cpi]
 #[derive(Accounts)]
 #[instruction(slots: Vec<u32>, max_moga_in: u64)]
 pub struct JoinWithMoga<'info> {
@@ -1536,6 +2450,14 @@ pub struct JoinWithMoga<'info> {
     #[account(mut, seeds = [SLOTS_SEED, raffle.key().as_ref()], bump)]
     pub slots: Account<'info, RaffleSlots>,
     
+    /// Presale allocation; required while the raffle is in its allowlist presale.
+    #[account(mut, seeds = [ALLOCATION_SEED, raffle.key().as_ref(), payer.key().as_ref()], bump = allocation.bump)]
+    pub allocation: Option<Account<'info, Allocation>>,
+    /// Payer's token account proving the raffle's gate (gate mint, or a collection NFT).
+    pub gate_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    /// CHECK: Metaplex metadata of `gate_token_account.mint` for collection gates; checked in `check_gate`.
+    pub gate_metadata: Option<UncheckedAccount<'info>>,
+    
     /// USDC mint (escrow mint)
     #[account(address = raffle.mint)]
     pub usdc_mint: InterfaceAccount<'info, Mint>,
@@ -1580,18 +2502,40 @@ pub struct JoinWithMoga<'info> {
 
 /// Accounts for joining raffle with MRFT (refund ticket NFTs).
 #[cfg(feature = "bubblegum")]
+#[event_cpi]
 #[derive(Accounts)]
-#[instruction(slots: Vec<u32>, _nft_proofs: Vec<u8>)]
+#[instruction(slots: Vec<u32>)]
 pub struct JoinWithTicket<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
     
-    #[account(mut)]
+    #[account(mut, has_one = mint, constraint = raffle.escrow == escrow_ata.key())]
     pub raffle: Account<'info, Raffle>,
+
+    /// Escrow mint; every MRFT must be worth one whole token of it.
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mut, constraint = escrow_ata.owner == raffle.key(), constraint = escrow_ata.mint == mint.key())]
+    pub escrow_ata: InterfaceAccount<'info, TokenAccount>,
+
+    /// Failed raffle that issued the MRFTs; its escrow still holds their backing.
+    #[account(mut, has_one = mint, constraint = origin_raffle.escrow == origin_escrow.key())]
+    pub origin_raffle: Account<'info, Raffle>,
+
+    #[account(mut, constraint = origin_escrow.owner == origin_raffle.key(), constraint = origin_escrow.mint == mint.key())]
+    pub origin_escrow: InterfaceAccount<'info, TokenAccount>,
     
     #[account(mut, seeds = [SLOTS_SEED, raffle.key().as_ref()], bump)]
     pub slots: Account<'info, RaffleSlots>,
     
+    /// Presale allocation; required while the raffle is in its allowlist presale.
+    #[account(mut, seeds = [ALLOCATION_SEED, raffle.key().as_ref(), payer.key().as_ref()], bump = allocation.bump)]
+    pub allocation: Option<Account<'info, Allocation>>,
+    /// Payer's token account proving the raffle's gate (gate mint, or a collection NFT).
+    pub gate_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    /// CHECK: Metaplex metadata of `gate_token_account.mint` for collection gates; checked in `check_gate`.
+    pub gate_metadata: Option<UncheckedAccount<'info>>,
+    
     /// Ticket PDA (created for this join)
     #[account(
         init,
@@ -1601,42 +2545,148 @@ pub struct JoinWithTicket<'info> {
         bump,
     )]
     pub ticket: Account<'info, Ticket>,
+
+    pub bubblegum: MrftRedeem<'info>,
     
-    #[cfg(feature = "bubblegum")]
-    /// CHECK: Merkle tree account for compressed NFT
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Bubblegum accounts for retiring MRFTs (see `retire_mrfts`).
+#[cfg(feature = "bubblegum")]
+#[derive(Accounts)]
+pub struct MrftRedeem<'info> {
+    /// CHECK: `[MRFT_TREE_AUTHORITY_SEED]` PDA: leaf delegate of untraded MRFTs, receives
+    /// MRFTs in `ticket_mode == 1`
+    #[account(seeds = [MRFT_TREE_AUTHORITY_SEED], bump)]
+    pub tree_authority: AccountInfo<'info>,
+    /// CHECK: Merkle tree holding the MRFTs, checked by Bubblegum
+    #[account(mut)]
     pub merkle_tree: AccountInfo<'info>,
-    
-    #[cfg(feature = "bubblegum")]
-    /// CHECK: Tree config PDA
+    /// CHECK: Tree config PDA, checked by Bubblegum
     pub tree_config: AccountInfo<'info>,
-    
-    #[cfg(feature = "bubblegum")]
-    /// CHECK: Log wrapper for compression
+    /// CHECK: SPL Noop log wrapper
+    #[account(address = SPL_NOOP_ID)]
     pub log_wrapper: AccountInfo<'info>,
-    
-    #[cfg(feature = "bubblegum")]
-    pub compression_program: Program<'info, SplAccountCompression>,
-    
-    #[cfg(feature = "bubblegum")]
+    /// CHECK: SPL Account Compression program
+    #[account(address = SPL_ACCOUNT_COMPRESSION_ID)]
+    pub compression_program: AccountInfo<'info>,
     /// CHECK: Bubblegum program
+    #[account(address = MPL_BUBBLEGUM_ID)]
     pub bubblegum_program: AccountInfo<'info>,
-    
     pub system_program: Program<'info, System>,
 }
 
+/// Accounts for selling MRFTs back to the platform refund vault.
+#[cfg(feature = "bubblegum")]
+#[event_cpi]
+#[derive(Accounts)]
+pub struct BuyBackMrfts<'info> {
+    #[account(mut)]
+    pub holder: Signer<'info>,
+
+    /// Failed raffle that issued the MRFTs; its escrow still holds their backing.
+    #[account(mut, has_one = mint, constraint = origin_raffle.escrow == origin_escrow.key())]
+    pub origin_raffle: Account<'info, Raffle>,
+
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mut, constraint = origin_escrow.owner == origin_raffle.key(), constraint = origin_escrow.mint == mint.key())]
+    pub origin_escrow: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: `[REFUND_VAULT_SEED]` PDA owning the refund vault
+    #[account(seeds = [REFUND_VAULT_SEED], bump)]
+    pub vault_authority: AccountInfo<'info>,
+
+    /// Platform refund vault: `vault_authority`'s token account for `mint`.
+    #[account(mut, constraint = refund_vault.owner == vault_authority.key(), constraint = refund_vault.mint == mint.key())]
+    pub refund_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut, constraint = holder_ata.owner == holder.key(), constraint = holder_ata.mint == mint.key())]
+    pub holder_ata: InterfaceAccount<'info, TokenAccount>,
+
+    pub bubblegum: MrftRedeem<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct InitializePlatformConfig<'info> {
+    #[account(mut)]
+    pub upgrade_authority: Signer<'info>,
+    #[account(init, payer = upgrade_authority, space = 8 + PlatformConfig::LEN, seeds = [PLATFORM_CONFIG_SEED], bump)]
+    pub config: Account<'info, PlatformConfig>,
+    #[account(constraint = raffle_program.programdata_address()? == Some(program_data.key()))]
+    pub raffle_program: Program<'info, crate::program::RwaRaffle>,
+    #[account(constraint = program_data.upgrade_authority_address == Some(upgrade_authority.key()) @ RaffleError::Unauthorized)]
+    pub program_data: Account<'info, ProgramData>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetPlatformAuthority<'info> {
+    #[account(mut, seeds = [PLATFORM_CONFIG_SEED], bump = config.bump, has_one = authority @ RaffleError::Unauthorized)]
+    pub config: Account<'info, PlatformConfig>,
+    pub authority: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct WithdrawRefundVault<'info> {
+    #[account(seeds = [PLATFORM_CONFIG_SEED], bump = config.bump, has_one = authority @ RaffleError::Unauthorized)]
+    pub config: Account<'info, PlatformConfig>,
+    pub authority: Signer<'info>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    /// CHECK: `[REFUND_VAULT_SEED]` PDA owning the refund vault
+    #[account(seeds = [REFUND_VAULT_SEED], bump)]
+    pub vault_authority: AccountInfo<'info>,
+    #[account(mut, constraint = refund_vault.owner == vault_authority.key(), constraint = refund_vault.mint == mint.key())]
+    pub refund_vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut, constraint = destination.mint == mint.key())]
+    pub destination: InterfaceAccount<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[event_cpi]
 #[derive(Accounts)]
 pub struct RequestDraw<'info> {
-    pub organizer: Signer<'info>,
+    /// Any keeper (or the organizer); receives the draw bounty.
+    #[account(mut)]
+    pub caller: Signer<'info>,
     #[account(mut)]
     pub raffle: Account<'info, Raffle>,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct SettleDraw<'info> {
+    #[account(seeds = [PLATFORM_CONFIG_SEED], bump = config.bump, has_one = authority @ RaffleError::Unauthorized)]
+    pub config: Account<'info, PlatformConfig>,
+    /// Draw oracle: the platform authority.
+    pub authority: Signer<'info>,
+    #[account(mut)]
+    pub raffle: Account<'info, Raffle>,
+    /// Ticket owners, to keep forfeited winners out of a redraw.
+    #[account(seeds = [SLOTS_SEED, raffle.key().as_ref()], bump)]
+    pub slots: Account<'info, RaffleSlots>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ForfeitWinner<'info> {
+    /// Keeper running the crank; receives the bounty.
+    #[account(mut)]
+    pub caller: Signer<'info>,
     #[account(mut)]
     pub raffle: Account<'info, Raffle>,
+    #[account(seeds = [SLOTS_SEED, raffle.key().as_ref()], bump)]
+    pub slots: Account<'info, RaffleSlots>,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct ClaimRefund<'info> {
     #[account(mut)]
@@ -1648,20 +2698,63 @@ pub struct ClaimRefund<'info> {
     pub payer_ata: InterfaceAccount<'info, TokenAccount>,
     #[account(mut, constraint = escrow_ata.owner == raffle.key(), constraint = escrow_ata.mint == mint.key())]
     pub escrow_ata: InterfaceAccount<'info, TokenAccount>,
-    #[account(mut, seeds = [TICKET_SEED, raffle.key().as_ref(), payer.key().as_ref(), &ticket.start.to_le_bytes()], bump = ticket.bump)]
+    /// Either PDA layout is accepted, as in `transfer_ticket`; the handler checks its raffle.
+    #[account(mut)]
     pub ticket: Account<'info, Ticket>,
+    /// Payer's token account holding the ticket NFT; required for tokenized tickets.
+    pub holder_ticket_account: Option<InterfaceAccount<'info, TokenAccount>>,
     pub token_program: Program<'info, Token>,
+    pub mrft: MrftMint<'info>,
+}
+
+/// Bubblegum accounts for minting MRFTs in `claim_refund`. Leave them out to have the
+/// off-chain worker mint from `RefundTicketsRequested` instead.
+#[derive(Accounts)]
+pub struct MrftMint<'info> {
+    /// CHECK: `[MRFT_TREE_AUTHORITY_SEED]` PDA, checked in `mint_mrfts`
+    pub tree_authority: Option<UncheckedAccount<'info>>,
+    /// CHECK: Bubblegum tree config delegated to `tree_authority`, checked by Bubblegum
+    #[account(mut)]
+    pub tree_config: Option<UncheckedAccount<'info>>,
+    /// CHECK: MRFT merkle tree, checked by Bubblegum against `tree_config`
+    #[account(mut)]
+    pub merkle_tree: Option<UncheckedAccount<'info>>,
+    /// CHECK: Must be `MRFT_COLLECTION_MINT`, checked in `mint_mrfts`
+    pub collection_mint: Option<UncheckedAccount<'info>>,
+    /// CHECK: Metaplex metadata of the MRFT collection, checked by Bubblegum
+    #[account(mut)]
+    pub collection_metadata: Option<UncheckedAccount<'info>>,
+    /// CHECK: Metaplex master edition of the MRFT collection, checked by Bubblegum
+    pub collection_edition: Option<UncheckedAccount<'info>>,
+    /// CHECK: Collection authority record delegating the collection to `tree_authority`
+    pub collection_authority_record: Option<UncheckedAccount<'info>>,
+    /// CHECK: Bubblegum's collection CPI signer, checked by Bubblegum
+    pub bubblegum_signer: Option<UncheckedAccount<'info>>,
+    /// CHECK: SPL Noop log wrapper, checked in `mint_mrfts`
+    pub log_wrapper: Option<UncheckedAccount<'info>>,
+    /// CHECK: SPL Account Compression program, checked in `mint_mrfts`
+    pub compression_program: Option<UncheckedAccount<'info>>,
+    /// CHECK: Metaplex Token Metadata program, checked by Bubblegum
+    pub token_metadata_program: Option<UncheckedAccount<'info>>,
+    /// CHECK: Bubblegum program, checked in `mint_mrfts`
+    pub bubblegum_program: Option<UncheckedAccount<'info>>,
+    pub system_program: Option<Program<'info, System>>,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct ClaimWin<'info> {
     pub winner: Signer<'info>,
     #[account(mut)]
     pub raffle: Account<'info, Raffle>,
-    #[account(mut, seeds = [TICKET_SEED, raffle.key().as_ref(), winner.key().as_ref(), &ticket.start.to_le_bytes()], bump = ticket.bump)]
+    /// Either PDA layout is accepted, as in `transfer_ticket`; the handler checks its raffle.
+    #[account(mut)]
     pub ticket: Account<'info, Ticket>,
+    /// Winner's token account holding the ticket NFT; required for tokenized tickets.
+    pub holder_ticket_account: Option<InterfaceAccount<'info, TokenAccount>>,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct SetPrizeNft<'info> {
     #[account(mut)]
@@ -1673,33 +2766,388 @@ pub struct SetPrizeNft<'info> {
     pub organizer_prize_ata: InterfaceAccount<'info, TokenAccount>,
     #[account(mut, constraint = prize_escrow.owner == raffle.key(), constraint = prize_escrow.mint == prize_mint.key())]
     pub prize_escrow: InterfaceAccount<'info, TokenAccount>,
+    /// CHECK: Metaplex metadata of `prize_mint`, validated in `verify_prize_nft` (`metaplex` builds)
+    #[account(mut)]
+    pub prize_metadata: Option<UncheckedAccount<'info>>,
+    /// CHECK: Metaplex master edition of `prize_mint`, validated in `verify_prize_nft` (`metaplex` builds)
+    pub prize_master_edition: Option<UncheckedAccount<'info>>,
     pub token_program: Program<'info, Token>,
+    pub pnft: ProgrammableTransfer<'info>,
 }
 
+/// Accounts for escrowing a compressed prize; the leaf's proof path follows as remaining accounts.
+#[cfg(feature = "bubblegum")]
+#[event_cpi]
 #[derive(Accounts)]
-pub struct ClaimPrize<'info> {
-    pub winner: Signer<'info>,
+pub struct SetPrizeCnft<'info> {
     #[account(mut)]
+    pub organizer: Signer<'info>,
+    #[account(mut, has_one = organizer @ RaffleError::Unauthorized)]
     pub raffle: Account<'info, Raffle>,
-    pub prize_mint: InterfaceAccount<'info, Mint>,
-    #[account(mut, constraint = prize_escrow.owner == raffle.key(), constraint = prize_escrow.mint == prize_mint.key())]
-    pub prize_escrow: InterfaceAccount<'info, TokenAccount>,
-    #[account(mut, constraint = winner_prize_ata.owner == winner.key(), constraint = winner_prize_ata.mint == prize_mint.key())]
-    pub winner_prize_ata: InterfaceAccount<'info, TokenAccount>,
-    #[account(seeds = [TICKET_SEED, raffle.key().as_ref(), winner.key().as_ref(), &ticket.start.to_le_bytes()], bump = ticket.bump)]
-    pub ticket: Account<'info, Ticket>,
-    pub token_program: Program<'info, Token>,
+    /// CHECK: Merkle tree holding the prize leaf, checked by Bubblegum
+    #[account(mut)]
+    pub merkle_tree: AccountInfo<'info>,
+    /// CHECK: Tree config PDA, checked by Bubblegum
+    pub tree_config: AccountInfo<'info>,
+    /// CHECK: SPL Noop log wrapper
+    #[account(address = SPL_NOOP_ID)]
+    pub log_wrapper: AccountInfo<'info>,
+    /// CHECK: SPL Account Compression program
+    #[account(address = SPL_ACCOUNT_COMPRESSION_ID)]
+    pub compression_program: AccountInfo<'info>,
+    /// CHECK: Bubblegum program
+    #[account(address = MPL_BUBBLEGUM_ID)]
+    pub bubblegum_program: AccountInfo<'info>,
+    pub system_program: Program<'info, System>,
 }
 
-/// Accounts for join_with_moga_with_permit (backend-signed permit path)
-#[cfg(feature = "pyth-jupiter")]
+/// Extra accounts Token Metadata's `TransferV1` needs to move a programmable prize (pNFT).
+/// Leave them out for regular NFTs.
 #[derive(Accounts)]
-#[instruction(slots: Vec<u32>, max_moga_in: u64, permit_nonce: [u8; 16], permit_expiry_unix_ts: i64)]
-pub struct JoinWithMogaWithPermit<'info> {
+pub struct ProgrammableTransfer<'info> {
+    /// CHECK: Metaplex Token Metadata program, checked in `transfer_prize`
+    pub token_metadata_program: Option<UncheckedAccount<'info>>,
+    /// CHECK: Token record of the sending token account, checked by Token Metadata
     #[account(mut)]
-    pub payer: Signer<'info>,
-    
-    #[account(mut, has_one = mint)]
+    pub source_token_record: Option<UncheckedAccount<'info>>,
+    /// CHECK: Token record of the receiving token account, created by Token Metadata
+    #[account(mut)]
+    pub destination_token_record: Option<UncheckedAccount<'info>>,
+    /// CHECK: Instructions sysvar, checked by Token Metadata
+    pub sysvar_instructions: Option<UncheckedAccount<'info>>,
+    pub associated_token_program: Option<Program<'info, AssociatedToken>>,
+    pub system_program: Option<Program<'info, System>>,
+    /// CHECK: Token Auth Rules program, for pNFTs with a rule set
+    pub authorization_rules_program: Option<UncheckedAccount<'info>>,
+    /// CHECK: The pNFT's rule set, checked by Token Metadata
+    pub authorization_rules: Option<UncheckedAccount<'info>>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetAllowlist<'info> {
+    pub organizer: Signer<'info>,
+    #[account(mut, has_one = organizer @ RaffleError::Unauthorized)]
+    pub raffle: Account<'info, Raffle>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetGate<'info> {
+    pub organizer: Signer<'info>,
+    #[account(mut, has_one = organizer @ RaffleError::Unauthorized)]
+    pub raffle: Account<'info, Raffle>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetPrizeBacked<'info> {
+    pub organizer: Signer<'info>,
+    #[account(mut, has_one = organizer @ RaffleError::Unauthorized)]
+    pub raffle: Account<'info, Raffle>,
+    /// CHECK: Metadata of `raffle.prize_collection_mint`, validated in `verify_prize_collection`
+    pub collection_metadata: Option<UncheckedAccount<'info>>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetClaimWindow<'info> {
+    pub organizer: Signer<'info>,
+    #[account(mut, has_one = organizer @ RaffleError::Unauthorized)]
+    pub raffle: Account<'info, Raffle>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RegisterAllowlist<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub raffle: Account<'info, Raffle>,
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + Allocation::LEN,
+        seeds = [ALLOCATION_SEED, raffle.key().as_ref(), payer.key().as_ref()],
+        bump,
+    )]
+    pub allocation: Account<'info, Allocation>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CloseAllocation<'info> {
+    /// Anyone may close an allocation after the presale; rent always goes to the wallet.
+    pub caller: Signer<'info>,
+    pub raffle: Account<'info, Raffle>,
+    #[account(
+        mut,
+        close = wallet,
+        has_one = raffle @ RaffleError::WrongRaffle,
+        has_one = wallet @ RaffleError::Unauthorized,
+        seeds = [ALLOCATION_SEED, raffle.key().as_ref(), wallet.key().as_ref()],
+        bump = allocation.bump,
+    )]
+    pub allocation: Account<'info, Allocation>,
+    /// CHECK: Allocation wallet, receives the rent; checked by `has_one` on allocation.
+    #[account(mut)]
+    pub wallet: UncheckedAccount<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClaimPrize<'info> {
+    #[account(mut)]
+    pub winner: Signer<'info>,
+    #[account(mut)]
+    pub raffle: Account<'info, Raffle>,
+    /// SPL prize mint, escrow and the winner's token account; checked in `claim_prize`.
+    /// Left out for compressed prizes.
+    pub prize_mint: Option<InterfaceAccount<'info, Mint>>,
+    #[account(mut)]
+    pub prize_escrow: Option<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut)]
+    pub winner_prize_ata: Option<InterfaceAccount<'info, TokenAccount>>,
+    /// Either PDA layout is accepted, as in `transfer_ticket`; the handler checks its raffle.
+    pub ticket: Account<'info, Ticket>,
+    /// Winner's token account holding the ticket NFT; required for tokenized tickets.
+    pub holder_ticket_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    /// CHECK: Metaplex metadata of `prize_mint`; tells programmable prizes apart (`metaplex` builds)
+    #[account(mut)]
+    pub prize_metadata: Option<UncheckedAccount<'info>>,
+    /// CHECK: Metaplex master edition of `prize_mint`, checked by Token Metadata
+    pub prize_master_edition: Option<UncheckedAccount<'info>>,
+    pub token_program: Program<'info, Token>,
+    pub pnft: ProgrammableTransfer<'info>,
+    pub cnft: CompressedTransfer<'info>,
+}
+
+/// Bubblegum accounts that move a compressed prize out of escrow (see `set_prize_cnft`);
+/// the leaf's proof path follows as remaining accounts. Leave them out for SPL prizes.
+#[derive(Accounts)]
+pub struct CompressedTransfer<'info> {
+    /// CHECK: Bubblegum program, checked in `transfer_compressed_prize`
+    pub bubblegum_program: Option<UncheckedAccount<'info>>,
+    /// CHECK: Bubblegum tree config of `merkle_tree`, checked by Bubblegum
+    pub tree_config: Option<UncheckedAccount<'info>>,
+    /// CHECK: Merkle tree holding the prize leaf, checked against `raffle.prize_cnft`
+    #[account(mut)]
+    pub merkle_tree: Option<UncheckedAccount<'info>>,
+    /// CHECK: SPL Noop log wrapper, checked in `transfer_compressed_prize`
+    pub log_wrapper: Option<UncheckedAccount<'info>>,
+    /// CHECK: SPL Account Compression program, checked in `transfer_compressed_prize`
+    pub compression_program: Option<UncheckedAccount<'info>>,
+    /// CHECK: Organizer, still the leaf owner of a delegated prize; proven by the leaf hash
+    pub leaf_owner: Option<UncheckedAccount<'info>>,
+    pub system_program: Option<Program<'info, System>>,
+}
+
+#[derive(Accounts)]
+pub struct CloseTicket<'info> {
+    /// Anyone may close a settled ticket; rent always goes to the ticket's payer.
+    pub caller: Signer<'info>,
+    #[account(mut)]
+    pub raffle: Account<'info, Raffle>,
+    /// Either PDA layout is accepted, as in `transfer_ticket`.
+    #[account(
+        mut,
+        close = payer,
+        has_one = raffle @ RaffleError::WrongRaffle,
+        has_one = payer @ RaffleError::Unauthorized,
+    )]
+    pub ticket: Account<'info, Ticket>,
+    /// CHECK: Paid the ticket's rent and receives it back; checked by `has_one` on ticket.
+    #[account(mut)]
+    pub payer: UncheckedAccount<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct TransferTicket<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub raffle: Account<'info, Raffle>,
+    #[account(mut, seeds = [SLOTS_SEED, raffle.key().as_ref()], bump)]
+    pub slots: Account<'info, RaffleSlots>,
+    /// Either PDA layout is accepted (range deposits seed by `start`, slot joins by the first
+    /// slot); the re-created ticket always uses the range layout.
+    #[account(
+        mut,
+        close = payer,
+        has_one = raffle @ RaffleError::WrongRaffle,
+        has_one = owner @ RaffleError::Unauthorized,
+        has_one = payer @ RaffleError::Unauthorized,
+    )]
+    pub ticket: Account<'info, Ticket>,
+    /// CHECK: Paid the old ticket's rent and receives it back; checked by `has_one` on ticket.
+    #[account(mut)]
+    pub payer: UncheckedAccount<'info>,
+    /// CHECK: New owner; only used as a PDA seed and stored on the ticket.
+    pub recipient: UncheckedAccount<'info>,
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + Ticket::LEN,
+        seeds = [TICKET_SEED, raffle.key().as_ref(), recipient.key().as_ref(), &ticket.start.to_le_bytes()],
+        bump,
+    )]
+    pub new_ticket: Account<'info, Ticket>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct TokenizeTicket<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub raffle: Account<'info, Raffle>,
+    #[account(mut, seeds = [SLOTS_SEED, raffle.key().as_ref()], bump)]
+    pub slots: Account<'info, RaffleSlots>,
+    /// Either PDA layout is accepted, as in `transfer_ticket`.
+    #[account(
+        mut,
+        close = payer,
+        has_one = raffle @ RaffleError::WrongRaffle,
+        has_one = owner @ RaffleError::Unauthorized,
+        has_one = payer @ RaffleError::Unauthorized,
+    )]
+    pub ticket: Account<'info, Ticket>,
+    /// CHECK: Paid the old ticket's rent and receives it back; checked by `has_one` on ticket.
+    #[account(mut)]
+    pub payer: UncheckedAccount<'info>,
+    /// Ticket NFT; the raffle PDA mints it and owns its metadata.
+    #[account(
+        init,
+        payer = owner,
+        seeds = [TICKET_MINT_SEED, raffle.key().as_ref(), &ticket.start.to_le_bytes()],
+        bump,
+        mint::decimals = 0,
+        mint::authority = raffle,
+        mint::token_program = token_2022_program,
+        extensions::metadata_pointer::authority = raffle,
+        extensions::metadata_pointer::metadata_address = ticket_mint,
+    )]
+    pub ticket_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        init,
+        payer = owner,
+        associated_token::mint = ticket_mint,
+        associated_token::authority = owner,
+        associated_token::token_program = token_2022_program,
+    )]
+    pub owner_ticket_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + Ticket::LEN,
+        seeds = [TICKET_SEED, raffle.key().as_ref(), ticket_mint.key().as_ref(), &ticket.start.to_le_bytes()],
+        bump,
+    )]
+    pub new_ticket: Account<'info, Ticket>,
+    pub token_2022_program: Program<'info, Token2022>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CloseSlots<'info> {
+    pub caller: Signer<'info>,
+    #[account(mut, has_one = organizer)]
+    pub raffle: Account<'info, Raffle>,
+    #[account(mut, close = organizer, seeds = [SLOTS_SEED, raffle.key().as_ref()], bump)]
+    pub slots: Account<'info, RaffleSlots>,
+    /// CHECK: Raffle organizer, receives the rent; checked by `has_one` on raffle.
+    #[account(mut)]
+    pub organizer: UncheckedAccount<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CloseRaffle<'info> {
+    pub caller: Signer<'info>,
+    #[account(
+        mut,
+        close = organizer,
+        has_one = organizer,
+        constraint = raffle.escrow == escrow_ata.key(),
+        seeds = [RAFFLE_SEED, raffle.mint.as_ref(), organizer.key().as_ref()],
+        bump = raffle.bump,
+    )]
+    pub raffle: Account<'info, Raffle>,
+    #[account(mut, constraint = escrow_ata.owner == raffle.key())]
+    pub escrow_ata: InterfaceAccount<'info, TokenAccount>,
+    /// CHECK: Raffle organizer, receives the rent; checked by `has_one` on raffle.
+    #[account(mut)]
+    pub organizer: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SweepMrftBacking<'info> {
+    /// Any keeper.
+    pub caller: Signer<'info>,
+    #[account(mut, has_one = mint, constraint = raffle.escrow == escrow_ata.key())]
+    pub raffle: Account<'info, Raffle>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(mut, constraint = escrow_ata.owner == raffle.key(), constraint = escrow_ata.mint == mint.key())]
+    pub escrow_ata: InterfaceAccount<'info, TokenAccount>,
+    /// CHECK: `[REFUND_VAULT_SEED]` PDA owning the refund vault
+    #[account(seeds = [REFUND_VAULT_SEED], bump)]
+    pub vault_authority: AccountInfo<'info>,
+    /// Platform refund vault: `vault_authority`'s token account for `mint`.
+    #[account(mut, constraint = refund_vault.owner == vault_authority.key(), constraint = refund_vault.mint == mint.key())]
+    pub refund_vault: InterfaceAccount<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct AuditRaffle<'info> {
+    #[account(has_one = mint, constraint = raffle.escrow == escrow_ata.key())]
+    pub raffle: Account<'info, Raffle>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(constraint = escrow_ata.owner == raffle.key(), constraint = escrow_ata.mint == mint.key())]
+    pub escrow_ata: InterfaceAccount<'info, TokenAccount>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct MigrateRaffle<'info> {
+    /// Pays the additional rent for the larger layout.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: Legacy layouts cannot be loaded as `Account<Raffle>`; the discriminator and
+    /// layout are validated in the handler and the owner is checked here.
+    #[account(mut, owner = crate::ID)]
+    pub raffle: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct MigrateTicket<'info> {
+    /// Pays the additional rent for the larger layout.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: Legacy layouts cannot be loaded as `Account<Ticket>`; the discriminator and
+    /// layout are validated in the handler and the owner is checked here.
+    #[account(mut, owner = crate::ID)]
+    pub ticket: UncheckedAccount<'info>,
+    /// Raffle of the ticket, already migrated; counts legacy refunds.
+    #[account(mut)]
+    pub raffle: Account<'info, Raffle>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for join_with_moga_with_permit (backend-signed permit path)
+#[cfg(feature = "pyth-jupiter")]
+#[derive(Accounts)]
+#[instruction(slots: Vec<u32>, max_moga_in: u64, permit_nonce: [u8; 16], permit_expiry_unix_ts: i64)]
+pub struct JoinWithMogaWithPermit<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    #[account(mut, has_one = mint)]
     pub raffle: Account<'info, Raffle>,
     
     #[account(mut, seeds = [SLOTS_SEED, raffle.key().as_ref()], bump)]
@@ -1748,6 +3196,7 @@ pub struct JoinWithMogaWithPermit<'info> {
 
 /// Accounts for claim_prize_mint (post-mint path)
 #[cfg(feature = "metaplex")]
+#[event_cpi]
 #[derive(Accounts)]
 pub struct ClaimPrizeMint<'info> {
     #[account(mut)]
@@ -1783,8 +3232,10 @@ pub struct ClaimPrizeMint<'info> {
     /// CHECK: Collection authority (must sign for verification)
     pub collection_authority: Signer<'info>,
     
-    #[account(seeds = [TICKET_SEED, raffle.key().as_ref(), winner.key().as_ref(), &ticket.start.to_le_bytes()], bump = ticket.bump)]
+    /// Either PDA layout is accepted, as in `transfer_ticket`; the handler checks its raffle.
     pub ticket: Account<'info, Ticket>,
+    /// Winner's token account holding the ticket NFT; required for tokenized tickets.
+    pub holder_ticket_account: Option<InterfaceAccount<'info, TokenAccount>>,
     
     /// CHECK: Token Metadata program
     pub token_metadata_program: AccountInfo<'info>,
@@ -1798,6 +3249,10 @@ pub struct ClaimPrizeMint<'info> {
     pub rent: Sysvar<'info, Rent>,
 }
 
+/// One `batch_create_raffles` config: (required_tickets, deadline, auto_draw, ticket_mode,
+/// reveal_time_unix_ts, prize_metadata_hash, mrft_collection).
+pub type BatchRaffleConfig = (u64, i64, bool, u8, Option<i64>, [u8; 32], Pubkey);
+
 /// Accounts for batch_create_raffles
 #[derive(Accounts)]
 pub struct BatchCreateRaffles<'info> {
@@ -1812,14 +3267,896 @@ pub struct BatchCreateRaffles<'info> {
     // Note: Raffle, escrow, and slots accounts passed via remaining_accounts
 }
 
+/// Move the organizer's crank reserve into the raffle PDA at creation.
+fn fund_crank_reserve<'info>(
+    organizer: &Signer<'info>,
+    raffle: &AccountInfo<'info>,
+    system_program: &Program<'info, System>,
+    lamports: u64,
+) -> Result<()> {
+    if lamports == 0 {
+        return Ok(());
+    }
+    anchor_lang::system_program::transfer(
+        CpiContext::new(
+            system_program.to_account_info(),
+            anchor_lang::system_program::Transfer {
+                from: organizer.to_account_info(),
+                to: raffle.clone(),
+            },
+        ),
+        lamports,
+    )
+}
+
+/// Pay a keeper bounty out of the raffle's crank reserve and return the amount paid.
+/// The payout is capped by what is left in the reserve, so an exhausted reserve never
+/// blocks the crank.
+fn pay_keeper_bounty<'info>(
+    raffle: &mut Account<'info, Raffle>,
+    keeper: &AccountInfo<'info>,
+    bounty: u64,
+) -> Result<u64> {
+    let amount = bounty.min(raffle.crank_reserve);
+    if amount == 0 {
+        return Ok(0);
+    }
+    raffle.crank_reserve -= amount;
+    let raffle_info = raffle.to_account_info();
+    **raffle_info.try_borrow_mut_lamports()? = raffle_info.lamports().checked_sub(amount).ok_or(RaffleError::Overflow)?;
+    **keeper.try_borrow_mut_lamports()? = keeper.lamports().checked_add(amount).ok_or(RaffleError::Overflow)?;
+    Ok(amount)
+}
+
+/// Charge `tickets` against the payer's presale allocation while the presale runs.
+/// Public sales ignore the allocation account.
+fn consume_allocation(
+    raffle: &Raffle,
+    allocation: &mut Option<Account<'_, Allocation>>,
+    tickets: u64,
+    now: i64,
+) -> Result<()> {
+    if !raffle.is_presale(now) {
+        return Ok(());
+    }
+    let allocation = allocation.as_mut().ok_or(RaffleError::NotAllowlisted)?;
+    // An allocation left over from an earlier raffle at this PDA was proven against another list
+    require!(allocation.allowlist_root == raffle.allowlist_root, RaffleError::NotAllowlisted);
+    let used = allocation.used.checked_add(tickets).ok_or(RaffleError::Overflow)?;
+    require!(used <= allocation.max_allocation, RaffleError::AllocationExceeded);
+    allocation.used = used;
+    Ok(())
+}
+
+/// Enforce the raffle's token gate (see `set_gate`) for `payer`.
+fn check_gate(
+    raffle: &Raffle,
+    payer: &Pubkey,
+    gate_token_account: Option<&InterfaceAccount<'_, TokenAccount>>,
+    gate_metadata: Option<&UncheckedAccount<'_>>,
+) -> Result<()> {
+    if raffle.gate_mode == 0 {
+        return Ok(());
+    }
+    let token_account = gate_token_account.ok_or(RaffleError::GateNotMet)?;
+    require_keys_eq!(token_account.owner, *payer, RaffleError::GateNotMet);
+    match raffle.gate_mode {
+        1 => {
+            require_keys_eq!(token_account.mint, raffle.gate_mint, RaffleError::GateNotMet);
+            require!(token_account.amount >= raffle.gate_min_amount, RaffleError::GateNotMet);
+            Ok(())
+        }
+        2 => {
+            require!(token_account.amount > 0, RaffleError::GateNotMet);
+            verify_collection_member(&token_account.mint, gate_metadata, &raffle.gate_mint)
+        }
+        _ => err!(RaffleError::InvalidGate),
+    }
+}
+
+/// `mint`'s Metaplex metadata, passed as `metadata`; `InvalidCollection` unless it is that
+/// Token Metadata account.
+#[cfg(feature = "metaplex")]
+fn load_metadata(mint: &Pubkey, metadata: Option<&UncheckedAccount<'_>>) -> Result<mpl_token_metadata::accounts::Metadata> {
+    use mpl_token_metadata::accounts::Metadata;
+
+    let metadata = metadata.ok_or(RaffleError::InvalidCollection)?;
+    require_keys_eq!(*metadata.owner, mpl_token_metadata::ID, RaffleError::InvalidCollection);
+    require_keys_eq!(metadata.key(), Metadata::find_pda(mint).0, RaffleError::InvalidCollection);
+    Metadata::safe_deserialize(&metadata.try_borrow_data()?).map_err(|_| error!(RaffleError::InvalidCollection))
+}
+
+/// `mint`'s Metaplex metadata (passed as `metadata`) names `collection` as its verified collection.
+#[cfg(feature = "metaplex")]
+fn verify_collection_member(mint: &Pubkey, metadata: Option<&UncheckedAccount<'_>>, collection: &Pubkey) -> Result<()> {
+    let data = load_metadata(mint, metadata)?;
+    require!(
+        data.collection.is_some_and(|c| c.verified && c.key == *collection),
+        RaffleError::InvalidCollection
+    );
+    Ok(())
+}
+
+/// Collection gates can't be set without the `metaplex` feature.
+#[cfg(not(feature = "metaplex"))]
+fn verify_collection_member(_mint: &Pubkey, _metadata: Option<&UncheckedAccount<'_>>, _collection: &Pubkey) -> Result<()> {
+    err!(RaffleError::InvalidGate)
+}
+
+/// Pre-escrowed prizes are 1/1s from the raffle's collection (see `set_prize_nft`); the
+/// rules themselves are in `check_prize_nft`.
+#[cfg(feature = "metaplex")]
+fn verify_prize_nft(
+    raffle: &Raffle,
+    mint: &Pubkey,
+    metadata: Option<&UncheckedAccount<'_>>,
+    master_edition: Option<&UncheckedAccount<'_>>,
+) -> Result<()> {
+    use mpl_token_metadata::accounts::MasterEdition;
+
+    let data = load_metadata(mint, metadata)?;
+    let master_edition = master_edition.ok_or(RaffleError::InvalidMasterEdition)?;
+    require_keys_eq!(*master_edition.owner, mpl_token_metadata::ID, RaffleError::InvalidMasterEdition);
+    require_keys_eq!(master_edition.key(), MasterEdition::find_pda(mint).0, RaffleError::InvalidMasterEdition);
+    let edition = MasterEdition::safe_deserialize(&master_edition.try_borrow_data()?)
+        .map_err(|_| RaffleError::InvalidMasterEdition)?;
+    check_prize_nft(raffle, data.collection.map(|c| (c.key, c.verified)), edition.max_supply)
+}
+
+/// A prize NFT's deserialized Metaplex fields pass if its `collection` (`(key, verified)`)
+/// is the raffle's prize collection, verified, and its master edition allows no prints
+/// (`max_supply == Some(0)`).
+#[cfg_attr(not(feature = "metaplex"), allow(dead_code))]
+fn check_prize_nft(raffle: &Raffle, collection: Option<(Pubkey, bool)>, max_supply: Option<u64>) -> Result<()> {
+    let expected = if raffle.prize_collection_mint != Pubkey::default() {
+        raffle.prize_collection_mint
+    } else {
+        PRIZE_COLLECTION_MINT
+    };
+    require!(
+        collection.is_some_and(|(key, verified)| verified && key == expected),
+        RaffleError::InvalidCollection
+    );
+    require!(max_supply == Some(0), RaffleError::InvalidMasterEdition);
+    Ok(())
+}
+
+/// Without Metaplex there is no collection to check; `set_prize_nft` relies on `decimals == 0`.
+#[cfg(not(feature = "metaplex"))]
+fn verify_prize_nft(
+    _raffle: &Raffle,
+    _mint: &Pubkey,
+    _metadata: Option<&UncheckedAccount<'_>>,
+    _master_edition: Option<&UncheckedAccount<'_>>,
+) -> Result<()> {
+    Ok(())
+}
+
+/// Token accounts and owners a prize NFT moves between (see `transfer_prize`).
+/// `to_owner` and `payer` only matter for `TransferV1`.
+#[cfg_attr(not(feature = "metaplex"), allow(dead_code))]
+struct PrizeMove<'info> {
+    from: AccountInfo<'info>,
+    from_owner: AccountInfo<'info>,
+    to: AccountInfo<'info>,
+    to_owner: AccountInfo<'info>,
+    mint: AccountInfo<'info>,
+    payer: AccountInfo<'info>,
+    token_program: AccountInfo<'info>,
+}
+
+/// Move the prize NFT, signed by `from_owner` (with `signer_seeds` when it is the raffle PDA).
+/// Programmable NFTs stay frozen in their token account and only move through Token
+/// Metadata's `TransferV1`; every other NFT uses `transfer_checked`.
+fn transfer_prize<'info>(
+    prize: PrizeMove<'info>,
+    metadata: Option<&UncheckedAccount<'info>>,
+    master_edition: Option<&UncheckedAccount<'info>>,
+    pnft: &ProgrammableTransfer<'info>,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    if is_programmable(&prize.mint.key(), metadata)? {
+        return transfer_programmable(prize, metadata, master_edition, pnft, signer_seeds);
+    }
+    let cpi_accounts = TransferChecked { from: prize.from, to: prize.to, mint: prize.mint, authority: prize.from_owner };
+    token::transfer_checked(CpiContext::new_with_signer(prize.token_program, cpi_accounts, signer_seeds), 1, 0)
+}
+
+/// `metadata` is `mint`'s Metaplex metadata with `TokenStandard::ProgrammableNonFungible`.
+/// Mints without Metaplex metadata are regular NFTs.
+#[cfg(feature = "metaplex")]
+fn is_programmable(mint: &Pubkey, metadata: Option<&UncheckedAccount<'_>>) -> Result<bool> {
+    use mpl_token_metadata::accounts::Metadata;
+    use mpl_token_metadata::types::TokenStandard;
+
+    let account = metadata.map(|m| (m.key(), *m.owner));
+    programmable_from_metadata(account, || Metadata::find_pda(mint).0, || {
+        let metadata = metadata.ok_or(RaffleError::InvalidPrizeAccounts)?;
+        let data = Metadata::safe_deserialize(&metadata.try_borrow_data()?).map_err(|_| RaffleError::InvalidPrizeAccounts)?;
+        Ok(data.token_standard == Some(TokenStandard::ProgrammableNonFungible))
+    })
+}
+
+/// The decision behind `is_programmable`, given the prize's metadata account as `(key, owner)`:
+/// none, or one Token Metadata doesn't own, is a regular NFT. Token Metadata's account must be
+/// `mint`'s `metadata_pda`, and then its token standard (`programmable`) decides.
+#[cfg_attr(not(feature = "metaplex"), allow(dead_code))]
+fn programmable_from_metadata(
+    metadata: Option<(Pubkey, Pubkey)>,
+    metadata_pda: impl FnOnce() -> Pubkey,
+    programmable: impl FnOnce() -> Result<bool>,
+) -> Result<bool> {
+    let Some((key, owner)) = metadata else {
+        return Ok(false);
+    };
+    if owner != MPL_TOKEN_METADATA_ID {
+        return Ok(false);
+    }
+    let expected = metadata_pda();
+    require_keys_eq!(key, expected, RaffleError::InvalidPrizeAccounts);
+    programmable()
+}
+
+/// Programmable NFTs need Metaplex; without it every prize is a regular SPL NFT.
+#[cfg(not(feature = "metaplex"))]
+fn is_programmable(_mint: &Pubkey, _metadata: Option<&UncheckedAccount<'_>>) -> Result<bool> {
+    Ok(false)
+}
+
+/// Token Metadata `TransferV1` for a pNFT; token records and the rule set come from `pnft`.
+#[cfg(feature = "metaplex")]
+fn transfer_programmable<'info>(
+    prize: PrizeMove<'info>,
+    metadata: Option<&UncheckedAccount<'info>>,
+    master_edition: Option<&UncheckedAccount<'info>>,
+    pnft: &ProgrammableTransfer<'info>,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    use mpl_token_metadata::instructions::TransferV1CpiBuilder;
+
+    let call = TransferV1Call::new(prize, metadata, master_edition, pnft)?;
+    TransferV1CpiBuilder::new(&call.token_metadata_program)
+        .token(&call.prize.from)
+        .token_owner(&call.prize.from_owner)
+        .destination_token(&call.prize.to)
+        .destination_owner(&call.prize.to_owner)
+        .mint(&call.prize.mint)
+        .metadata(&call.metadata)
+        .edition(Some(&call.edition))
+        .token_record(Some(&call.source_token_record))
+        .destination_token_record(Some(&call.destination_token_record))
+        .authority(&call.prize.from_owner)
+        .payer(&call.prize.payer)
+        .system_program(&call.system_program)
+        .sysvar_instructions(&call.sysvar_instructions)
+        .spl_token_program(&call.prize.token_program)
+        .spl_ata_program(&call.associated_token_program)
+        .authorization_rules_program(call.authorization_rules_program.as_ref())
+        .authorization_rules(call.authorization_rules.as_ref())
+        .amount(call.amount)
+        .invoke_signed(signer_seeds)?;
+    Ok(())
+}
+
+/// Accounts and amount of the `TransferV1` that moves a pNFT prize. `prize.from_owner`
+/// signs as the authority; `prize.payer` funds the destination token record.
+#[cfg_attr(not(feature = "metaplex"), allow(dead_code))]
+struct TransferV1Call<'info> {
+    prize: PrizeMove<'info>,
+    token_metadata_program: AccountInfo<'info>,
+    metadata: AccountInfo<'info>,
+    edition: AccountInfo<'info>,
+    source_token_record: AccountInfo<'info>,
+    destination_token_record: AccountInfo<'info>,
+    sysvar_instructions: AccountInfo<'info>,
+    associated_token_program: AccountInfo<'info>,
+    system_program: AccountInfo<'info>,
+    authorization_rules_program: Option<AccountInfo<'info>>,
+    authorization_rules: Option<AccountInfo<'info>>,
+    amount: u64,
+}
+
+#[cfg_attr(not(feature = "metaplex"), allow(dead_code))]
+impl<'info> TransferV1Call<'info> {
+    /// Every account but the rule set ones is required, and the program must be Token Metadata.
+    fn new(
+        prize: PrizeMove<'info>,
+        metadata: Option<&UncheckedAccount<'info>>,
+        master_edition: Option<&UncheckedAccount<'info>>,
+        pnft: &ProgrammableTransfer<'info>,
+    ) -> Result<Self> {
+        fn required<'info>(account: Option<AccountInfo<'info>>) -> Result<AccountInfo<'info>> {
+            account.ok_or_else(|| error!(RaffleError::InvalidPrizeAccounts))
+        }
+        let token_metadata_program = required(pnft.token_metadata_program.as_ref().map(|a| a.to_account_info()))?;
+        require_keys_eq!(token_metadata_program.key(), MPL_TOKEN_METADATA_ID, RaffleError::InvalidPrizeAccounts);
+        Ok(Self {
+            prize,
+            token_metadata_program,
+            metadata: required(metadata.map(|a| a.to_account_info()))?,
+            edition: required(master_edition.map(|a| a.to_account_info()))?,
+            source_token_record: required(pnft.source_token_record.as_ref().map(|a| a.to_account_info()))?,
+            destination_token_record: required(pnft.destination_token_record.as_ref().map(|a| a.to_account_info()))?,
+            sysvar_instructions: required(pnft.sysvar_instructions.as_ref().map(|a| a.to_account_info()))?,
+            associated_token_program: required(pnft.associated_token_program.as_ref().map(|p| p.to_account_info()))?,
+            system_program: required(pnft.system_program.as_ref().map(|p| p.to_account_info()))?,
+            authorization_rules_program: pnft.authorization_rules_program.as_ref().map(|a| a.to_account_info()),
+            authorization_rules: pnft.authorization_rules.as_ref().map(|a| a.to_account_info()),
+            amount: 1,
+        })
+    }
+}
+
+#[cfg(not(feature = "metaplex"))]
+fn transfer_programmable<'info>(
+    _prize: PrizeMove<'info>,
+    _metadata: Option<&UncheckedAccount<'info>>,
+    _master_edition: Option<&UncheckedAccount<'info>>,
+    _pnft: &ProgrammableTransfer<'info>,
+    _signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    err!(RaffleError::InvalidPrizeAccounts)
+}
+
+/// Bubblegum `transfer` of the escrowed cNFT to `new_owner`, signed by the raffle PDA as leaf
+/// delegate (and as owner unless the prize is delegated). Bubblegum rebuilds the leaf from
+/// the stored hashes and checks it against `root` with the `proof` path, so a wrong
+/// `leaf_owner` or tree fails there.
+#[cfg(feature = "bubblegum")]
+fn transfer_compressed_prize<'info>(
+    prize: &CompressedPrize,
+    raffle: AccountInfo<'info>,
+    new_owner: AccountInfo<'info>,
+    cnft: &CompressedTransfer<'info>,
+    root: [u8; 32],
+    proof: &[AccountInfo<'info>],
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    use mpl_bubblegum::instructions::TransferCpiBuilder;
+
+    fn required<'info>(account: Option<&UncheckedAccount<'info>>) -> Result<AccountInfo<'info>> {
+        account.map(|a| a.to_account_info()).ok_or_else(|| error!(RaffleError::InvalidPrizeAccounts))
+    }
+    let bubblegum_program = required(cnft.bubblegum_program.as_ref())?;
+    require_keys_eq!(bubblegum_program.key(), MPL_BUBBLEGUM_ID, RaffleError::InvalidPrizeAccounts);
+    let merkle_tree = required(cnft.merkle_tree.as_ref())?;
+    require_keys_eq!(merkle_tree.key(), prize.merkle_tree, RaffleError::InvalidPrizeAccounts);
+    let log_wrapper = required(cnft.log_wrapper.as_ref())?;
+    require_keys_eq!(log_wrapper.key(), SPL_NOOP_ID, RaffleError::InvalidPrizeAccounts);
+    let compression_program = required(cnft.compression_program.as_ref())?;
+    require_keys_eq!(compression_program.key(), SPL_ACCOUNT_COMPRESSION_ID, RaffleError::InvalidPrizeAccounts);
+    let tree_config = required(cnft.tree_config.as_ref())?;
+    let system_program = cnft.system_program.as_ref().map(|p| p.to_account_info()).ok_or(RaffleError::InvalidPrizeAccounts)?;
+    let leaf_owner = if prize.delegated { required(cnft.leaf_owner.as_ref())? } else { raffle.clone() };
+    let proof: Vec<(&AccountInfo<'info>, bool, bool)> = proof.iter().map(|node| (node, false, false)).collect();
+
+    TransferCpiBuilder::new(&bubblegum_program)
+        .tree_config(&tree_config)
+        .leaf_owner(&leaf_owner, !prize.delegated)
+        .leaf_delegate(&raffle, true)
+        .new_leaf_owner(&new_owner)
+        .merkle_tree(&merkle_tree)
+        .log_wrapper(&log_wrapper)
+        .compression_program(&compression_program)
+        .system_program(&system_program)
+        .root(root)
+        .data_hash(prize.data_hash)
+        .creator_hash(prize.creator_hash)
+        .nonce(prize.nonce)
+        .index(prize.index)
+        .add_remaining_accounts(&proof)
+        .invoke_signed(signer_seeds)?;
+    Ok(())
+}
+
+/// Compressed prizes need Bubblegum; builds without it can't release them.
+#[cfg(not(feature = "bubblegum"))]
+fn transfer_compressed_prize<'info>(
+    _prize: &CompressedPrize,
+    _raffle: AccountInfo<'info>,
+    _new_owner: AccountInfo<'info>,
+    _cnft: &CompressedTransfer<'info>,
+    _root: [u8; 32],
+    _proof: &[AccountInfo<'info>],
+    _signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    err!(RaffleError::InvalidPrizeAccounts)
+}
+
+/// URI of the MRFT for ticket number `original_slot` of `raffle`, worth `usdc_value` escrow
+/// base units; the metadata service serves the `raffle_id` / `original_slot` / `usdc_value`
+/// attributes from it.
+pub fn mrft_uri(raffle: &Pubkey, original_slot: u64, usdc_value: u64) -> String {
+    format!("{MRFT_METADATA_URI}/{raffle}/{original_slot}.json?usdc_value={usdc_value}")
+}
+
+/// Bubblegum metadata of an MRFT in `collection`. Fully determined by its arguments, so
+/// `join_with_ticket` can rebuild the leaf from them; the verified collection is what
+/// makes it genuine. The tree authority is listed as (unverified) creator for royalties, so
+/// MRFTs minted by the off-chain worker hash the same.
+#[cfg(feature = "bubblegum")]
+pub fn mrft_metadata(
+    raffle: &Pubkey,
+    original_slot: u64,
+    usdc_value: u64,
+    collection: &Pubkey,
+) -> mpl_bubblegum::types::MetadataArgs {
+    use mpl_bubblegum::types::{Collection, Creator, MetadataArgs, TokenProgramVersion, TokenStandard};
+
+    MetadataArgs {
+        name: format!("{MRFT_SYMBOL} #{original_slot}"),
+        symbol: MRFT_SYMBOL.to_string(),
+        uri: mrft_uri(raffle, original_slot, usdc_value),
+        seller_fee_basis_points: MRFT_SELLER_FEE_BASIS_POINTS,
+        primary_sale_happened: true,
+        is_mutable: false,
+        edition_nonce: None,
+        token_standard: Some(TokenStandard::NonFungible),
+        collection: Some(Collection { verified: true, key: *collection }),
+        uses: None,
+        token_program_version: TokenProgramVersion::Original,
+        creators: vec![Creator { address: mrft_tree_authority().0, verified: false, share: 100 }],
+    }
+}
+
+/// `[MRFT_TREE_AUTHORITY_SEED]` PDA: tree delegate of the MRFT trees and MRFT creator.
+pub fn mrft_tree_authority() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[MRFT_TREE_AUTHORITY_SEED], &crate::ID)
+}
+
+/// Burn (`burn`) or hand to the tree authority every MRFT in `mrfts`, owned by `owner`.
+/// The leaf hashes come from `mrft_metadata` in `collection`, so Bubblegum only accepts
+/// genuine MRFTs; each must be worth `unit`. `proofs` holds each leaf's proof path in order.
+#[cfg(feature = "bubblegum")]
+fn retire_mrfts<'info>(
+    accounts: &MrftRedeem<'info>,
+    owner: AccountInfo<'info>,
+    mrfts: &[MrftLeaf],
+    proofs: &'info [AccountInfo<'info>],
+    collection: &Pubkey,
+    unit: u64,
+    burn: bool,
+) -> Result<()> {
+    use mpl_bubblegum::hash::{hash_creators, hash_metadata};
+    use mpl_bubblegum::instructions::{BurnCpiBuilder, TransferCpiBuilder};
+
+    let (_, authority_bump) = mrft_tree_authority();
+    let seeds: &[&[u8]] = &[MRFT_TREE_AUTHORITY_SEED, &[authority_bump]];
+    let tree_authority = &accounts.tree_authority;
+    for (mrft, proof) in mrfts.iter().zip(mrft_proof_paths(mrfts, proofs, unit)?) {
+        let proof: Vec<(&AccountInfo<'info>, bool, bool)> = proof.iter().map(|node| (node, false, false)).collect();
+
+        let metadata = mrft_metadata(&mrft.raffle, mrft.original_slot, mrft.usdc_value, collection);
+        let data_hash = hash_metadata(&metadata).map_err(|_| RaffleError::InvalidMrft)?;
+        let creator_hash = hash_creators(&metadata.creators);
+        // The tree authority signs while it is still the leaf delegate, the owner otherwise
+        let delegate = if mrft.program_delegated { tree_authority } else { &owner };
+
+        if burn {
+            BurnCpiBuilder::new(&accounts.bubblegum_program)
+                .tree_config(&accounts.tree_config)
+                .leaf_owner(&owner, !mrft.program_delegated)
+                .leaf_delegate(delegate, mrft.program_delegated)
+                .merkle_tree(&accounts.merkle_tree)
+                .log_wrapper(&accounts.log_wrapper)
+                .compression_program(&accounts.compression_program)
+                .system_program(&accounts.system_program)
+                .root(mrft.root)
+                .data_hash(data_hash)
+                .creator_hash(creator_hash)
+                .nonce(mrft.nonce)
+                .index(mrft.index)
+                .add_remaining_accounts(&proof)
+                .invoke_signed(&[seeds])?;
+        } else {
+            TransferCpiBuilder::new(&accounts.bubblegum_program)
+                .tree_config(&accounts.tree_config)
+                .leaf_owner(&owner, !mrft.program_delegated)
+                .leaf_delegate(delegate, mrft.program_delegated)
+                .new_leaf_owner(tree_authority)
+                .merkle_tree(&accounts.merkle_tree)
+                .log_wrapper(&accounts.log_wrapper)
+                .compression_program(&accounts.compression_program)
+                .system_program(&accounts.system_program)
+                .root(mrft.root)
+                .data_hash(data_hash)
+                .creator_hash(creator_hash)
+                .nonce(mrft.nonce)
+                .index(mrft.index)
+                .add_remaining_accounts(&proof)
+                .invoke_signed(&[seeds])?;
+        }
+    }
+    Ok(())
+}
+
+/// Split `proofs` into each MRFT's proof path (`proof_len` nodes each, in order), checking
+/// every MRFT is worth `unit`. Every node must belong to some MRFT.
+pub fn mrft_proof_paths<'a, T>(mrfts: &[MrftLeaf], proofs: &'a [T], unit: u64) -> Result<Vec<&'a [T]>> {
+    let mut rest = proofs;
+    let mut paths = Vec::with_capacity(mrfts.len());
+    for mrft in mrfts {
+        require!(mrft.usdc_value == unit, RaffleError::InvalidMrft);
+        require!(rest.len() >= mrft.proof_len as usize, RaffleError::InvalidProof);
+        let (path, tail) = rest.split_at(mrft.proof_len as usize);
+        paths.push(path);
+        rest = tail;
+    }
+    require!(rest.is_empty(), RaffleError::InvalidProof);
+    Ok(paths)
+}
+
+/// Bubblegum `mint_to_collection_v1` of one MRFT per ticket number in `ticket`, each worth
+/// `usdc_value` and owned by `owner`, signed by the tree authority. The tree authority stays
+/// leaf delegate so `join_with_ticket` can burn it until it changes hands. Every mint is a CPI, so
+/// large tickets can run out of compute; their holders use the off-chain path instead.
+/// Returns the tree the MRFTs went to.
+#[cfg(feature = "bubblegum")]
+fn mint_mrfts<'info>(
+    raffle: &Pubkey,
+    ticket: &Ticket,
+    usdc_value: u64,
+    owner: AccountInfo<'info>,
+    mrft: &MrftMint<'info>,
+) -> Result<Pubkey> {
+    use mpl_bubblegum::instructions::MintToCollectionV1CpiBuilder;
+
+    fn required<'info>(account: Option<&UncheckedAccount<'info>>) -> Result<AccountInfo<'info>> {
+        account.map(|a| a.to_account_info()).ok_or_else(|| error!(RaffleError::InvalidMrftAccounts))
+    }
+    let (authority_key, authority_bump) = mrft_tree_authority();
+    let tree_authority = required(mrft.tree_authority.as_ref())?;
+    require_keys_eq!(tree_authority.key(), authority_key, RaffleError::InvalidMrftAccounts);
+    let bubblegum_program = required(mrft.bubblegum_program.as_ref())?;
+    require_keys_eq!(bubblegum_program.key(), MPL_BUBBLEGUM_ID, RaffleError::InvalidMrftAccounts);
+    let collection_mint = required(mrft.collection_mint.as_ref())?;
+    require_keys_eq!(collection_mint.key(), MRFT_COLLECTION_MINT, RaffleError::InvalidMrftAccounts);
+    let log_wrapper = required(mrft.log_wrapper.as_ref())?;
+    require_keys_eq!(log_wrapper.key(), SPL_NOOP_ID, RaffleError::InvalidMrftAccounts);
+    let compression_program = required(mrft.compression_program.as_ref())?;
+    require_keys_eq!(compression_program.key(), SPL_ACCOUNT_COMPRESSION_ID, RaffleError::InvalidMrftAccounts);
+    let tree_config = required(mrft.tree_config.as_ref())?;
+    let merkle_tree = required(mrft.merkle_tree.as_ref())?;
+    let collection_metadata = required(mrft.collection_metadata.as_ref())?;
+    let collection_edition = required(mrft.collection_edition.as_ref())?;
+    let collection_authority_record = mrft.collection_authority_record.as_ref().map(|a| a.to_account_info());
+    let bubblegum_signer = required(mrft.bubblegum_signer.as_ref())?;
+    let token_metadata_program = required(mrft.token_metadata_program.as_ref())?;
+    let system_program = mrft.system_program.as_ref().map(|p| p.to_account_info()).ok_or(RaffleError::InvalidMrftAccounts)?;
+
+    let seeds: &[&[u8]] = &[MRFT_TREE_AUTHORITY_SEED, &[authority_bump]];
+    for original_slot in ticket.start..ticket.start + ticket.count {
+        MintToCollectionV1CpiBuilder::new(&bubblegum_program)
+            .tree_config(&tree_config)
+            .leaf_owner(&owner)
+            .leaf_delegate(&tree_authority)
+            .merkle_tree(&merkle_tree)
+            .payer(&owner)
+            .tree_creator_or_delegate(&tree_authority)
+            .collection_authority(&tree_authority)
+            .collection_authority_record_pda(collection_authority_record.as_ref())
+            .collection_mint(&collection_mint)
+            .collection_metadata(&collection_metadata)
+            .collection_edition(&collection_edition)
+            .bubblegum_signer(&bubblegum_signer)
+            .log_wrapper(&log_wrapper)
+            .compression_program(&compression_program)
+            .token_metadata_program(&token_metadata_program)
+            .system_program(&system_program)
+            .metadata(mrft_metadata(raffle, original_slot, usdc_value, &MRFT_COLLECTION_MINT))
+            .invoke_signed(&[seeds])?;
+    }
+    Ok(merkle_tree.key())
+}
+
+/// Minting MRFTs on-chain needs Bubblegum; without it refunds go through the off-chain worker.
+#[cfg(not(feature = "bubblegum"))]
+fn mint_mrfts<'info>(
+    _raffle: &Pubkey,
+    _ticket: &Ticket,
+    _usdc_value: u64,
+    _owner: AccountInfo<'info>,
+    _mrft: &MrftMint<'info>,
+) -> Result<Pubkey> {
+    err!(RaffleError::InvalidMrftAccounts)
+}
+
+/// `collection`'s Metaplex metadata (passed as `metadata`) marks it as a collection NFT.
+#[cfg(feature = "metaplex")]
+fn verify_prize_collection(collection: &Pubkey, metadata: Option<&UncheckedAccount<'_>>) -> Result<()> {
+    use mpl_token_metadata::accounts::Metadata;
+
+    let metadata = metadata.ok_or(RaffleError::InvalidCollectionMint)?;
+    require_keys_eq!(*metadata.owner, mpl_token_metadata::ID, RaffleError::InvalidCollectionMint);
+    require_keys_eq!(metadata.key(), Metadata::find_pda(collection).0, RaffleError::InvalidCollectionMint);
+    let data = Metadata::safe_deserialize(&metadata.try_borrow_data()?).map_err(|_| RaffleError::InvalidCollectionMint)?;
+    require!(data.collection_details.is_some(), RaffleError::InvalidCollectionMint);
+    Ok(())
+}
+
+/// Post-mint prizes need the `metaplex` feature, so no collection can back a raffle without it.
+#[cfg(not(feature = "metaplex"))]
+fn verify_prize_collection(_collection: &Pubkey, _metadata: Option<&UncheckedAccount<'_>>) -> Result<()> {
+    err!(RaffleError::InvalidCollectionMint)
+}
+
+/// Public key and message of a single-signature ed25519 precompile instruction, if its
+/// signature, key and message are all inline. `data` starts with `num_signatures` and a
+/// padding byte, then seven u16 offsets: signature offset / instruction, public key
+/// offset / instruction, message offset / size / instruction. The precompile has already
+/// checked the signature against these same bytes.
+fn ed25519_ix_parts(data: &[u8]) -> Option<(&[u8], &[u8])> {
+    if data.len() < 16 || data[0] != 1 { return None; }
+    let field = |at: usize| u16::from_le_bytes([data[at], data[at + 1]]);
+    if field(4) != u16::MAX || field(8) != u16::MAX || field(14) != u16::MAX { return None; }
+    let (pk_off, msg_off, msg_len) = (field(6) as usize, field(10) as usize, field(12) as usize);
+    let pk_bytes = data.get(pk_off..pk_off + 32)?;
+    let msg_bytes = data.get(msg_off..msg_off + msg_len)?;
+    Some((pk_bytes, msg_bytes))
+}
+
+/// Digest of `metadata` signed into the raffle permit (see `initialize_raffle_with_permit`).
+pub fn prize_metadata_hash(metadata: &PrizeMetadata) -> [u8; 32] {
+    let bytes = metadata.try_to_vec().expect("serializing into a Vec cannot fail");
+    hashv(&[&bytes]).to_bytes()
+}
+
+/// Allowlist leaf for `(wallet, max_allocation)`.
+pub fn allowlist_leaf(wallet: &Pubkey, max_allocation: u64) -> [u8; 32] {
+    hashv(&[wallet.as_ref(), &max_allocation.to_le_bytes()]).to_bytes()
+}
+
+/// Parent of two allowlist nodes; pairs are hashed sorted so proofs need no left/right flags.
+pub fn allowlist_node(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    let (lo, hi) = if a <= b { (a, b) } else { (b, a) };
+    hashv(&[lo.as_ref(), hi.as_ref()]).to_bytes()
+}
+
+/// Fold `proof` (sibling hashes, leaf first) into a root and compare it with `root`.
+pub fn verify_allowlist_proof(root: &[u8; 32], leaf: [u8; 32], proof: &[[u8; 32]]) -> bool {
+    proof.iter().fold(leaf, |node, sibling| allowlist_node(&node, sibling)) == *root
+}
+
+/// Mint address of the tokenized ticket starting at `start` (see `tokenize_ticket`).
+pub fn ticket_mint_address(raffle: &Pubkey, start: u64) -> Pubkey {
+    Pubkey::find_program_address(&[TICKET_MINT_SEED, raffle.as_ref(), &start.to_le_bytes()], &crate::ID).0
+}
+
+/// Top up `account`'s rent from `payer` for `new_len` bytes, then grow it (layout migrations).
+fn grow_account<'info>(
+    account: &AccountInfo<'info>,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+    new_len: usize,
+) -> Result<()> {
+    let rent_due = Rent::get()?.minimum_balance(new_len).saturating_sub(account.lamports());
+    if rent_due > 0 {
+        anchor_lang::system_program::transfer(
+            CpiContext::new(
+                system_program.to_account_info(),
+                anchor_lang::system_program::Transfer { from: payer.to_account_info(), to: account.clone() },
+            ),
+            rent_due,
+        )?;
+    }
+    account.realloc(new_len, false)?;
+    Ok(())
+}
+
+/// `wallet` may act for `ticket` if it owns it, or, for a tokenized ticket (owned by its
+/// ticket mint), if `holder_ticket_account` is `wallet`'s account holding that NFT.
+fn authorize_ticket_holder(
+    ticket: &Ticket,
+    wallet: &Pubkey,
+    holder_ticket_account: Option<&InterfaceAccount<TokenAccount>>,
+) -> Result<()> {
+    if ticket.owner == *wallet {
+        return Ok(());
+    }
+    let holder = holder_ticket_account.ok_or(RaffleError::Unauthorized)?;
+    require!(
+        holder.mint == ticket.owner && holder.owner == *wallet && holder.amount == 1,
+        RaffleError::Unauthorized
+    );
+    require_keys_eq!(ticket_mint_address(&ticket.raffle, ticket.start), ticket.owner, RaffleError::Unauthorized);
+    Ok(())
+}
+
+/// Fund and write the ticket NFT's Token-2022 metadata, mint it to the owner and drop the
+/// mint authority so supply stays at 1. The raffle PDA signs as mint and update authority.
+fn mint_ticket_nft(accounts: &TokenizeTicket, start: u64, count: u64) -> Result<()> {
+    use token::spl_token_2022::instruction::AuthorityType;
+    use token::spl_token_metadata_interface::state::{Field, TokenMetadata};
+
+    let raffle = &accounts.raffle;
+    let mint_info = accounts.ticket_mint.to_account_info();
+    let fields = [
+        ("raffle", raffle.key().to_string()),
+        ("start", start.to_string()),
+        ("count", count.to_string()),
+    ];
+    let metadata = TokenMetadata {
+        name: format!("Raffle Ticket #{}-{}", start, start + count - 1),
+        symbol: TICKET_NFT_SYMBOL.to_string(),
+        uri: String::new(),
+        additional_metadata: fields.iter().map(|(k, v)| (k.to_string(), v.clone())).collect(),
+        ..Default::default()
+    };
+
+    // Token-2022 reallocs the mint for the metadata TLV, but the mint must already hold its rent
+    let new_len = mint_info.data_len() + metadata.tlv_size_of()?;
+    let top_up = Rent::get()?.minimum_balance(new_len).saturating_sub(mint_info.lamports());
+    if top_up > 0 {
+        anchor_lang::system_program::transfer(
+            CpiContext::new(
+                accounts.system_program.to_account_info(),
+                anchor_lang::system_program::Transfer { from: accounts.owner.to_account_info(), to: mint_info.clone() },
+            ),
+            top_up,
+        )?;
+    }
+
+    let seeds: &[&[u8]] = &[RAFFLE_SEED, raffle.mint.as_ref(), raffle.organizer.as_ref(), &[raffle.bump]];
+    let program = accounts.token_2022_program.to_account_info();
+    token::token_metadata_initialize(
+        CpiContext::new_with_signer(
+            program.clone(),
+            token::TokenMetadataInitialize {
+                program_id: program.clone(),
+                metadata: mint_info.clone(),
+                update_authority: raffle.to_account_info(),
+                mint_authority: raffle.to_account_info(),
+                mint: mint_info.clone(),
+            },
+            &[seeds],
+        ),
+        metadata.name,
+        metadata.symbol,
+        metadata.uri,
+    )?;
+    for (key, value) in fields {
+        token::token_metadata_update_field(
+            CpiContext::new_with_signer(
+                program.clone(),
+                token::TokenMetadataUpdateField {
+                    program_id: program.clone(),
+                    metadata: mint_info.clone(),
+                    update_authority: raffle.to_account_info(),
+                },
+                &[seeds],
+            ),
+            Field::Key(key.to_string()),
+            value,
+        )?;
+    }
+
+    token::mint_to(
+        CpiContext::new_with_signer(
+            program.clone(),
+            token::MintTo {
+                mint: mint_info.clone(),
+                to: accounts.owner_ticket_account.to_account_info(),
+                authority: raffle.to_account_info(),
+            },
+            &[seeds],
+        ),
+        1,
+    )?;
+    token::set_authority(
+        CpiContext::new_with_signer(
+            program,
+            token::SetAuthority { current_authority: raffle.to_account_info(), account_or_mint: mint_info },
+            &[seeds],
+        ),
+        AuthorityType::MintTokens,
+        None,
+    )?;
+    Ok(())
+}
+
+/// Return a ticket's USDC (1 whole token per ticket) from escrow to the owner's token account.
+/// The raffle PDA signs the transfer.
+fn refund_ticket_usdc<'info>(
+    raffle: &Account<'info, Raffle>,
+    ticket: &Ticket,
+    mint: &InterfaceAccount<'info, Mint>,
+    escrow_ata: &InterfaceAccount<'info, TokenAccount>,
+    owner_ata: &InterfaceAccount<'info, TokenAccount>,
+    token_program: &Program<'info, Token>,
+) -> Result<u64> {
+    require_keys_eq!(owner_ata.mint, mint.key());
+    let unit = 10u64.pow(mint.decimals as u32);
+    let amount = ticket.count.checked_mul(unit).ok_or(RaffleError::Overflow)?;
+
+    let seeds: &[&[u8]] = &[
+        RAFFLE_SEED,
+        raffle.mint.as_ref(),
+        raffle.organizer.as_ref(),
+        &[raffle.bump],
+    ];
+    let cpi_accounts = TransferChecked {
+        from: escrow_ata.to_account_info(),
+        to: owner_ata.to_account_info(),
+        mint: mint.to_account_info(),
+        authority: raffle.to_account_info(),
+    };
+    token::transfer_checked(
+        CpiContext::new_with_signer(token_program.to_account_info(), cpi_accounts, &[seeds]),
+        amount,
+        mint.decimals,
+    )?;
+    Ok(amount)
+}
+
+/// Move the USDC backing `count` MRFTs of `origin` (a failed MRFT-refund raffle) into
+/// `escrow_ata`: the escrow of the raffle they are redeemed in, or the refund vault buying
+/// them back. Returns the amount moved.
+#[cfg(feature = "bubblegum")]
+fn migrate_mrft_backing<'info>(
+    origin: &mut Account<'info, Raffle>,
+    origin_escrow: &InterfaceAccount<'info, TokenAccount>,
+    escrow_ata: &InterfaceAccount<'info, TokenAccount>,
+    mint: &InterfaceAccount<'info, Mint>,
+    token_program: &Program<'info, Token>,
+    count: u64,
+) -> Result<u64> {
+    origin.release_mrft_backing(count)?;
+
+    let unit = 10u64.pow(mint.decimals as u32);
+    let amount = count.checked_mul(unit).ok_or(RaffleError::Overflow)?;
+    let seeds: &[&[u8]] = &[
+        RAFFLE_SEED,
+        origin.mint.as_ref(),
+        origin.organizer.as_ref(),
+        &[origin.bump],
+    ];
+    let cpi_accounts = TransferChecked {
+        from: origin_escrow.to_account_info(),
+        to: escrow_ata.to_account_info(),
+        mint: mint.to_account_info(),
+        authority: origin.to_account_info(),
+    };
+    token::transfer_checked(
+        CpiContext::new_with_signer(token_program.to_account_info(), cpi_accounts, &[seeds]),
+        amount,
+        mint.decimals,
+    )?;
+    Ok(amount)
+}
+
+/// Part of an MRFT buy-back of `amount` the refund vault keeps when the failed raffle's
+/// deadline is `age_secs` old: nothing during `MRFT_HAIRCUT_GRACE_SECS`, then
+/// `MRFT_HAIRCUT_BPS_PER_DAY` per started day, capped at `MRFT_MAX_HAIRCUT_BPS`.
+pub fn mrft_buyback_haircut(amount: u64, age_secs: i64) -> u64 {
+    let overdue = age_secs.saturating_sub(MRFT_HAIRCUT_GRACE_SECS);
+    if overdue <= 0 {
+        return 0;
+    }
+    let days = (overdue as u64).div_ceil(24 * 60 * 60);
+    let bps = days.saturating_mul(MRFT_HAIRCUT_BPS_PER_DAY).min(MRFT_MAX_HAIRCUT_BPS);
+    (amount as u128 * bps as u128 / 10_000) as u64
+}
+
+/// A scheduled reveal must be in the future and no later than the raffle's deadline.
+pub fn validate_reveal_time(reveal_time_unix_ts: Option<i64>, deadline: i64, now: i64) -> Result<()> {
+    if let Some(reveal_ts) = reveal_time_unix_ts {
+        require!(reveal_ts > now && reveal_ts <= deadline, RaffleError::InvalidRevealTime);
+    }
+    Ok(())
+}
+
+/// Raffle state. `version` comes first so future migrations can read it at a fixed
+/// offset; accounts created before versioning are recognised by their data length.
 #[account]
+#[derive(InitSpace)]
 pub struct Raffle {
+    pub version: u8,                     // Layout version, RAFFLE_VERSION for new raffles
     pub organizer: Pubkey,
     pub mint: Pubkey,                    // USDC/stable coin mint
     pub escrow: Pubkey,
     pub required_tickets: u64,
     pub tickets_sold: u64,
-    pub next_ticket_index: u64,
+    pub next_ticket_index: u64,          // Lowest free ticket number (range deposits start here unless joined past)
     pub deadline: i64,
     pub status: u8,
     pub winner_ticket: u64,
@@ -1830,13 +4167,294 @@ pub struct Raffle {
     pub prize_claimed: bool,
     pub proceeds_collected: bool,
     pub auto_draw: bool,
-    pub ticket_mode: u8,                 // 0=disabled, 1=require_burn, 2=accept_without_burn
+    pub ticket_mode: u8,                 // MRFT joins: 0=disabled, 1=accept_without_burn, 2=require_burn
     pub prize_collection_mint: Pubkey,   // Collection mint for post-mint prizes (organizer sets this)
     pub refund_mode: u8,                 // 0=USDC refund, 1=MRFT mint, 2=both (user choice)
+    pub reveal_time_unix_ts: Option<i64>, // Scheduled reveal: draws blocked until this time
+    pub crank_reserve: u64,              // Lamports left for keeper bounties (held by the raffle PDA)
+    pub draw_requested: bool,            // First request_draw already paid its bounty
+    pub tickets_refunded: u64,           // Tickets (units) refunded so far
+    pub open_tickets: u64,               // Ticket units held by Ticket accounts not yet closed
+    pub slots_closed: bool,              // RaffleSlots account already closed
+    pub allowlist_root: [u8; 32],        // Presale allowlist Merkle root (zero = no presale)
+    pub presale_end_unix_ts: i64,        // Allowlist-only sales until this time (0 = none)
+    pub gate_mode: u8,                   // 0=open, 1=min token balance, 2=verified collection NFT
+    pub gate_mint: Pubkey,               // Gate token mint (mode 1) or collection mint (mode 2)
+    pub gate_min_amount: u64,            // Minimum gate token balance in base units (mode 1)
+    pub prize_backed: bool,              // Entries need an escrowed prize (or validated post-mint collection)
+    pub prize_metadata: PrizeMetadata,   // Metadata of post-mint prizes (set at creation, bound by the permit)
+    pub prize_cnft: CompressedPrize,     // Escrowed Bubblegum cNFT prize (default = SPL prize or none)
+    pub mrft_collection: Pubkey,         // MRFT collection join_with_ticket accepts (default = MRFT_COLLECTION_MINT)
+    pub mrft_backing_released: u64,      // MRFT-refunded ticket units whose USDC moved to the redeeming raffle
+    pub claim_window_secs: i64,          // Time the winner has for claim_win (0 = no deadline)
+    pub winner_selected_at: i64,         // When settle_draw picked the current winner (0 = none)
+    pub win_claimed: bool,               // Current winner called claim_win
+    #[max_len(MAX_WINNER_FORFEITS)]
+    pub forfeited_owners: Vec<Pubkey>,   // Owners of forfeited wins (ticket mint if tokenized), excluded from redraws
+    pub mrft_backing_swept: bool,        // Unredeemed MRFT backing moved to the refund vault
+}
+
+/// Metadata `claim_prize_mint` gives the minted prize NFT.
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Default, PartialEq, Eq, Debug)]
+pub struct PrizeMetadata {
+    #[max_len(MAX_PRIZE_NAME_LEN)]
+    pub name: String,
+    #[max_len(MAX_PRIZE_SYMBOL_LEN)]
+    pub symbol: String,
+    #[max_len(MAX_PRIZE_URI_LEN)]
+    pub uri: String,
+    pub seller_fee_basis_points: u16,
+    #[max_len(MAX_PRIZE_CREATORS)]
+    pub creators: Vec<PrizeCreator>,
+}
+
+/// Unverified creator of a post-mint prize; shares add up to 100.
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct PrizeCreator {
+    pub address: Pubkey,
+    pub share: u8,
+}
+
+/// Bubblegum leaf of a compressed prize (see `set_prize_cnft`). Transfers keep the nonce,
+/// index and hashes, so only the tree's current root is needed again at claim time.
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct CompressedPrize {
+    pub merkle_tree: Pubkey,
+    pub nonce: u64,
+    pub index: u32,
+    pub data_hash: [u8; 32],
+    pub creator_hash: [u8; 32],
+    pub delegated: bool,                 // Organizer still owns the leaf; the raffle PDA is its delegate
+}
+
+/// MRFT redeemed in `join_with_ticket`. Its metadata isn't passed: the program rebuilds it
+/// from `raffle` / `original_slot` / `usdc_value` (see `mrft_metadata`). The leaf fields come
+/// from the indexer (e.g. DAS `getAsset` / `getAssetProof`).
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct MrftLeaf {
+    pub raffle: Pubkey,                  // raffle_id: failed raffle that issued the MRFT
+    pub original_slot: u64,              // Ticket number it refunds
+    pub usdc_value: u64,                 // Escrow mint base units backing it
+    pub root: [u8; 32],
+    pub nonce: u64,
+    pub index: u32,
+    pub program_delegated: bool,         // Tree authority is still the leaf delegate (never traded)
+    pub proof_len: u8,                   // Proof nodes of this leaf in the remaining accounts
+}
+
+impl CompressedPrize {
+    /// True for raffles whose prize is a cNFT rather than an SPL token.
+    pub fn is_set(&self) -> bool {
+        self.merkle_tree != Pubkey::default()
+    }
+}
+
+impl PrizeMetadata {
+    /// True once a URI has been configured; raffles created without one can't mint prizes.
+    pub fn is_set(&self) -> bool {
+        !self.uri.is_empty()
+    }
+
+    /// Fits the `Raffle` account and Metaplex's own checks.
+    pub fn validate(&self) -> Result<()> {
+        require!(self.name.len() <= MAX_PRIZE_NAME_LEN, RaffleError::InvalidPrizeMetadata);
+        require!(self.symbol.len() <= MAX_PRIZE_SYMBOL_LEN, RaffleError::InvalidPrizeMetadata);
+        require!(self.uri.len() <= MAX_PRIZE_URI_LEN, RaffleError::InvalidPrizeMetadata);
+        require!(self.seller_fee_basis_points <= 10_000, RaffleError::InvalidPrizeMetadata);
+        require!(self.creators.len() <= MAX_PRIZE_CREATORS, RaffleError::InvalidPrizeMetadata);
+        if !self.creators.is_empty() {
+            let shares: u32 = self.creators.iter().map(|c| c.share as u32).sum();
+            require!(shares == 100, RaffleError::InvalidPrizeMetadata);
+        }
+        Ok(())
+    }
 }
 
 impl Raffle {
-    pub const LEN: usize = 32 + 32 + 32 + 8 + 8 + 8 + 8 + 1 + 8 + 1 + 32 + 32 + 1 + 1 + 1 + 1 + 1 + 32 + 1;
+    pub const LEN: usize = Raffle::INIT_SPACE;
+
+    /// Escrow balance the counters account for, with `unit` = one whole escrow token.
+    /// Every USDC-paid ticket adds a unit; USDC refunds (`refund_mode == 0`) and
+    /// collecting the proceeds take them out again. MRFT refunds leave the USDC in escrow
+    /// until the MRFT is redeemed in another raffle (`mrft_backing_released`) or the rest
+    /// is swept to the refund vault (`mrft_backing_swept`).
+    pub fn expected_escrow(&self, unit: u64) -> Result<u64> {
+        if self.proceeds_collected || self.mrft_backing_swept {
+            return Ok(0);
+        }
+        let usdc_refunded = if self.refund_mode == 0 { self.tickets_refunded } else { 0 };
+        let funded = self
+            .tickets_sold
+            .checked_sub(usdc_refunded)
+            .and_then(|t| t.checked_sub(self.mrft_backing_released))
+            .ok_or(RaffleError::Overflow)?;
+        Ok(funded.checked_mul(unit).ok_or(RaffleError::Overflow)?)
+    }
+
+    /// Release the backing of `count` MRFTs of this failed MRFT-refund raffle (see
+    /// `migrate_mrft_backing`). Only MRFTs it actually refunded carry backing, each exactly
+    /// once, and none is left once it has been swept.
+    pub fn release_mrft_backing(&mut self, count: u64) -> Result<()> {
+        require!(self.status == RaffleStatus::Refunding as u8 && self.refund_mode != 0, RaffleError::InvalidMrft);
+        require!(!self.mrft_backing_swept, RaffleError::AlreadySwept);
+        let released = self.mrft_backing_released.checked_add(count).ok_or(RaffleError::Overflow)?;
+        require!(released <= self.tickets_refunded, RaffleError::InvalidMrft);
+        self.mrft_backing_released = released;
+        Ok(())
+    }
+
+    /// True when the winner collects the prize on chain: an escrowed prize, or one
+    /// `claim_prize_mint` mints from `prize_metadata` (`metaplex` builds). Other raffles
+    /// hand over an off-chain prize once `claim_win` is recorded.
+    pub fn has_onchain_prize(&self) -> bool {
+        self.prize_set || (cfg!(feature = "metaplex") && self.prize_metadata.is_set())
+    }
+
+    /// Terminal state: nothing is owed to anyone anymore, so accounts may be closed.
+    /// Completed raffles need the prize claimed (just the win, without an on-chain prize)
+    /// and proceeds collected; refunding raffles need every sold ticket refunded.
+    pub fn is_settled(&self) -> bool {
+        if self.status == RaffleStatus::Completed as u8 {
+            let prize_settled = if self.has_onchain_prize() { self.prize_claimed } else { self.win_claimed };
+            prize_settled && self.proceeds_collected
+        } else if self.status == RaffleStatus::Refunding as u8 {
+            self.tickets_refunded == self.tickets_sold
+        } else {
+            false
+        }
+    }
+
+    /// True while only allowlisted wallets may buy.
+    pub fn is_presale(&self, now: i64) -> bool {
+        now < self.presale_end_unix_ts
+    }
+
+    /// True once a scheduled reveal (if any) has passed and a draw may happen.
+    pub fn is_reveal_due(&self, now: i64) -> bool {
+        self.reveal_time_unix_ts.is_none_or(|ts| now >= ts)
+    }
+
+    /// True once the current winner's claim window has run out without `claim_win`.
+    pub fn is_claim_window_expired(&self, now: i64) -> bool {
+        self.claim_window_secs > 0
+            && !self.win_claimed
+            && now > self.winner_selected_at.saturating_add(self.claim_window_secs)
+    }
+
+    /// MRFT collection `join_with_ticket` accepts.
+    pub fn accepted_mrft_collection(&self) -> Pubkey {
+        if self.mrft_collection == Pubkey::default() {
+            MRFT_COLLECTION_MINT
+        } else {
+            self.mrft_collection
+        }
+    }
+
+    /// True unless the raffle is prize-backed and has neither an escrowed prize nor a
+    /// post-mint collection (validated by `set_prize_backed`). A delegated cNFT stays in
+    /// the organizer's wallet, so it doesn't count as escrowed.
+    pub fn is_prize_secured(&self) -> bool {
+        !self.prize_backed
+            || (self.prize_set && !self.prize_cnft.delegated)
+            || self.prize_collection_mint != Pubkey::default()
+    }
+}
+
+/// Layout written before `prize_collection_mint` / `refund_mode` were added (no version byte).
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace)]
+pub struct RaffleV0 {
+    pub organizer: Pubkey,
+    pub mint: Pubkey,
+    pub escrow: Pubkey,
+    pub required_tickets: u64,
+    pub tickets_sold: u64,
+    pub next_ticket_index: u64,
+    pub deadline: i64,
+    pub status: u8,
+    pub winner_ticket: u64,
+    pub bump: u8,
+    pub prize_mint: Pubkey,
+    pub prize_escrow: Pubkey,
+    pub prize_set: bool,
+    pub prize_claimed: bool,
+    pub proceeds_collected: bool,
+    pub auto_draw: bool,
+    pub ticket_mode: u8,
+}
+
+impl RaffleV0 {
+    pub const LEN: usize = RaffleV0::INIT_SPACE;
+}
+
+/// Unversioned layout with `prize_collection_mint` / `refund_mode` appended.
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace)]
+pub struct RaffleV1 {
+    pub legacy: RaffleV0,
+    pub prize_collection_mint: Pubkey,
+    pub refund_mode: u8,
+}
+
+impl RaffleV1 {
+    pub const LEN: usize = RaffleV1::INIT_SPACE;
+}
+
+impl From<RaffleV0> for Raffle {
+    fn from(v0: RaffleV0) -> Self {
+        Raffle::from(RaffleV1 { legacy: v0, prize_collection_mint: Pubkey::default(), refund_mode: 0 })
+    }
+}
+
+impl From<RaffleV1> for Raffle {
+    fn from(v1: RaffleV1) -> Self {
+        let RaffleV1 { legacy: v0, prize_collection_mint, refund_mode } = v1;
+        Raffle {
+            version: RAFFLE_VERSION,
+            organizer: v0.organizer,
+            mint: v0.mint,
+            escrow: v0.escrow,
+            required_tickets: v0.required_tickets,
+            tickets_sold: v0.tickets_sold,
+            next_ticket_index: v0.next_ticket_index,
+            deadline: v0.deadline,
+            status: v0.status,
+            winner_ticket: v0.winner_ticket,
+            bump: v0.bump,
+            prize_mint: v0.prize_mint,
+            prize_escrow: v0.prize_escrow,
+            prize_set: v0.prize_set,
+            prize_claimed: v0.prize_claimed,
+            proceeds_collected: v0.proceeds_collected,
+            auto_draw: v0.auto_draw,
+            ticket_mode: v0.ticket_mode,
+            prize_collection_mint,
+            // Refunds before versioning all requested MRFTs and left the USDC in escrow, so a
+            // raffle already refunding keeps accounting for them that way
+            refund_mode: if v0.status == RaffleStatus::Refunding as u8 { refund_mode.max(1) } else { refund_mode },
+            reveal_time_unix_ts: None,
+            crank_reserve: 0,
+            draw_requested: v0.status != RaffleStatus::Selling as u8,
+            // Refunds before versioning were not counted; `migrate_ticket` adds them back
+            tickets_refunded: 0,
+            // Every sold unit sits in exactly one ticket account
+            open_tickets: v0.tickets_sold,
+            slots_closed: false,
+            allowlist_root: [0u8; 32],
+            presale_end_unix_ts: 0,
+            gate_mode: 0,
+            gate_mint: Pubkey::default(),
+            gate_min_amount: 0,
+            prize_backed: false,
+            prize_metadata: PrizeMetadata::default(),
+            prize_cnft: CompressedPrize::default(),
+            mrft_collection: Pubkey::default(),
+            mrft_backing_released: 0,
+            claim_window_secs: 0,
+            winner_selected_at: 0,
+            win_claimed: false,
+            forfeited_owners: Vec::new(),
+            mrft_backing_swept: false,
+        }
+    }
 }
 
 #[account]
@@ -1848,6 +4466,67 @@ pub struct Ticket {
     pub refunded: bool,
     pub claimed_win: bool,
     pub bump: u8,
+    pub payer: Pubkey, // Funded the account; gets the rent back when it closes
+}
+
+/// Ticket layout written before `payer` was added.
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct TicketV0 {
+    pub raffle: Pubkey,
+    pub owner: Pubkey,
+    pub start: u64,
+    pub count: u64,
+    pub refunded: bool,
+    pub claimed_win: bool,
+    pub bump: u8,
+}
+
+impl TicketV0 {
+    pub const LEN: usize = 32 + 32 + 8 + 8 + 1 + 1 + 1;
+}
+
+impl From<TicketV0> for Ticket {
+    fn from(v0: TicketV0) -> Self {
+        Ticket {
+            raffle: v0.raffle,
+            owner: v0.owner,
+            start: v0.start,
+            count: v0.count,
+            refunded: v0.refunded,
+            claimed_win: v0.claimed_win,
+            bump: v0.bump,
+            // Gifts, transfers and tokenization came later: the owner paid for every legacy ticket
+            payer: v0.owner,
+        }
+    }
+}
+
+/// Platform-wide settings, a single `[PLATFORM_CONFIG_SEED]` PDA.
+#[account]
+#[derive(InitSpace)]
+pub struct PlatformConfig {
+    pub authority: Pubkey, // Withdraws from the refund vault
+    pub bump: u8,
+}
+
+impl PlatformConfig {
+    pub const LEN: usize = PlatformConfig::INIT_SPACE;
+}
+
+/// A wallet's presale allocation, proven once against the raffle's allowlist root.
+#[account]
+#[derive(InitSpace)]
+pub struct Allocation {
+    pub raffle: Pubkey,
+    pub wallet: Pubkey,
+    pub allowlist_root: [u8; 32],
+    pub max_allocation: u64, // Tickets the wallet may buy during the presale
+    pub used: u64,           // Tickets bought so far during the presale
+    pub bump: u8,
+}
+
+impl Allocation {
+    pub const LEN: usize = Allocation::INIT_SPACE;
 }
 
 #[account]
@@ -1858,17 +4537,129 @@ pub struct RaffleSlots {
     pub owners: Vec<Pubkey>,
 }
 
-impl RaffleSlots {
-    pub fn space(required_slots: u64) -> usize {
-        // discriminator (8) added by caller; this returns struct size only
-        let s = required_slots as usize;
-        let bitmap_bytes = (s + 7) / 8;
-        32 + 4 + 4 + bitmap_bytes + 4 + (32 * s)
+impl RaffleSlots {
+    pub fn space(required_slots: u64) -> usize {
+        // discriminator (8) added by caller; this returns struct size only
+        let s = required_slots as usize;
+        let bitmap_bytes = s.div_ceil(8);
+        32 + 4 + 4 + bitmap_bytes + 4 + (32 * s)
+    }
+
+    /// Byte index and bit mask of 0-based `slot` in `bitmap`.
+    pub fn bit(slot: u32) -> (usize, u8) {
+        ((slot / 8) as usize, 1u8 << (slot % 8))
+    }
+
+    pub fn is_taken(&self, slot: u32) -> bool {
+        let (byte, mask) = Self::bit(slot);
+        self.bitmap.get(byte).is_some_and(|b| b & mask != 0)
+    }
+
+    /// Number of reserved slots; always equals `Raffle.tickets_sold`.
+    pub fn taken_count(&self) -> u64 {
+        self.bitmap.iter().map(|b| b.count_ones() as u64).sum()
+    }
+
+    /// Owner of 1-based `ticket_number`, if that slot is reserved.
+    pub fn owner_of(&self, ticket_number: u64) -> Option<Pubkey> {
+        let slot = u32::try_from(ticket_number.checked_sub(1)?).ok()?;
+        if !self.is_taken(slot) {
+            return None;
+        }
+        self.owners.get(slot as usize).copied()
+    }
+
+    /// Reserve the 1-based ticket range `[start, start + count)` for `owner`.
+    /// All or nothing: the bitmap is untouched unless every slot is in range and free.
+    pub fn reserve_range(&mut self, start: u64, count: u64, owner: Pubkey) -> Result<()> {
+        require!(count > 0, RaffleError::InvalidAmount);
+        let end = start.checked_add(count).ok_or(RaffleError::Overflow)?;
+        require!(start >= 1 && end - 1 <= self.required_slots as u64, RaffleError::InvalidSlot);
+
+        let first = (start - 1) as u32;
+        let last = (end - 2) as u32;
+        for slot in first..=last {
+            require!(!self.is_taken(slot), RaffleError::SlotAlreadyTaken);
+        }
+        for slot in first..=last {
+            let (byte, mask) = Self::bit(slot);
+            self.bitmap[byte] |= mask;
+            self.owners[slot as usize] = owner;
+        }
+        Ok(())
+    }
+
+    /// True if the 1-based range `[start, start + count)` is non-empty, in range and free.
+    pub fn is_range_free(&self, start: u64, count: u64) -> bool {
+        let Some(end) = start.checked_add(count) else { return false };
+        count > 0
+            && start >= 1
+            && end - 1 <= self.required_slots as u64
+            && (start..end).all(|ticket| !self.is_taken((ticket - 1) as u32))
+    }
+
+    /// First free 1-based ticket number at or after `from` (`required_slots + 1` if none).
+    pub fn next_free(&self, from: u64) -> u64 {
+        let mut ticket = from.max(1);
+        while ticket <= self.required_slots as u64 && self.is_taken((ticket - 1) as u32) {
+            ticket += 1;
+        }
+        ticket
+    }
+
+    /// Start of the lowest free run of `count` tickets, for a `deposit` start index.
+    pub fn first_free_run(&self, count: u64) -> Option<u64> {
+        let mut start = self.next_free(1);
+        while start.checked_add(count)? - 1 <= self.required_slots as u64 {
+            match (start..start + count).find(|ticket| self.is_taken((ticket - 1) as u32)) {
+                Some(taken) => start = self.next_free(taken + 1),
+                None => return (count > 0).then_some(start),
+            }
+        }
+        None
+    }
+
+    /// `reserve_range` for a range deposit. A taken or out-of-range start means the client
+    /// read the slots before another entry landed, so it fails with `ConcurrentDeposit`.
+    pub fn reserve_deposit(&mut self, start: u64, count: u64, owner: Pubkey) -> Result<()> {
+        require!(self.is_range_free(start, count), RaffleError::ConcurrentDeposit);
+        self.reserve_range(start, count, owner)
+    }
+
+    /// Hand the reserved 1-based range `[start, start + count)` from `from` to `to`.
+    /// All or nothing: every slot must be reserved and owned by `from`.
+    pub fn transfer_range(&mut self, start: u64, count: u64, from: &Pubkey, to: Pubkey) -> Result<()> {
+        require!(count > 0, RaffleError::InvalidAmount);
+        let end = start.checked_add(count).ok_or(RaffleError::Overflow)?;
+        require!(start >= 1 && end - 1 <= self.required_slots as u64, RaffleError::InvalidSlot);
+
+        for n in start..end {
+            require!(self.owner_of(n) == Some(*from), RaffleError::Unauthorized);
+        }
+        for n in start..end {
+            self.owners[(n - 1) as usize] = to;
+        }
+        Ok(())
     }
 }
 
+/// Ticket range `(start, count)` for a slot join. Slots are 0-based and must be one
+/// ascending run (e.g. `[4, 5, 6]` → tickets 5..=7) so the Ticket range covers exactly
+/// the reserved slots; scattered picks need one join per run.
+pub fn slot_run_to_range(slots: &[u32]) -> Result<(u64, u64)> {
+    require!(!slots.is_empty(), RaffleError::InvalidAmount);
+    let contiguous = slots.windows(2).all(|w| w[0].checked_add(1) == Some(w[1]));
+    require!(contiguous, RaffleError::InvalidSlot);
+    Ok((slots[0] as u64 + 1, slots.len() as u64))
+}
+
 impl Ticket {
-    pub const LEN: usize = 32 + 32 + 8 + 8 + 1 + 1 + 1;
+    pub const LEN: usize = 32 + 32 + 8 + 8 + 1 + 1 + 1 + 32;
+
+    /// True if 1-based `ticket_number` falls in `[start, start + count)`.
+    pub fn covers(&self, ticket_number: u64) -> bool {
+        ticket_number >= self.start && ticket_number - self.start < self.count
+    }
 }
 
 // Temporarily commented out - Light SDK incompatible with rustc 1.79.0
@@ -1884,21 +4675,36 @@ impl Ticket {
 //     pub claimed_win: bool,
 // }
 
+// Events are emitted through a self-CPI (`emit_cpi!`) so they show up as inner
+// instructions instead of truncatable program logs. Payloads carry enough state for
+// indexers to rebuild raffles and tickets without account scans.
+
 #[event]
 pub struct RaffleInitialized {
     pub raffle: Pubkey,
     pub organizer: Pubkey,
     pub mint: Pubkey,
+    pub escrow: Pubkey,
     pub required_tickets: u64,
     pub deadline_unix_ts: i64,
+    pub auto_draw: bool,
+    pub ticket_mode: u8,
+    pub refund_mode: u8,
+    pub prize_collection_mint: Pubkey,
+    pub reveal_time_unix_ts: Option<i64>,
+    pub crank_reserve_lamports: u64,
 }
 
 #[event]
 pub struct Deposited {
     pub raffle: Pubkey,
+    pub ticket: Pubkey,
     pub owner: Pubkey,
+    pub payer: Pubkey,                   // Funded the entry; differs from `owner` for gifted tickets
     pub start: u64,
     pub count: u64,
+    pub slots: Vec<u32>,                 // 0-based slots reserved by joins; empty for range deposits
+    pub amount_paid: u64,                // Escrow mint base units moved into escrow
     pub tickets_sold: u64,
 }
 
@@ -1923,21 +4729,107 @@ pub struct WinnerSelected {
 #[event]
 pub struct Refunded {
     pub raffle: Pubkey,
+    pub ticket: Pubkey,
     pub owner: Pubkey,
+    pub count: u64,
+    pub amount: u64,
+}
+
+#[event]
+pub struct RaffleMigrated {
+    pub raffle: Pubkey,
+    pub from_version: u8,
+    pub to_version: u8,
+}
+
+#[event]
+pub struct TicketMigrated {
+    pub raffle: Pubkey,
+    pub ticket: Pubkey,
+    pub payer: Pubkey,                   // Owner of the legacy ticket, now its rent payer
+}
+
+#[event]
+pub struct PlatformAuthoritySet {
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct RefundVaultWithdrawn {
+    pub destination: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct MrftBackingSwept {
+    pub raffle: Pubkey,
+    pub refund_vault: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct RaffleClosed {
+    pub raffle: Pubkey,
+    pub organizer: Pubkey,
+}
+
+#[event]
+pub struct RefundingStarted {
+    pub raffle: Pubkey,
+    pub tickets_sold: u64,
+}
+
+#[event]
+pub struct KeeperBountyPaid {
+    pub raffle: Pubkey,
+    pub keeper: Pubkey,
     pub amount: u64,
 }
 
 #[event]
 pub struct RefundTicketsRequested {
     pub raffle: Pubkey,
+    pub ticket: Pubkey,
+    pub owner: Pubkey,
+    pub start: u64,
+    pub count: u64,
+    pub refund_mode: u8,
+    pub usdc_value_per_ticket: u64,      // Escrow mint base units backing each MRFT
+}
+
+#[event]
+pub struct RefundTicketsMinted {
+    pub raffle: Pubkey,
+    pub ticket: Pubkey,
     pub owner: Pubkey,
     pub start: u64,
     pub count: u64,
+    pub merkle_tree: Pubkey,
+    pub usdc_value_per_ticket: u64,      // Escrow mint base units backing each MRFT
+}
+
+#[event]
+pub struct MrftBackingMigrated {
+    pub origin_raffle: Pubkey,
+    pub raffle: Pubkey,
+    pub ticket: Pubkey,
+    pub count: u64,
+    pub amount: u64,
+}
+
+#[event]
+pub struct MrftsBoughtBack {
+    pub origin_raffle: Pubkey,
+    pub holder: Pubkey,
+    pub count: u64,
+    pub amount: u64,                     // Paid to the holder
+    pub haircut: u64,                    // Kept by the refund vault
 }
 
 #[event]
 pub struct WinClaimed {
     pub raffle: Pubkey,
+    pub ticket: Pubkey,
     pub owner: Pubkey,
     pub winner_ticket: u64,
 }
@@ -1948,6 +4840,79 @@ pub struct PrizeSet {
     pub prize_mint: Pubkey,
 }
 
+#[event]
+pub struct AllowlistSet {
+    pub raffle: Pubkey,
+    pub allowlist_root: [u8; 32],
+    pub presale_end_unix_ts: i64,
+}
+
+#[event]
+pub struct GateSet {
+    pub raffle: Pubkey,
+    pub gate_mode: u8,
+    pub gate_mint: Pubkey,
+    pub gate_min_amount: u64,
+}
+
+#[event]
+pub struct ClaimWindowSet {
+    pub raffle: Pubkey,
+    pub claim_window_secs: i64,
+}
+
+#[event]
+pub struct WinnerForfeited {
+    pub raffle: Pubkey,
+    pub winner_ticket: u64,
+    pub owner: Pubkey,                   // Excluded from the redraw
+}
+
+#[event]
+pub struct PrizeBackingSet {
+    pub raffle: Pubkey,
+    pub prize_backed: bool,
+}
+
+#[event]
+pub struct TicketTransferred {
+    pub raffle: Pubkey,
+    pub old_ticket: Pubkey,                  // Closed PDA under the sender
+    pub ticket: Pubkey,                      // New PDA under the recipient
+    pub from: Pubkey,
+    pub to: Pubkey,
+    pub start: u64,
+    pub count: u64,
+}
+
+#[event]
+pub struct TicketTokenized {
+    pub raffle: Pubkey,
+    pub old_ticket: Pubkey,                  // Closed PDA under the previous owner
+    pub ticket: Pubkey,                      // New PDA under the ticket mint
+    pub ticket_mint: Pubkey,
+    pub holder: Pubkey,
+    pub start: u64,
+    pub count: u64,
+}
+
+#[event]
+pub struct AllowlistRegistered {
+    pub raffle: Pubkey,
+    pub wallet: Pubkey,
+    pub max_allocation: u64,
+}
+
+#[event]
+pub struct EscrowAudited {
+    pub raffle: Pubkey,
+    pub status: u8,
+    pub tickets_sold: u64,
+    pub tickets_refunded: u64,
+    pub expected: u64,
+    pub actual: u64,
+}
+
 #[event]
 pub struct PrizeClaimed {
     pub raffle: Pubkey,
@@ -1963,7 +4928,7 @@ pub enum RaffleError {
     #[msg("Overflow")] Overflow,
     #[msg("Raffle not selling")] RaffleNotSelling,
     #[msg("Past deadline")] PastDeadline,
-    #[msg("Concurrent deposit; refresh raffle and pass current start_index")] ConcurrentDeposit,
+    #[msg("Ticket range already taken; refresh slots and pass a free start_index")] ConcurrentDeposit,
     #[msg("Over-subscription")] OverSubscription,
     #[msg("Wrong status")] WrongStatus,
     #[msg("Invalid winner index")] InvalidWinner,
@@ -1994,6 +4959,47 @@ pub enum RaffleError {
     #[msg("Jupiter accounts missing")] JupiterAccountsMissing,
     #[msg("Batch size exceeded")] BatchSizeExceeded,
     #[msg("Invalid collection mint")] InvalidCollectionMint,
+    #[msg("Invalid reveal time")] InvalidRevealTime,
+    #[msg("Reveal time not reached")] RevealTimeNotReached,
+    #[msg("Invalid remaining accounts")] InvalidRemainingAccounts,
+    #[msg("Raffle not settled")] RaffleNotSettled,
+    #[msg("Tickets still open")] TicketsStillOpen,
+    #[msg("Slots account still open")] SlotsStillOpen,
+    #[msg("Escrow not empty")] EscrowNotEmpty,
+    #[msg("Unknown raffle account layout")] UnknownRaffleLayout,
+    #[msg("Account already uses the current layout")] AlreadyMigrated,
+    #[msg("Escrow balance does not match raffle counters")] EscrowMismatch,
+    #[msg("Allowlist can only be set before the first sale")] AllowlistLocked,
+    #[msg("Invalid presale end")] InvalidPresaleEnd,
+    #[msg("Presale ended")] PresaleEnded,
+    #[msg("Presale still active")] PresaleActive,
+    #[msg("Invalid allowlist proof")] InvalidAllowlistProof,
+    #[msg("Wallet not allowlisted for the presale")] NotAllowlisted,
+    #[msg("Presale allocation exceeded")] AllocationExceeded,
+    #[msg("Gate can only be set before the first sale")] GateLocked,
+    #[msg("Invalid gate configuration")] InvalidGate,
+    #[msg("Wallet does not hold the gating token")] GateNotMet,
+    #[msg("Ticket cannot be transferred in its current state")] TicketNotTransferable,
+    #[msg("Invalid recipient")] InvalidRecipient,
+    #[msg("Prize must be escrowed before tickets can be sold")] PrizeNotEscrowed,
+    #[msg("Prize can only be set while the raffle is selling")] PrizeLocked,
+    #[msg("Prize backing can only be set before the first sale")] PrizeBackingLocked,
+    #[msg("Prize master edition must allow no prints")] InvalidMasterEdition,
+    #[msg("Invalid prize metadata")] InvalidPrizeMetadata,
+    #[msg("Prize metadata not configured")] PrizeMetadataNotSet,
+    #[msg("Missing or invalid prize metadata / pNFT transfer accounts")] InvalidPrizeAccounts,
+    #[msg("Missing or invalid MRFT minting accounts")] InvalidMrftAccounts,
+    #[msg("This raffle does not accept MRFTs")] MrftNotAccepted,
+    #[msg("MRFT is not worth one ticket of this raffle")] InvalidMrft,
+    #[msg("Claim window must not be negative")] InvalidClaimWindow,
+    #[msg("Claim window can't change after the first ticket is sold")] ClaimWindowLocked,
+    #[msg("Claim window has expired")] ClaimWindowExpired,
+    #[msg("Claim window is still open")] ClaimWindowOpen,
+    #[msg("Winning ticket belongs to a forfeited winner")] ExcludedWinner,
+    #[msg("Too many forfeited winners")] TooManyForfeits,
+    #[msg("Unknown ticket account layout")] UnknownTicketLayout,
+    #[msg("MRFT backing can't be swept yet")] SweepTooEarly,
+    #[msg("MRFT backing already swept to the refund vault")] AlreadySwept,
 }
 
 #[repr(u8)]
@@ -2009,3 +5015,742 @@ pub enum ArcError {
     #[msg("The computation was aborted")] AbortedComputation,
     #[msg("The cluster is not set")] ClusterNotSet,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::collection::vec;
+    use proptest::prelude::*;
+    use solana_ed25519_program::new_ed25519_instruction_with_signature;
+
+    fn max_raffle() -> Raffle {
+        Raffle::from(RaffleV1 {
+            legacy: RaffleV0 {
+                organizer: Pubkey::new_unique(),
+                mint: Pubkey::new_unique(),
+                escrow: Pubkey::new_unique(),
+                required_tickets: u64::MAX,
+                tickets_sold: 42,
+                next_ticket_index: 43,
+                deadline: i64::MAX,
+                status: RaffleStatus::Refunding as u8,
+                winner_ticket: 7,
+                bump: 255,
+                prize_mint: Pubkey::new_unique(),
+                prize_escrow: Pubkey::new_unique(),
+                prize_set: true,
+                prize_claimed: false,
+                proceeds_collected: false,
+                auto_draw: true,
+                ticket_mode: 2,
+            },
+            prize_collection_mint: Pubkey::new_unique(),
+            refund_mode: 1,
+        })
+    }
+
+    #[test]
+    fn raffle_len_matches_serialized_size() {
+        let mut raffle = max_raffle();
+        raffle.reveal_time_unix_ts = Some(i64::MAX);
+        raffle.prize_metadata = PrizeMetadata {
+            name: "n".repeat(MAX_PRIZE_NAME_LEN),
+            symbol: "s".repeat(MAX_PRIZE_SYMBOL_LEN),
+            uri: "u".repeat(MAX_PRIZE_URI_LEN),
+            seller_fee_basis_points: 500,
+            creators: vec![PrizeCreator { address: Pubkey::new_unique(), share: 20 }; MAX_PRIZE_CREATORS],
+        };
+        raffle.prize_metadata.validate().unwrap();
+        raffle.forfeited_owners = vec![Pubkey::new_unique(); MAX_WINNER_FORFEITS];
+        assert_eq!(raffle.try_to_vec().unwrap().len(), Raffle::LEN);
+    }
+
+    #[test]
+    fn prize_metadata_rejects_out_of_range_values() {
+        let valid = PrizeMetadata {
+            name: "Rolex Cosmograph Daytona".into(),
+            symbol: "ROLEX".into(),
+            uri: "https://example.com/rolex.json".into(),
+            seller_fee_basis_points: 500,
+            creators: vec![PrizeCreator { address: Pubkey::new_unique(), share: 100 }],
+        };
+        valid.validate().unwrap();
+        PrizeMetadata::default().validate().unwrap();
+
+        let too_long = PrizeMetadata { symbol: "S".repeat(MAX_PRIZE_SYMBOL_LEN + 1), ..valid.clone() };
+        let bad_fee = PrizeMetadata { seller_fee_basis_points: 10_001, ..valid.clone() };
+        let bad_shares = PrizeMetadata { creators: vec![PrizeCreator { address: Pubkey::new_unique(), share: 99 }], ..valid.clone() };
+        for metadata in [too_long, bad_fee, bad_shares] {
+            assert!(metadata.validate().is_err());
+        }
+        assert_ne!(prize_metadata_hash(&valid), prize_metadata_hash(&PrizeMetadata::default()));
+    }
+
+    #[test]
+    fn legacy_layout_sizes_are_frozen() {
+        // Sizes of accounts already on-chain; changing them breaks migration.
+        assert_eq!(RaffleV0::LEN, 207);
+        assert_eq!(RaffleV1::LEN, 240);
+        assert!(![RaffleV0::LEN, RaffleV1::LEN].contains(&Raffle::LEN));
+    }
+
+    #[test]
+    fn legacy_tickets_migrate_with_the_owner_as_payer() {
+        let legacy = TicketV0 {
+            raffle: Pubkey::new_unique(),
+            owner: Pubkey::new_unique(),
+            start: 5,
+            count: 3,
+            refunded: true,
+            claimed_win: false,
+            bump: 253,
+        };
+        // Size of tickets already on-chain; changing it breaks migration
+        assert_eq!(legacy.try_to_vec().unwrap().len(), TicketV0::LEN);
+        assert_eq!(TicketV0::LEN, 83);
+        assert_ne!(TicketV0::LEN, Ticket::LEN);
+
+        let owner = legacy.owner;
+        let ticket = Ticket::from(legacy);
+        assert_eq!(ticket.payer, owner);
+        assert_eq!((ticket.start, ticket.count, ticket.refunded, ticket.bump), (5, 3, true, 253));
+    }
+
+    #[test]
+    fn ticket_and_slots_space_match_serialized_size() {
+        let ticket = Ticket {
+            raffle: Pubkey::new_unique(),
+            owner: Pubkey::new_unique(),
+            start: 1,
+            count: 10,
+            refunded: false,
+            claimed_win: false,
+            bump: 254,
+            payer: Pubkey::new_unique(),
+        };
+        assert_eq!(ticket.try_to_vec().unwrap().len(), Ticket::LEN);
+
+        for required in [1u64, 7, 8, 9, 100] {
+            let slots = RaffleSlots {
+                raffle: Pubkey::new_unique(),
+                required_slots: required as u32,
+                bitmap: vec![0u8; required.div_ceil(8) as usize],
+                owners: vec![Pubkey::default(); required as usize],
+            };
+            assert_eq!(slots.try_to_vec().unwrap().len(), RaffleSlots::space(required));
+        }
+    }
+
+    #[test]
+    fn migration_preserves_legacy_fields() {
+        let legacy = RaffleV1 {
+            legacy: RaffleV0 {
+                organizer: Pubkey::new_unique(),
+                mint: Pubkey::new_unique(),
+                escrow: Pubkey::new_unique(),
+                required_tickets: 100,
+                tickets_sold: 40,
+                next_ticket_index: 41,
+                deadline: 1_700_000_000,
+                status: RaffleStatus::Selling as u8,
+                winner_ticket: 0,
+                bump: 250,
+                prize_mint: Pubkey::new_unique(),
+                prize_escrow: Pubkey::new_unique(),
+                prize_set: true,
+                prize_claimed: false,
+                proceeds_collected: false,
+                auto_draw: true,
+                ticket_mode: 1,
+            },
+            prize_collection_mint: Pubkey::new_unique(),
+            refund_mode: 2,
+        };
+        let bytes = legacy.try_to_vec().unwrap();
+        assert_eq!(bytes.len(), RaffleV1::LEN);
+
+        let raffle = Raffle::from(RaffleV1::deserialize(&mut bytes.as_slice()).unwrap());
+        assert_eq!(raffle.version, RAFFLE_VERSION);
+        assert_eq!(raffle.organizer, legacy.legacy.organizer);
+        assert_eq!(raffle.escrow, legacy.legacy.escrow);
+        assert_eq!(raffle.tickets_sold, 40);
+        assert_eq!(raffle.next_ticket_index, 41);
+        assert_eq!(raffle.bump, 250);
+        assert_eq!(raffle.prize_collection_mint, legacy.prize_collection_mint);
+        assert_eq!(raffle.refund_mode, 2);
+        assert_eq!(raffle.reveal_time_unix_ts, None);
+        assert_eq!(raffle.open_tickets, 40);
+        assert!(!raffle.draw_requested);
+
+        // A raffle already refunding had only ever requested MRFTs
+        let mut refunding = legacy;
+        refunding.legacy.status = RaffleStatus::Refunding as u8;
+        refunding.refund_mode = 0;
+        let raffle = Raffle::from(refunding);
+        assert_eq!(raffle.refund_mode, 1);
+        assert_eq!(raffle.tickets_refunded, 0);
+        assert!(!raffle.is_settled());
+    }
+
+    #[test]
+    fn ed25519_ix_parts_reads_the_precompile_layout() {
+        let message = b"permit";
+        let ix = new_ed25519_instruction_with_signature(message, &[7u8; 64], &BACKEND_SIGNER.to_bytes());
+        assert_eq!(ed25519_ix_parts(&ix.data), Some((BACKEND_SIGNER.as_ref(), &message[..])));
+
+        // Signature, key or message taken from another instruction
+        for at in [4, 8, 14] {
+            let mut data = ix.data.clone();
+            data[at..at + 2].copy_from_slice(&0u16.to_le_bytes());
+            assert_eq!(ed25519_ix_parts(&data), None);
+        }
+        // Several signatures, or a message running past the data
+        let mut data = ix.data.clone();
+        data[0] = 2;
+        assert_eq!(ed25519_ix_parts(&data), None);
+        assert_eq!(ed25519_ix_parts(&ix.data[..ix.data.len() - 1]), None);
+    }
+
+    #[test]
+    fn expected_escrow_follows_usdc_flows() {
+        let unit = 1_000_000;
+        let mut raffle = max_raffle();
+        raffle.refund_mode = 0;
+        raffle.tickets_sold = 10;
+        raffle.tickets_refunded = 4;
+        assert_eq!(raffle.expected_escrow(unit).unwrap(), 6 * unit);
+
+        // MRFT refunds keep the USDC in escrow
+        raffle.refund_mode = 1;
+        assert_eq!(raffle.expected_escrow(unit).unwrap(), 10 * unit);
+
+        // ... until the MRFTs are redeemed elsewhere and the USDC follows them
+        raffle.mrft_backing_released = 2;
+        assert_eq!(raffle.expected_escrow(unit).unwrap(), 8 * unit);
+        raffle.mrft_backing_released = 0;
+
+        // ... or swept to the refund vault
+        raffle.mrft_backing_swept = true;
+        assert_eq!(raffle.expected_escrow(unit).unwrap(), 0);
+        raffle.mrft_backing_swept = false;
+
+        raffle.proceeds_collected = true;
+        assert_eq!(raffle.expected_escrow(unit).unwrap(), 0);
+
+        // Counters that would leave escrow negative are corrupt, not solvent
+        raffle.proceeds_collected = false;
+        raffle.refund_mode = 0;
+        raffle.tickets_refunded = 11;
+        assert!(raffle.expected_escrow(unit).is_err());
+    }
+
+    #[test]
+    fn mrft_proofs_are_split_per_leaf_and_fully_used() {
+        let unit = 1_000_000;
+        let leaf = |proof_len| MrftLeaf { usdc_value: unit, proof_len, ..Default::default() };
+        let nodes = [1, 2, 3, 4, 5];
+
+        let paths = mrft_proof_paths(&[leaf(3), leaf(0), leaf(2)], &nodes, unit).unwrap();
+        assert_eq!(paths, vec![&nodes[..3], &nodes[3..3], &nodes[3..]]);
+
+        let err = |mrfts: &[MrftLeaf]| mrft_proof_paths(mrfts, &nodes, unit).unwrap_err();
+        // Short or leftover proof nodes would hash against the wrong leaf
+        assert_eq!(err(&[leaf(3), leaf(3)]), RaffleError::InvalidProof.into());
+        assert_eq!(err(&[leaf(3), leaf(1)]), RaffleError::InvalidProof.into());
+        // An MRFT of another denomination doesn't redeem a ticket here
+        let cheap = MrftLeaf { usdc_value: unit / 2, ..leaf(5) };
+        assert_eq!(err(&[cheap]), RaffleError::InvalidMrft.into());
+    }
+
+    #[test]
+    fn mrft_backing_is_released_once_per_refunded_ticket() {
+        let mut raffle = max_raffle();
+        raffle.status = RaffleStatus::Refunding as u8;
+        raffle.refund_mode = 1;
+        raffle.tickets_refunded = 3;
+        raffle.mrft_backing_released = 0;
+        raffle.mrft_backing_swept = false;
+
+        raffle.release_mrft_backing(2).unwrap();
+        raffle.release_mrft_backing(1).unwrap();
+        assert_eq!(raffle.mrft_backing_released, 3);
+        // Every refunded ticket's backing is gone; a replayed MRFT finds nothing left
+        assert_eq!(raffle.release_mrft_backing(1).unwrap_err(), RaffleError::InvalidMrft.into());
+        assert_eq!(raffle.mrft_backing_released, 3);
+
+        raffle.mrft_backing_released = 0;
+        raffle.mrft_backing_swept = true;
+        assert_eq!(raffle.release_mrft_backing(1).unwrap_err(), RaffleError::AlreadySwept.into());
+        raffle.mrft_backing_swept = false;
+        // USDC-refund raffles and raffles that didn't fail never issued MRFTs
+        raffle.refund_mode = 0;
+        assert_eq!(raffle.release_mrft_backing(1).unwrap_err(), RaffleError::InvalidMrft.into());
+        raffle.refund_mode = 1;
+        raffle.status = RaffleStatus::Completed as u8;
+        assert_eq!(raffle.release_mrft_backing(1).unwrap_err(), RaffleError::InvalidMrft.into());
+    }
+
+    #[test]
+    fn delegated_cnft_prize_does_not_secure_raffle() {
+        let mut raffle = max_raffle();
+        raffle.prize_backed = true;
+        raffle.prize_collection_mint = Pubkey::default();
+        raffle.prize_cnft = CompressedPrize { merkle_tree: Pubkey::new_unique(), nonce: 3, ..Default::default() };
+        assert!(raffle.prize_cnft.is_set());
+        assert!(raffle.is_prize_secured());
+
+        // The organizer still owns a delegated leaf and could move it before the claim
+        raffle.prize_cnft.delegated = true;
+        assert!(!raffle.is_prize_secured());
+    }
+
+    #[test]
+    fn mrft_haircut_starts_after_grace_and_is_capped() {
+        let amount = 5_000_000;
+        let day = 24 * 60 * 60;
+        assert_eq!(mrft_buyback_haircut(amount, -day), 0);
+        assert_eq!(mrft_buyback_haircut(amount, MRFT_HAIRCUT_GRACE_SECS), 0);
+        // A started day counts in full
+        let one_day = amount * MRFT_HAIRCUT_BPS_PER_DAY / 10_000;
+        assert_eq!(mrft_buyback_haircut(amount, MRFT_HAIRCUT_GRACE_SECS + 1), one_day);
+        assert_eq!(mrft_buyback_haircut(amount, MRFT_HAIRCUT_GRACE_SECS + 3 * day), 3 * one_day);
+        assert_eq!(mrft_buyback_haircut(amount, i64::MAX), amount * MRFT_MAX_HAIRCUT_BPS / 10_000);
+    }
+
+    #[test]
+    fn reveal_time_must_fall_between_now_and_the_deadline() {
+        let (now, deadline) = (1_000, 2_000);
+        assert!(validate_reveal_time(None, deadline, now).is_ok());
+        assert!(validate_reveal_time(Some(now + 1), deadline, now).is_ok());
+        assert!(validate_reveal_time(Some(deadline), deadline, now).is_ok());
+        for reveal_ts in [now - 1, now, deadline + 1] {
+            assert_eq!(
+                validate_reveal_time(Some(reveal_ts), deadline, now).unwrap_err(),
+                RaffleError::InvalidRevealTime.into()
+            );
+        }
+    }
+
+    #[test]
+    fn completed_raffle_settles_once_prize_and_proceeds_are_out() {
+        let mut raffle = max_raffle();
+        raffle.status = RaffleStatus::Completed as u8;
+        raffle.win_claimed = true;
+        raffle.proceeds_collected = true;
+        assert!(!raffle.is_settled());
+        raffle.prize_claimed = true;
+        assert!(raffle.is_settled());
+
+        // No on-chain prize: the recorded win is all the winner gets from the program
+        raffle.prize_set = false;
+        raffle.prize_claimed = false;
+        raffle.prize_metadata = PrizeMetadata::default();
+        assert!(raffle.is_settled());
+        raffle.win_claimed = false;
+        assert!(!raffle.is_settled());
+        raffle.win_claimed = true;
+        raffle.proceeds_collected = false;
+        assert!(!raffle.is_settled());
+    }
+
+    #[test]
+    fn prize_nft_must_be_a_verified_one_of_one_from_the_collection() {
+        let mut raffle = max_raffle();
+        let collection = raffle.prize_collection_mint;
+        let err = |raffle: &Raffle, member: Option<(Pubkey, bool)>, max_supply| {
+            check_prize_nft(raffle, member, max_supply).unwrap_err()
+        };
+        assert!(check_prize_nft(&raffle, Some((collection, true)), Some(0)).is_ok());
+        assert_eq!(err(&raffle, Some((collection, false)), Some(0)), RaffleError::InvalidCollection.into());
+        assert_eq!(err(&raffle, None, Some(0)), RaffleError::InvalidCollection.into());
+        assert_eq!(err(&raffle, Some((PRIZE_COLLECTION_MINT, true)), Some(0)), RaffleError::InvalidCollection.into());
+
+        // Printable or open editions aren't 1/1s
+        assert_eq!(err(&raffle, Some((collection, true)), Some(5)), RaffleError::InvalidMasterEdition.into());
+        assert_eq!(err(&raffle, Some((collection, true)), None), RaffleError::InvalidMasterEdition.into());
+
+        // Raffles without their own collection take the platform's
+        raffle.prize_collection_mint = Pubkey::default();
+        assert!(check_prize_nft(&raffle, Some((PRIZE_COLLECTION_MINT, true)), Some(0)).is_ok());
+        assert_eq!(err(&raffle, Some((collection, true)), Some(0)), RaffleError::InvalidCollection.into());
+    }
+
+    #[test]
+    fn only_token_metadata_pnft_metadata_makes_a_programmable_prize() {
+        let pda = Pubkey::new_unique();
+        let standard = |programmable| move || Ok(programmable);
+        let unread = || -> Result<bool> { panic!("metadata read") };
+
+        // No metadata, or metadata Token Metadata doesn't own, is a regular NFT
+        assert!(!programmable_from_metadata(None, || pda, unread).unwrap());
+        assert!(!programmable_from_metadata(Some((pda, Pubkey::new_unique())), || pda, unread).unwrap());
+        assert!(programmable_from_metadata(Some((pda, MPL_TOKEN_METADATA_ID)), || pda, standard(true)).unwrap());
+        assert!(!programmable_from_metadata(Some((pda, MPL_TOKEN_METADATA_ID)), || pda, standard(false)).unwrap());
+
+        // Token Metadata's account of another mint
+        let other = Some((Pubkey::new_unique(), MPL_TOKEN_METADATA_ID));
+        assert_eq!(programmable_from_metadata(other, || pda, unread).unwrap_err(), RaffleError::InvalidPrizeAccounts.into());
+    }
+
+    fn leaked_account(key: Pubkey, executable: bool) -> &'static AccountInfo<'static> {
+        Box::leak(Box::new(AccountInfo::new(
+            Box::leak(Box::new(key)),
+            false,
+            true,
+            Box::leak(Box::new(0)),
+            Box::leak(Vec::new().into_boxed_slice()),
+            Box::leak(Box::new(Pubkey::default())),
+            executable,
+            0,
+        )))
+    }
+
+    fn unchecked() -> UncheckedAccount<'static> {
+        UncheckedAccount::try_from(leaked_account(Pubkey::new_unique(), false))
+    }
+
+    fn prize_move() -> PrizeMove<'static> {
+        let info = || leaked_account(Pubkey::new_unique(), false).clone();
+        PrizeMove { from: info(), from_owner: info(), to: info(), to_owner: info(), mint: info(), payer: info(), token_program: info() }
+    }
+
+    fn pnft_accounts() -> ProgrammableTransfer<'static> {
+        ProgrammableTransfer {
+            token_metadata_program: Some(UncheckedAccount::try_from(leaked_account(MPL_TOKEN_METADATA_ID, true))),
+            source_token_record: Some(unchecked()),
+            destination_token_record: Some(unchecked()),
+            sysvar_instructions: Some(unchecked()),
+            associated_token_program: Some(Program::try_from(leaked_account(AssociatedToken::id(), true)).unwrap()),
+            system_program: Some(Program::try_from(leaked_account(System::id(), true)).unwrap()),
+            authorization_rules_program: None,
+            authorization_rules: None,
+        }
+    }
+
+    #[test]
+    fn pnft_transfer_needs_every_account_but_the_rule_set() {
+        let (metadata, edition) = (unchecked(), unchecked());
+        let pnft = pnft_accounts();
+        let call = TransferV1Call::new(prize_move(), Some(&metadata), Some(&edition), &pnft).unwrap();
+        assert_eq!(call.token_metadata_program.key(), MPL_TOKEN_METADATA_ID);
+        assert_eq!((call.metadata.key(), call.edition.key()), (metadata.key(), edition.key()));
+        let records = (pnft.source_token_record.as_ref().unwrap().key(), pnft.destination_token_record.as_ref().unwrap().key());
+        assert_eq!((call.source_token_record.key(), call.destination_token_record.key()), records);
+        assert_eq!(call.sysvar_instructions.key(), pnft.sysvar_instructions.as_ref().unwrap().key());
+        assert_eq!((call.associated_token_program.key(), call.system_program.key()), (AssociatedToken::id(), System::id()));
+        assert!(call.authorization_rules_program.is_none() && call.authorization_rules.is_none());
+        assert_eq!(call.amount, 1);
+
+        // A rule set is passed through when given
+        let (rules_program, rules) = (unchecked(), unchecked());
+        let ruled = ProgrammableTransfer {
+            authorization_rules_program: Some(rules_program.clone()),
+            authorization_rules: Some(rules.clone()),
+            ..pnft_accounts()
+        };
+        let call = TransferV1Call::new(prize_move(), Some(&metadata), Some(&edition), &ruled).unwrap();
+        assert_eq!(call.authorization_rules_program.map(|a| a.key()), Some(rules_program.key()));
+        assert_eq!(call.authorization_rules.map(|a| a.key()), Some(rules.key()));
+
+        let err = |metadata, edition, pnft: &ProgrammableTransfer<'static>| {
+            TransferV1Call::new(prize_move(), metadata, edition, pnft).err().unwrap()
+        };
+        let invalid: Error = RaffleError::InvalidPrizeAccounts.into();
+        assert_eq!(err(None, Some(&edition), &pnft), invalid);
+        assert_eq!(err(Some(&metadata), None, &pnft), invalid);
+        assert_eq!(err(Some(&metadata), Some(&edition), &ProgrammableTransfer { destination_token_record: None, ..pnft_accounts() }), invalid);
+        assert_eq!(err(Some(&metadata), Some(&edition), &ProgrammableTransfer { system_program: None, ..pnft_accounts() }), invalid);
+        let impostor = ProgrammableTransfer { token_metadata_program: Some(unchecked()), ..pnft_accounts() };
+        assert_eq!(err(Some(&metadata), Some(&edition), &impostor), invalid);
+    }
+
+    #[test]
+    fn claim_window_expires_only_for_unclaimed_wins() {
+        let mut raffle = max_raffle();
+        raffle.winner_selected_at = 1_000;
+        raffle.claim_window_secs = 0;
+        assert!(!raffle.is_claim_window_expired(i64::MAX));
+
+        raffle.claim_window_secs = 100;
+        assert!(!raffle.is_claim_window_expired(1_100));
+        assert!(raffle.is_claim_window_expired(1_101));
+
+        raffle.win_claimed = true;
+        assert!(!raffle.is_claim_window_expired(1_101));
+    }
+
+    #[test]
+    fn unset_mrft_collection_accepts_platform_mrfts() {
+        let mut raffle = max_raffle();
+        raffle.mrft_collection = Pubkey::default();
+        assert_eq!(raffle.accepted_mrft_collection(), MRFT_COLLECTION_MINT);
+
+        let partner = Pubkey::new_unique();
+        raffle.mrft_collection = partner;
+        assert_eq!(raffle.accepted_mrft_collection(), partner);
+    }
+
+    /// Allowlist tree layers, leaves first; an odd node out is carried up unchanged.
+    fn allowlist_layers(entries: &[(Pubkey, u64)]) -> Vec<Vec<[u8; 32]>> {
+        let mut layers = vec![entries.iter().map(|(w, max)| allowlist_leaf(w, *max)).collect::<Vec<_>>()];
+        while layers.last().unwrap().len() > 1 {
+            let next = layers
+                .last()
+                .unwrap()
+                .chunks(2)
+                .map(|pair| if pair.len() == 2 { allowlist_node(&pair[0], &pair[1]) } else { pair[0] })
+                .collect();
+            layers.push(next);
+        }
+        layers
+    }
+
+    fn allowlist_proof(layers: &[Vec<[u8; 32]>], mut index: usize) -> Vec<[u8; 32]> {
+        let mut proof = Vec::new();
+        for layer in &layers[..layers.len() - 1] {
+            if let Some(sibling) = layer.get(index ^ 1) {
+                proof.push(*sibling);
+            }
+            index /= 2;
+        }
+        proof
+    }
+
+    fn empty_slots(required: u32) -> RaffleSlots {
+        RaffleSlots {
+            raffle: Pubkey::new_unique(),
+            required_slots: required,
+            bitmap: vec![0u8; (required as usize).div_ceil(8)],
+            owners: vec![Pubkey::default(); required as usize],
+        }
+    }
+
+    fn ticket(start: u64, count: u64, owner: Pubkey) -> Ticket {
+        Ticket { raffle: Pubkey::default(), owner, start, count, refunded: false, claimed_win: false, bump: 0, payer: owner }
+    }
+
+    /// `deposit`'s slot bookkeeping for a fresh beneficiary.
+    fn deposit_at(slots: &mut RaffleSlots, next_ticket_index: &mut u64, tickets: &mut Vec<Ticket>, start: u64, count: u64) -> Result<()> {
+        let owner = Pubkey::new_unique();
+        slots.reserve_deposit(start, count, owner)?;
+        tickets.push(ticket(start, count, owner));
+        *next_ticket_index = slots.next_free(*next_ticket_index);
+        Ok(())
+    }
+
+    #[test]
+    fn deposit_over_a_joined_slot_is_rejected_untouched() {
+        let mut slots = empty_slots(10);
+        let (start, count) = slot_run_to_range(&[1, 2]).unwrap();
+        slots.reserve_range(start, count, Pubkey::new_unique()).unwrap();
+        // next_ticket_index is still 1; tickets 2 and 3 were joined
+        assert_eq!(slots.next_free(1), 1);
+
+        let bitmap = slots.bitmap.clone();
+        let err = slots.reserve_deposit(1, 3, Pubkey::new_unique()).unwrap_err();
+        assert_eq!(err, RaffleError::ConcurrentDeposit.into());
+        assert_eq!(slots.bitmap, bitmap);
+        assert_eq!(slots.first_free_run(3), Some(4));
+
+        slots.reserve_deposit(1, 1, Pubkey::new_unique()).unwrap();
+        assert_eq!(slots.next_free(1), 4);
+        assert_eq!(slots.first_free_run(8), None);
+        assert!(slots.reserve_deposit(4, 8, Pubkey::new_unique()).is_err());
+    }
+
+    proptest! {
+        #[test]
+        fn slots_space_matches_serialized_size(required in 1u64..2_000) {
+            let slots = empty_slots(required as u32);
+            prop_assert_eq!(slots.try_to_vec().unwrap().len(), RaffleSlots::space(required));
+        }
+
+        /// Random (often overlapping or out-of-range) reservations: accepted ones never
+        /// touch a taken slot, rejected ones leave the bitmap untouched, and the set bits
+        /// always equal the tickets sold so far.
+        #[test]
+        fn reservations_never_double_book(
+            required in 1u32..200,
+            requests in vec((0u64..210, 0u64..12, 0usize..4), 0..60),
+        ) {
+            let owners: Vec<Pubkey> = (0..4).map(|_| Pubkey::new_unique()).collect();
+            let mut slots = empty_slots(required);
+            let mut model: Vec<Option<Pubkey>> = vec![None; required as usize];
+            let mut tickets_sold = 0u64;
+
+            for (start, count, who) in requests {
+                let before = slots.bitmap.clone();
+                let fits = count > 0 && start >= 1 && start + count - 1 <= required as u64;
+                let free = fits && (start..start + count).all(|t| model[(t - 1) as usize].is_none());
+                match slots.reserve_range(start, count, owners[who]) {
+                    Ok(()) => {
+                        prop_assert!(free);
+                        for t in start..start + count {
+                            model[(t - 1) as usize] = Some(owners[who]);
+                        }
+                        tickets_sold += count;
+                    }
+                    Err(_) => {
+                        prop_assert!(!free);
+                        prop_assert_eq!(&slots.bitmap, &before);
+                    }
+                }
+                prop_assert_eq!(slots.taken_count(), tickets_sold);
+            }
+            for t in 1..=required as u64 {
+                prop_assert_eq!(slots.owner_of(t), model[(t - 1) as usize]);
+            }
+        }
+
+        /// Selling out through range deposits of random sizes: every winning ticket in
+        /// `1..=required_tickets` is covered by exactly one Ticket, owned by the slot owner.
+        #[test]
+        fn every_winner_maps_to_one_owner(required in 1u64..300, sizes in vec(1u64..20, 1..64)) {
+            let mut slots = empty_slots(required as u32);
+            let mut tickets = Vec::new();
+            let mut next_ticket_index = 1u64;
+            for size in sizes.iter().cycle() {
+                if next_ticket_index > required {
+                    break;
+                }
+                let count = (*size).min(required - next_ticket_index + 1);
+                let owner = Pubkey::new_unique();
+                slots.reserve_range(next_ticket_index, count, owner).unwrap();
+                tickets.push(ticket(next_ticket_index, count, owner));
+                next_ticket_index += count;
+            }
+
+            prop_assert_eq!(slots.taken_count(), required);
+            for winner in 1..=required {
+                let holders: Vec<&Ticket> = tickets.iter().filter(|t| t.covers(winner)).collect();
+                prop_assert_eq!(holders.len(), 1);
+                prop_assert_eq!(slots.owner_of(winner), Some(holders[0].owner));
+            }
+            prop_assert!(!tickets.iter().any(|t| t.covers(0) || t.covers(required + 1)));
+        }
+
+        /// Random transfers between sold-out ranges: only the sender's own full range moves,
+        /// rejected transfers change nothing, and the bitmap is never touched.
+        #[test]
+        fn transfers_move_only_owned_ranges(
+            sizes in vec(1u64..10, 1..20),
+            transfers in vec((0usize..20, 0usize..4, 0usize..4), 0..40),
+        ) {
+            let wallets: Vec<Pubkey> = (0..4).map(|_| Pubkey::new_unique()).collect();
+            let required: u64 = sizes.iter().sum();
+            let mut slots = empty_slots(required as u32);
+            let mut tickets = Vec::new();
+            let mut start = 1u64;
+            for (i, size) in sizes.iter().enumerate() {
+                let owner = wallets[i % wallets.len()];
+                slots.reserve_range(start, *size, owner).unwrap();
+                tickets.push(ticket(start, *size, owner));
+                start += size;
+            }
+            let bitmap = slots.bitmap.clone();
+
+            for (which, from, to) in transfers {
+                let Some(t) = tickets.get_mut(which) else { continue };
+                let before = slots.owners.clone();
+                match slots.transfer_range(t.start, t.count, &wallets[from], wallets[to]) {
+                    Ok(()) => {
+                        prop_assert_eq!(t.owner, wallets[from]);
+                        t.owner = wallets[to];
+                    }
+                    Err(_) => {
+                        prop_assert_ne!(t.owner, wallets[from]);
+                        prop_assert_eq!(&slots.owners, &before);
+                    }
+                }
+            }
+
+            prop_assert_eq!(&slots.bitmap, &bitmap);
+            for t in &tickets {
+                for n in t.start..t.start + t.count {
+                    prop_assert_eq!(slots.owner_of(n), Some(t.owner));
+                }
+            }
+        }
+
+        /// Every allowlisted `(wallet, max_allocation)` proves its own leaf and nothing else.
+        #[test]
+        fn allowlist_proofs_bind_wallet_and_allocation(allocations in vec(1u64..100, 1..40)) {
+            let entries: Vec<(Pubkey, u64)> = allocations.iter().map(|max| (Pubkey::new_unique(), *max)).collect();
+            let layers = allowlist_layers(&entries);
+            let root = layers.last().unwrap()[0];
+            for (index, (wallet, max)) in entries.iter().enumerate() {
+                let proof = allowlist_proof(&layers, index);
+                prop_assert!(verify_allowlist_proof(&root, allowlist_leaf(wallet, *max), &proof));
+                prop_assert!(!verify_allowlist_proof(&root, allowlist_leaf(wallet, max + 1), &proof));
+                prop_assert!(!verify_allowlist_proof(&root, allowlist_leaf(&Pubkey::new_unique(), *max), &proof));
+            }
+        }
+
+        /// A slot run maps onto exactly the ticket numbers it reserves.
+        #[test]
+        fn slot_runs_map_to_ticket_ranges(first in 0u32..1_000, len in 1u32..50) {
+            let run: Vec<u32> = (first..first + len).collect();
+            let (start, count) = slot_run_to_range(&run).unwrap();
+            let joined = ticket(start, count, Pubkey::new_unique());
+            for &slot in &run {
+                prop_assert!(joined.covers(slot as u64 + 1));
+            }
+            prop_assert!(!joined.covers(first as u64));
+            prop_assert!(!joined.covers((first + len) as u64 + 1));
+
+            let mut slots = empty_slots(first + len);
+            slots.reserve_range(start, count, joined.owner).unwrap();
+            prop_assert_eq!(slots.taken_count(), len as u64);
+            prop_assert!(run.iter().all(|&slot| slots.is_taken(slot)));
+        }
+
+        /// Slot joins at random runs interleaved with deposits at `first_free_run`: a deposit
+        /// only fails when no free run of its size is left, `next_ticket_index` stays the
+        /// lowest free ticket, and single-ticket deposits can always sell the raffle out.
+        #[test]
+        fn joins_and_deposits_share_the_bitmap(
+            required in 1u32..200,
+            entries in vec((any::<bool>(), 0u32..210, 1u64..12), 0..60),
+        ) {
+            let mut slots = empty_slots(required);
+            let mut next_ticket_index = 1u64;
+            let mut tickets = Vec::new();
+            for (join, first, len) in entries {
+                if join {
+                    let run: Vec<u32> = (first..first + len as u32).collect();
+                    let (start, count) = slot_run_to_range(&run).unwrap();
+                    let owner = Pubkey::new_unique();
+                    if slots.reserve_range(start, count, owner).is_ok() {
+                        tickets.push(ticket(start, count, owner));
+                        next_ticket_index = slots.next_free(next_ticket_index);
+                    }
+                } else if let Some(start) = slots.first_free_run(len) {
+                    prop_assert!((1..start).all(|s| !slots.is_range_free(s, len)));
+                    deposit_at(&mut slots, &mut next_ticket_index, &mut tickets, start, len).unwrap();
+                } else {
+                    prop_assert!((1..=required as u64).all(|s| !slots.is_range_free(s, len)));
+                }
+                prop_assert_eq!(next_ticket_index, slots.next_free(1));
+            }
+
+            while let Some(start) = slots.first_free_run(1) {
+                prop_assert_eq!(start, next_ticket_index);
+                deposit_at(&mut slots, &mut next_ticket_index, &mut tickets, start, 1).unwrap();
+            }
+            prop_assert_eq!(slots.taken_count(), required as u64);
+            prop_assert_eq!(next_ticket_index, required as u64 + 1);
+            for winner in 1..=required as u64 {
+                let holders: Vec<&Ticket> = tickets.iter().filter(|t| t.covers(winner)).collect();
+                prop_assert_eq!(holders.len(), 1);
+                prop_assert_eq!(slots.owner_of(winner), Some(holders[0].owner));
+            }
+        }
+
+        /// Scattered, unordered or duplicated picks never map to a ticket range.
+        #[test]
+        fn non_runs_are_rejected(picks in vec(0u32..64, 2..10)) {
+            prop_assume!(picks.windows(2).any(|w| w[0].checked_add(1) != Some(w[1])));
+            prop_assert!(slot_run_to_range(&picks).is_err());
+        }
+    }
+}
@@ -0,0 +1,1272 @@
+//! Shared LiteSVM fixtures for the rwa_raffle e2e suite.
+//!
+//! The program is loaded from its SBF build, so build it first:
+//! `cargo build-sbf --manifest-path programs/rwa_raffle/Cargo.toml` (or `anchor build`),
+//! then `cargo test -p rwa_raffle`.
+//!
+//! Mock USDC and NFT mints and their token accounts are written straight into the
+//! SVM (no mint authority round-trips), so every test starts from a known state.
+//!
+//! Only the default build is exercised, and no Metaplex program is loaded. Paths behind the
+//! `metaplex` / `bubblegum` features are covered here only as far as the default build
+//! refuses them; beyond that:
+//! - Programmable NFT prizes (`TransferV1` in `claim_prize`): telling them apart
+//!   (`programmable_from_metadata`) and assembling the call (`TransferV1Call`) are
+//!   unit-tested in `lib.rs`; the CPI itself needs the `metaplex` build and the Token
+//!   Metadata and Token Auth Rules programs. Without `metaplex` every prize is moved with
+//!   `transfer_checked`, which a pNFT's frozen escrow refuses.
+//! - Prize collection checks in `set_prize_nft`: the rules (verified collection, 1/1 master
+//!   edition) are unit-tested on `check_prize_nft` in `lib.rs`; reading them from Token
+//!   Metadata-owned accounts needs the `metaplex` build. Without it the prize is only checked
+//!   for `decimals == 0` (`set_prize_requires_nft_mint`).
+
+#![allow(dead_code)]
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::program_option::COption;
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_lang::system_program;
+use anchor_lang::{AccountDeserialize, AccountSerialize, AnchorSerialize, InstructionData, ToAccountMetas};
+use anchor_spl::associated_token::{get_associated_token_address, get_associated_token_address_with_program_id};
+use anchor_spl::token::spl_token;
+use anchor_spl::associated_token::spl_associated_token_account::instruction::create_associated_token_account;
+use anchor_spl::token_2022::spl_token_2022;
+use anchor_spl::token_2022::spl_token_2022::extension::StateWithExtensions;
+use litesvm::types::TransactionResult;
+use litesvm::LiteSVM;
+use rwa_raffle::{
+    accounts, allowlist_leaf, allowlist_node, instruction, mrft_tree_authority, Allocation, BatchRaffleConfig, PlatformConfig, Raffle,
+    RaffleError, RaffleSlots, RaffleV0, RaffleV1, Ticket, TicketV0, ALLOCATION_SEED, MRFT_COLLECTION_MINT, PLATFORM_CONFIG_SEED,
+    RAFFLE_SEED, REFUND_VAULT_SEED, SLOTS_SEED, SPL_ACCOUNT_COMPRESSION_ID, SPL_NOOP_ID, TICKET_MINT_SEED, TICKET_SEED,
+};
+use solana_sdk::account::Account;
+use solana_sdk::clock::Clock;
+use solana_sdk::instruction::{AccountMeta, Instruction, InstructionError};
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::sysvar::instructions as instructions_sysvar;
+use solana_sdk::transaction::{Transaction, TransactionError};
+
+pub const PROGRAM_SO: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../../target/deploy/rwa_raffle.so");
+
+pub const USDC_DECIMALS: u8 = 6;
+/// One whole USDC (= one ticket).
+pub const USDC: u64 = 1_000_000;
+/// Clock at the start of every test.
+pub const START_TS: i64 = 1_750_000_000;
+/// Default sale window.
+pub const DAY: i64 = 86_400;
+pub const SOL: u64 = 1_000_000_000;
+
+/// Bubblegum and Token Metadata: not loaded into the SVM (see `claim_refund_minting`,
+/// `claim_prize_cnft`).
+pub const BUBBLEGUM_ID: Pubkey = solana_sdk::pubkey!("BGUMAp9Gq7iTEuizy4pqaxsTyUCBK68MDfK752saRPUY");
+pub const TOKEN_METADATA_ID: Pubkey = solana_sdk::pubkey!("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
+
+pub struct Fixture {
+    pub svm: LiteSVM,
+    pub organizer: Keypair,
+    /// `PlatformConfig.authority` (see `Fixture::new`).
+    pub platform: Keypair,
+    /// Mock USDC (6 decimals), the escrow mint of every raffle unless a test makes another.
+    pub usdc: Pubkey,
+}
+
+/// Addresses of a raffle created by `Fixture::create_raffle`.
+pub struct TestRaffle {
+    pub key: Pubkey,
+    pub mint: Pubkey,
+    pub organizer: Pubkey,
+    pub escrow: Pubkey,
+    pub slots: Pubkey,
+}
+
+/// `initialize_raffle` arguments; `Default` is a 10 ticket raffle ending in one day.
+#[derive(Clone)]
+pub struct RaffleParams {
+    pub required_tickets: u64,
+    pub deadline: i64,
+    pub auto_draw: bool,
+    pub ticket_mode: u8,
+    pub reveal_time: Option<i64>,
+    pub crank_reserve: u64,
+}
+
+impl Default for RaffleParams {
+    fn default() -> Self {
+        Self {
+            required_tickets: 10,
+            deadline: START_TS + DAY,
+            auto_draw: false,
+            ticket_mode: 0,
+            reveal_time: None,
+            crank_reserve: 0,
+        }
+    }
+}
+
+impl Fixture {
+    pub fn new() -> Self {
+        let mut svm = LiteSVM::new();
+        svm.add_program_from_file(rwa_raffle::ID, PROGRAM_SO)
+            .unwrap_or_else(|e| panic!("loading {PROGRAM_SO} ({e}); run `cargo build-sbf` for rwa_raffle first"));
+
+        let organizer = Keypair::new();
+        svm.airdrop(&organizer.pubkey(), 10 * SOL).unwrap();
+
+        let platform = Keypair::new();
+        svm.airdrop(&platform.pubkey(), SOL).unwrap();
+
+        let mut fixture = Self { svm, organizer, platform, usdc: Pubkey::default() };
+        fixture.warp_to(START_TS);
+        fixture.usdc = fixture.create_mint(USDC_DECIMALS);
+        fixture.set_platform_config();
+        fixture
+    }
+
+    /// LiteSVM loads the program without a ProgramData account, so there is no upgrade
+    /// authority for `initialize_platform_config`; write the config it would create.
+    fn set_platform_config(&mut self) {
+        let (address, bump) = Pubkey::find_program_address(&[PLATFORM_CONFIG_SEED], &rwa_raffle::ID);
+        let mut data = Vec::new();
+        PlatformConfig { authority: self.platform.pubkey(), bump }.try_serialize(&mut data).unwrap();
+        let lamports = self.svm.minimum_balance_for_rent_exemption(data.len());
+        self.svm
+            .set_account(address, Account { lamports, data, owner: rwa_raffle::ID, executable: false, rent_epoch: 0 })
+            .unwrap();
+    }
+
+    // ---- clock ----
+
+    pub fn now(&self) -> i64 {
+        self.svm.get_sysvar::<Clock>().unix_timestamp
+    }
+
+    pub fn warp_to(&mut self, unix_timestamp: i64) {
+        let mut clock = self.svm.get_sysvar::<Clock>();
+        clock.unix_timestamp = unix_timestamp;
+        self.svm.set_sysvar::<Clock>(&clock);
+    }
+
+    // ---- mock mints and token accounts ----
+
+    /// Write an initialized SPL mint. The authority is a throwaway key: balances are set
+    /// directly with `set_token_account`.
+    pub fn create_mint(&mut self, decimals: u8) -> Pubkey {
+        let mint = Pubkey::new_unique();
+        let state = spl_token::state::Mint {
+            mint_authority: COption::Some(Pubkey::new_unique()),
+            supply: u64::MAX / 2,
+            decimals,
+            is_initialized: true,
+            freeze_authority: COption::None,
+        };
+        let mut data = vec![0u8; spl_token::state::Mint::LEN];
+        state.pack_into_slice(&mut data);
+        self.set_token_program_account(&mint, data);
+        mint
+    }
+
+    /// Mock NFT: a 0-decimals mint with its single token in `owner`'s ATA.
+    pub fn create_nft(&mut self, owner: &Pubkey) -> (Pubkey, Pubkey) {
+        let mint = self.create_mint(0);
+        let ata = self.set_ata(owner, &mint, 1);
+        (mint, ata)
+    }
+
+    pub fn set_token_account(&mut self, address: &Pubkey, mint: &Pubkey, owner: &Pubkey, amount: u64) {
+        let state = spl_token::state::Account {
+            mint: *mint,
+            owner: *owner,
+            amount,
+            delegate: COption::None,
+            state: spl_token::state::AccountState::Initialized,
+            is_native: COption::None,
+            delegated_amount: 0,
+            close_authority: COption::None,
+        };
+        let mut data = vec![0u8; spl_token::state::Account::LEN];
+        state.pack_into_slice(&mut data);
+        self.set_token_program_account(address, data);
+    }
+
+    /// Write `owner`'s associated token account for `mint` holding `amount`.
+    pub fn set_ata(&mut self, owner: &Pubkey, mint: &Pubkey, amount: u64) -> Pubkey {
+        let ata = get_associated_token_address(owner, mint);
+        self.set_token_account(&ata, mint, owner, amount);
+        ata
+    }
+
+    fn set_token_program_account(&mut self, address: &Pubkey, data: Vec<u8>) {
+        let lamports = self.svm.minimum_balance_for_rent_exemption(data.len());
+        self.svm
+            .set_account(*address, Account { lamports, data, owner: spl_token::ID, executable: false, rent_epoch: 0 })
+            .unwrap();
+    }
+
+    pub fn token_balance(&self, address: &Pubkey) -> u64 {
+        let account = self.svm.get_account(address).expect("token account missing");
+        spl_token::state::Account::unpack(&account.data).unwrap().amount
+    }
+
+    /// Balance of a Token-2022 account (ticket NFTs carry extensions, so `token_balance` can't read them).
+    pub fn nft_balance(&self, address: &Pubkey) -> u64 {
+        let account = self.svm.get_account(address).expect("token account missing");
+        StateWithExtensions::<spl_token_2022::state::Account>::unpack(&account.data).unwrap().base.amount
+    }
+
+    /// Funded wallet holding `usdc` whole USDC in its ATA.
+    pub fn user(&mut self, usdc: u64) -> Keypair {
+        let user = Keypair::new();
+        self.svm.airdrop(&user.pubkey(), SOL).unwrap();
+        let mint = self.usdc;
+        self.set_ata(&user.pubkey(), &mint, usdc * USDC);
+        user
+    }
+
+    pub fn usdc_ata(&self, owner: &Pubkey) -> Pubkey {
+        get_associated_token_address(owner, &self.usdc)
+    }
+
+    // ---- accounts ----
+
+    pub fn lamports(&self, address: &Pubkey) -> u64 {
+        self.svm.get_balance(address).unwrap_or(0)
+    }
+
+    pub fn exists(&self, address: &Pubkey) -> bool {
+        self.svm.get_account(address).is_some_and(|a| a.lamports > 0)
+    }
+
+    pub fn raffle(&self, raffle: &TestRaffle) -> Raffle {
+        let account = self.svm.get_account(&raffle.key).expect("raffle missing");
+        Raffle::try_deserialize(&mut account.data.as_slice()).unwrap()
+    }
+
+    /// Rewrite raffle state no test instruction reaches (e.g. `refund_mode`, which only
+    /// the permit path sets).
+    pub fn patch_raffle(&mut self, raffle: &TestRaffle, patch: impl FnOnce(&mut Raffle)) {
+        let mut account = self.svm.get_account(&raffle.key).expect("raffle missing");
+        let mut state = Raffle::try_deserialize(&mut account.data.as_slice()).unwrap();
+        patch(&mut state);
+        state.try_serialize(&mut &mut account.data[..]).unwrap();
+        self.svm.set_account(raffle.key, account).unwrap();
+    }
+
+    /// Rewrite the raffle in the unversioned `RaffleV1` layout, as `status`.
+    pub fn make_legacy_raffle(&mut self, raffle: &TestRaffle, status: u8) {
+        let state = self.raffle(raffle);
+        let legacy = RaffleV1 {
+            legacy: RaffleV0 {
+                organizer: state.organizer,
+                mint: state.mint,
+                escrow: state.escrow,
+                required_tickets: state.required_tickets,
+                tickets_sold: state.tickets_sold,
+                next_ticket_index: state.next_ticket_index,
+                deadline: state.deadline,
+                status,
+                winner_ticket: state.winner_ticket,
+                bump: state.bump,
+                prize_mint: state.prize_mint,
+                prize_escrow: state.prize_escrow,
+                prize_set: state.prize_set,
+                prize_claimed: state.prize_claimed,
+                proceeds_collected: state.proceeds_collected,
+                auto_draw: state.auto_draw,
+                ticket_mode: state.ticket_mode,
+            },
+            prize_collection_mint: state.prize_collection_mint,
+            refund_mode: state.refund_mode,
+        };
+        let mut account = self.svm.get_account(&raffle.key).expect("raffle missing");
+        account.data.truncate(8);
+        legacy.serialize(&mut account.data).unwrap();
+        self.svm.set_account(raffle.key, account).unwrap();
+    }
+
+    /// Rewrite the ticket in the layout from before `payer` existed; legacy refunds only
+    /// set `refunded`.
+    pub fn make_legacy_ticket(&mut self, ticket: &Pubkey, refunded: bool) {
+        let state = self.ticket(ticket);
+        let legacy = TicketV0 {
+            raffle: state.raffle,
+            owner: state.owner,
+            start: state.start,
+            count: state.count,
+            refunded,
+            claimed_win: state.claimed_win,
+            bump: state.bump,
+        };
+        let mut account = self.svm.get_account(ticket).expect("ticket missing");
+        account.data.truncate(8);
+        legacy.serialize(&mut account.data).unwrap();
+        self.svm.set_account(*ticket, account).unwrap();
+    }
+
+    /// Move the ticket to the PDA slot joins (`join_with_moga` / `join_with_ticket`) create,
+    /// seeded by its first 0-based slot as a u32; those joins need feature builds.
+    pub fn make_slot_ticket(&mut self, ticket: &Pubkey) -> Pubkey {
+        let mut state = self.ticket(ticket);
+        let first_slot = (state.start - 1) as u32;
+        let (address, bump) = Pubkey::find_program_address(
+            &[TICKET_SEED, state.raffle.as_ref(), state.owner.as_ref(), &first_slot.to_le_bytes()],
+            &rwa_raffle::ID,
+        );
+        state.bump = bump;
+        let mut account = self.svm.get_account(ticket).expect("ticket missing");
+        account.data.clear();
+        state.try_serialize(&mut account.data).unwrap();
+        self.svm.set_account(address, account).unwrap();
+        self.svm.set_account(*ticket, Account::default()).unwrap();
+        address
+    }
+
+    pub fn ticket(&self, ticket: &Pubkey) -> Ticket {
+        let account = self.svm.get_account(ticket).expect("ticket missing");
+        Ticket::try_deserialize(&mut account.data.as_slice()).unwrap()
+    }
+
+    pub fn slots(&self, raffle: &TestRaffle) -> RaffleSlots {
+        let account = self.svm.get_account(&raffle.slots).expect("slots missing");
+        RaffleSlots::try_deserialize(&mut account.data.as_slice()).unwrap()
+    }
+
+    pub fn allocation(&self, allocation: &Pubkey) -> Allocation {
+        let account = self.svm.get_account(allocation).expect("allocation missing");
+        Allocation::try_deserialize(&mut account.data.as_slice()).unwrap()
+    }
+
+    // ---- transactions ----
+
+    /// Send `ixs`; `signers[0]` pays the fee. The blockhash is expired afterwards so an
+    /// identical retry is a new transaction rather than `AlreadyProcessed`.
+    #[allow(clippy::result_large_err)] // litesvm's own `TransactionResult`
+    pub fn send(&mut self, ixs: &[Instruction], signers: &[&Keypair]) -> TransactionResult {
+        let tx = Transaction::new_signed_with_payer(ixs, Some(&signers[0].pubkey()), signers, self.svm.latest_blockhash());
+        let result = self.svm.send_transaction(tx);
+        self.svm.expire_blockhash();
+        result
+    }
+
+    /// `send` that must succeed; prints the program logs otherwise.
+    #[track_caller]
+    pub fn ok(&mut self, ixs: &[Instruction], signers: &[&Keypair]) {
+        if let Err(failed) = self.send(ixs, signers) {
+            panic!("transaction failed: {:?}\n{}", failed.err, failed.meta.logs.join("\n"));
+        }
+    }
+
+    /// `audit_raffle` must pass: the escrow holds exactly what the counters account for.
+    #[track_caller]
+    pub fn audit(&mut self, raffle: &TestRaffle) {
+        let organizer = self.organizer.insecure_clone();
+        self.ok(&[audit_raffle(raffle)], &[&organizer]);
+    }
+
+    // ---- raffle setup ----
+
+    pub fn raffle_addresses(&self, mint: &Pubkey, organizer: &Pubkey) -> TestRaffle {
+        let key = raffle_pda(mint, organizer);
+        TestRaffle {
+            key,
+            mint: *mint,
+            organizer: *organizer,
+            escrow: get_associated_token_address(&key, mint),
+            slots: slots_pda(&key),
+        }
+    }
+
+    /// Create a USDC raffle for the fixture organizer (escrow ATA included).
+    pub fn create_raffle(&mut self, params: RaffleParams) -> TestRaffle {
+        let mint = self.usdc;
+        self.create_raffle_for_mint(&mint, params)
+    }
+
+    /// Organizer can only run one raffle per escrow mint; tests needing several use
+    /// fresh 6-decimals mints.
+    pub fn create_raffle_for_mint(&mut self, mint: &Pubkey, params: RaffleParams) -> TestRaffle {
+        let organizer = self.organizer.insecure_clone();
+        let raffle = self.raffle_addresses(mint, &organizer.pubkey());
+        self.set_token_account(&raffle.escrow, mint, &raffle.key, 0);
+        self.ok(&[initialize_raffle(&raffle, &params)], &[&organizer]);
+        raffle
+    }
+
+    /// `payer` buys `tickets` whole tickets at the raffle's current `next_ticket_index`.
+    /// Returns the Ticket PDA.
+    pub fn buy(&mut self, raffle: &TestRaffle, payer: &Keypair, tickets: u64) -> Pubkey {
+        let start = self.raffle(raffle).next_ticket_index;
+        let ix = deposit(raffle, &payer.pubkey(), tickets * USDC, start);
+        self.ok(&[ix], &[payer]);
+        ticket_pda(&raffle.key, &payer.pubkey(), start)
+    }
+
+    /// `payer` buys `tickets` for `beneficiary`. Returns the beneficiary's ticket PDA.
+    pub fn gift(&mut self, raffle: &TestRaffle, payer: &Keypair, beneficiary: &Pubkey, tickets: u64) -> Pubkey {
+        let start = self.raffle(raffle).next_ticket_index;
+        let ix = gift_deposit(raffle, &payer.pubkey(), beneficiary, tickets * USDC, start);
+        self.ok(&[ix], &[payer]);
+        ticket_pda(&raffle.key, beneficiary, start)
+    }
+
+    /// Tokenize `owner`'s ticket. Returns `(ticket_mint, new_ticket, owner_nft_account)`.
+    pub fn tokenize(&mut self, raffle: &TestRaffle, owner: &Keypair, ticket: &Pubkey) -> (Pubkey, Pubkey, Pubkey) {
+        let state = self.ticket(ticket);
+        self.ok(&[tokenize_ticket(raffle, &owner.pubkey(), ticket, state.start, &state.payer)], &[owner]);
+        ticket_nft_keys(raffle, &owner.pubkey(), state.start)
+    }
+
+    /// Sell a ticket NFT on the "secondary market": move it to `to`'s Token-2022 ATA.
+    pub fn send_nft(&mut self, from: &Keypair, ticket_mint: &Pubkey, to: &Pubkey) -> Pubkey {
+        let source = nft_account(&from.pubkey(), ticket_mint);
+        let destination = nft_account(to, ticket_mint);
+        let ixs = [
+            create_associated_token_account(&from.pubkey(), to, ticket_mint, &spl_token_2022::ID),
+            spl_token_2022::instruction::transfer_checked(
+                &spl_token_2022::ID,
+                &source,
+                ticket_mint,
+                &destination,
+                &from.pubkey(),
+                &[],
+                1,
+                0,
+            )
+            .unwrap(),
+        ];
+        self.ok(&ixs, &[from]);
+        destination
+    }
+
+    /// Start an allowlist presale for `entries` that ends at `presale_end`.
+    pub fn open_presale(&mut self, raffle: &TestRaffle, entries: Vec<(Pubkey, u64)>, presale_end: i64) -> Allowlist {
+        let organizer = self.organizer.insecure_clone();
+        let allowlist = Allowlist::new(entries);
+        self.ok(&[set_allowlist(raffle, &organizer.pubkey(), allowlist.root(), presale_end)], &[&organizer]);
+        allowlist
+    }
+
+    /// `wallet` registers its allowlist entry. Returns the Allocation PDA.
+    pub fn register(&mut self, raffle: &TestRaffle, wallet: &Keypair, allowlist: &Allowlist) -> Pubkey {
+        let (max_allocation, proof) = allowlist.proof(&wallet.pubkey());
+        self.ok(&[register_allowlist(raffle, &wallet.pubkey(), max_allocation, proof)], &[wallet]);
+        allocation_pda(&raffle.key, &wallet.pubkey())
+    }
+
+    /// `buy` during the presale, charged to the payer's allocation.
+    pub fn presale_buy(&mut self, raffle: &TestRaffle, payer: &Keypair, tickets: u64) -> Pubkey {
+        let start = self.raffle(raffle).next_ticket_index;
+        let ix = presale_deposit(raffle, &payer.pubkey(), tickets * USDC, start);
+        self.ok(&[ix], &[payer]);
+        ticket_pda(&raffle.key, &payer.pubkey(), start)
+    }
+
+    /// Escrow a fresh mock NFT as the prize. Returns `(prize_mint, prize_escrow)`.
+    pub fn set_prize(&mut self, raffle: &TestRaffle) -> (Pubkey, Pubkey) {
+        let organizer = self.organizer.insecure_clone();
+        let (prize_mint, organizer_ata) = self.create_nft(&organizer.pubkey());
+        let prize_escrow = self.set_ata(&raffle.key, &prize_mint, 0);
+        self.ok(&[set_prize_nft(raffle, &organizer.pubkey(), &prize_mint, &organizer_ata, &prize_escrow)], &[&organizer]);
+        (prize_mint, prize_escrow)
+    }
+}
+
+// ---- error assertions ----
+
+pub fn error_code(result: &TransactionResult) -> Option<u32> {
+    match result {
+        Err(failed) => match failed.err {
+            TransactionError::InstructionError(_, InstructionError::Custom(code)) => Some(code),
+            _ => None,
+        },
+        Ok(_) => None,
+    }
+}
+
+#[track_caller]
+pub fn assert_raffle_err(result: TransactionResult, expected: RaffleError) {
+    let expected_name = format!("{expected:?}");
+    assert_custom_err(result, u32::from(expected), &expected_name);
+}
+
+#[track_caller]
+pub fn assert_anchor_err(result: TransactionResult, expected: anchor_lang::error::ErrorCode) {
+    let expected_name = format!("{expected:?}");
+    assert_custom_err(result, u32::from(expected), &expected_name);
+}
+
+#[track_caller]
+fn assert_custom_err(result: TransactionResult, code: u32, name: &str) {
+    match &result {
+        Ok(_) => panic!("expected {name} ({code}), transaction succeeded"),
+        Err(failed) => assert_eq!(
+            error_code(&result),
+            Some(code),
+            "expected {name} ({code}), got {:?}\n{}",
+            failed.err,
+            failed.meta.logs.join("\n"),
+        ),
+    }
+}
+
+// ---- allowlist ----
+
+/// Presale allowlist tree, hashed like the program (odd nodes are carried up).
+pub struct Allowlist {
+    entries: Vec<(Pubkey, u64)>,
+    layers: Vec<Vec<[u8; 32]>>,
+}
+
+impl Allowlist {
+    pub fn new(entries: Vec<(Pubkey, u64)>) -> Self {
+        let mut layers = vec![entries.iter().map(|(wallet, max)| allowlist_leaf(wallet, *max)).collect::<Vec<_>>()];
+        while layers[layers.len() - 1].len() > 1 {
+            let next = layers[layers.len() - 1]
+                .chunks(2)
+                .map(|pair| if pair.len() == 2 { allowlist_node(&pair[0], &pair[1]) } else { pair[0] })
+                .collect();
+            layers.push(next);
+        }
+        Self { entries, layers }
+    }
+
+    pub fn root(&self) -> [u8; 32] {
+        self.layers[self.layers.len() - 1][0]
+    }
+
+    /// `(max_allocation, proof)` of a listed wallet.
+    pub fn proof(&self, wallet: &Pubkey) -> (u64, Vec<[u8; 32]>) {
+        let mut index = self.entries.iter().position(|(w, _)| w == wallet).expect("wallet not allowlisted");
+        let max_allocation = self.entries[index].1;
+        let mut proof = Vec::new();
+        for layer in &self.layers[..self.layers.len() - 1] {
+            if let Some(sibling) = layer.get(index ^ 1) {
+                proof.push(*sibling);
+            }
+            index /= 2;
+        }
+        (max_allocation, proof)
+    }
+}
+
+// ---- PDAs ----
+
+pub fn raffle_pda(mint: &Pubkey, organizer: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[RAFFLE_SEED, mint.as_ref(), organizer.as_ref()], &rwa_raffle::ID).0
+}
+
+pub fn slots_pda(raffle: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[SLOTS_SEED, raffle.as_ref()], &rwa_raffle::ID).0
+}
+
+pub fn ticket_pda(raffle: &Pubkey, owner: &Pubkey, start: u64) -> Pubkey {
+    Pubkey::find_program_address(&[TICKET_SEED, raffle.as_ref(), owner.as_ref(), &start.to_le_bytes()], &rwa_raffle::ID).0
+}
+
+pub fn allocation_pda(raffle: &Pubkey, wallet: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[ALLOCATION_SEED, raffle.as_ref(), wallet.as_ref()], &rwa_raffle::ID).0
+}
+
+pub fn platform_config_pda() -> Pubkey {
+    Pubkey::find_program_address(&[PLATFORM_CONFIG_SEED], &rwa_raffle::ID).0
+}
+
+pub fn refund_vault_authority() -> Pubkey {
+    Pubkey::find_program_address(&[REFUND_VAULT_SEED], &rwa_raffle::ID).0
+}
+
+pub fn event_authority() -> Pubkey {
+    Pubkey::find_program_address(&[b"__event_authority"], &rwa_raffle::ID).0
+}
+
+// ---- instruction builders ----
+
+fn ix(accounts: impl ToAccountMetas, data: impl InstructionData) -> Instruction {
+    Instruction { program_id: rwa_raffle::ID, accounts: accounts.to_account_metas(None), data: data.data() }
+}
+
+pub fn initialize_raffle(raffle: &TestRaffle, p: &RaffleParams) -> Instruction {
+    ix(
+        accounts::InitializeRaffle {
+            organizer: raffle.organizer,
+            mint: raffle.mint,
+            escrow_ata: raffle.escrow,
+            raffle: raffle.key,
+            slots: raffle.slots,
+            system_program: system_program::ID,
+            token_program: spl_token::ID,
+            event_authority: event_authority(),
+            program: rwa_raffle::ID,
+        },
+        instruction::InitializeRaffle {
+            required_tickets: p.required_tickets,
+            deadline_unix_ts: p.deadline,
+            auto_draw: p.auto_draw,
+            ticket_mode: p.ticket_mode,
+            reveal_time_unix_ts: p.reveal_time,
+            crank_reserve_lamports: p.crank_reserve,
+        },
+    )
+}
+
+pub fn initialize_raffle_with_permit(
+    raffle: &TestRaffle,
+    p: &RaffleParams,
+    permit_nonce: [u8; 16],
+    permit_expiry_unix_ts: i64,
+) -> Instruction {
+    ix(
+        accounts::InitializeRaffleWithPermit {
+            organizer: raffle.organizer,
+            mint: raffle.mint,
+            escrow_ata: raffle.escrow,
+            raffle: raffle.key,
+            slots: raffle.slots,
+            instructions_sysvar: instructions_sysvar::ID,
+            system_program: system_program::ID,
+            token_program: spl_token::ID,
+            event_authority: event_authority(),
+            program: rwa_raffle::ID,
+        },
+        instruction::InitializeRaffleWithPermit {
+            required_tickets: p.required_tickets,
+            deadline_unix_ts: p.deadline,
+            permit_nonce,
+            permit_expiry_unix_ts,
+            auto_draw: p.auto_draw,
+            ticket_mode: p.ticket_mode,
+            prize_collection_mint: Pubkey::default(),
+            refund_mode: 0,
+            reveal_time_unix_ts: p.reveal_time,
+            crank_reserve_lamports: p.crank_reserve,
+            prize_metadata: Default::default(),
+            mrft_collection: Pubkey::default(),
+        },
+    )
+}
+
+pub fn batch_create_raffles(
+    organizer: &Pubkey,
+    configs: Vec<BatchRaffleConfig>,
+    permit_data: Vec<([u8; 16], i64)>,
+) -> Instruction {
+    ix(
+        accounts::BatchCreateRaffles {
+            organizer: *organizer,
+            instructions_sysvar: instructions_sysvar::ID,
+            system_program: system_program::ID,
+        },
+        instruction::BatchCreateRaffles { configs, permit_data },
+    )
+}
+
+pub fn deposit(raffle: &TestRaffle, payer: &Pubkey, amount: u64, start_index: u64) -> Instruction {
+    deposit_with(raffle, payer, payer, amount, start_index, None, None)
+}
+
+pub fn gift_deposit(raffle: &TestRaffle, payer: &Pubkey, beneficiary: &Pubkey, amount: u64, start_index: u64) -> Instruction {
+    deposit_with(raffle, payer, beneficiary, amount, start_index, None, None)
+}
+
+pub fn presale_deposit(raffle: &TestRaffle, payer: &Pubkey, amount: u64, start_index: u64) -> Instruction {
+    deposit_with(raffle, payer, payer, amount, start_index, Some(allocation_pda(&raffle.key, payer)), None)
+}
+
+pub fn gated_deposit(raffle: &TestRaffle, payer: &Pubkey, amount: u64, start_index: u64, gate_token_account: &Pubkey) -> Instruction {
+    deposit_with(raffle, payer, payer, amount, start_index, None, Some(*gate_token_account))
+}
+
+pub fn deposit_with(
+    raffle: &TestRaffle,
+    payer: &Pubkey,
+    beneficiary: &Pubkey,
+    amount: u64,
+    start_index: u64,
+    allocation: Option<Pubkey>,
+    gate_token_account: Option<Pubkey>,
+) -> Instruction {
+    ix(
+        accounts::Deposit {
+            payer: *payer,
+            raffle: raffle.key,
+            mint: raffle.mint,
+            payer_ata: get_associated_token_address(payer, &raffle.mint),
+            escrow_ata: raffle.escrow,
+            slots: raffle.slots,
+            allocation,
+            gate_token_account,
+            gate_metadata: None,
+            ticket: ticket_pda(&raffle.key, beneficiary, start_index),
+            system_program: system_program::ID,
+            token_program: spl_token::ID,
+            event_authority: event_authority(),
+            program: rwa_raffle::ID,
+        },
+        instruction::Deposit { amount, start_index, beneficiary: *beneficiary },
+    )
+}
+
+pub fn set_allowlist(raffle: &TestRaffle, organizer: &Pubkey, allowlist_root: [u8; 32], presale_end_unix_ts: i64) -> Instruction {
+    ix(
+        accounts::SetAllowlist {
+            organizer: *organizer,
+            raffle: raffle.key,
+            event_authority: event_authority(),
+            program: rwa_raffle::ID,
+        },
+        instruction::SetAllowlist { allowlist_root, presale_end_unix_ts },
+    )
+}
+
+pub fn set_gate(raffle: &TestRaffle, organizer: &Pubkey, gate_mode: u8, gate_mint: &Pubkey, gate_min_amount: u64) -> Instruction {
+    ix(
+        accounts::SetGate {
+            organizer: *organizer,
+            raffle: raffle.key,
+            event_authority: event_authority(),
+            program: rwa_raffle::ID,
+        },
+        instruction::SetGate { gate_mode, gate_mint: *gate_mint, gate_min_amount },
+    )
+}
+
+pub fn set_prize_backed(raffle: &TestRaffle, organizer: &Pubkey, prize_backed: bool) -> Instruction {
+    ix(
+        accounts::SetPrizeBacked {
+            organizer: *organizer,
+            raffle: raffle.key,
+            collection_metadata: None,
+            event_authority: event_authority(),
+            program: rwa_raffle::ID,
+        },
+        instruction::SetPrizeBacked { prize_backed },
+    )
+}
+
+pub fn set_claim_window(raffle: &TestRaffle, organizer: &Pubkey, claim_window_secs: i64) -> Instruction {
+    ix(
+        accounts::SetClaimWindow {
+            organizer: *organizer,
+            raffle: raffle.key,
+            event_authority: event_authority(),
+            program: rwa_raffle::ID,
+        },
+        instruction::SetClaimWindow { claim_window_secs },
+    )
+}
+
+pub fn register_allowlist(raffle: &TestRaffle, payer: &Pubkey, max_allocation: u64, proof: Vec<[u8; 32]>) -> Instruction {
+    ix(
+        accounts::RegisterAllowlist {
+            payer: *payer,
+            raffle: raffle.key,
+            allocation: allocation_pda(&raffle.key, payer),
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: rwa_raffle::ID,
+        },
+        instruction::RegisterAllowlist { max_allocation, proof },
+    )
+}
+
+pub fn close_allocation(raffle: &TestRaffle, caller: &Pubkey, wallet: &Pubkey) -> Instruction {
+    ix(
+        accounts::CloseAllocation {
+            caller: *caller,
+            raffle: raffle.key,
+            allocation: allocation_pda(&raffle.key, wallet),
+            wallet: *wallet,
+        },
+        instruction::CloseAllocation {},
+    )
+}
+
+pub fn request_draw(raffle: &TestRaffle, caller: &Pubkey) -> Instruction {
+    ix(
+        accounts::RequestDraw { caller: *caller, raffle: raffle.key, event_authority: event_authority(), program: rwa_raffle::ID },
+        instruction::RequestDraw {},
+    )
+}
+
+pub fn start_refunding(raffle: &TestRaffle, caller: &Pubkey) -> Instruction {
+    ix(
+        accounts::StartRefunding { caller: *caller, raffle: raffle.key, event_authority: event_authority(), program: rwa_raffle::ID },
+        instruction::StartRefunding {},
+    )
+}
+
+pub fn forfeit_winner(raffle: &TestRaffle, caller: &Pubkey) -> Instruction {
+    ix(
+        accounts::ForfeitWinner {
+            caller: *caller,
+            raffle: raffle.key,
+            slots: raffle.slots,
+            event_authority: event_authority(),
+            program: rwa_raffle::ID,
+        },
+        instruction::ForfeitWinner {},
+    )
+}
+
+pub fn settle_draw(raffle: &TestRaffle, authority: &Pubkey, winner_ticket: u64) -> Instruction {
+    ix(
+        accounts::SettleDraw {
+            config: platform_config_pda(),
+            authority: *authority,
+            raffle: raffle.key,
+            slots: raffle.slots,
+            event_authority: event_authority(),
+            program: rwa_raffle::ID,
+        },
+        instruction::SettleDraw { winner_ticket },
+    )
+}
+
+pub fn claim_refund(raffle: &TestRaffle, payer: &Pubkey, ticket: &Pubkey) -> Instruction {
+    claim_refund_with(raffle, payer, ticket, None)
+}
+
+pub fn claim_refund_with(raffle: &TestRaffle, payer: &Pubkey, ticket: &Pubkey, holder_ticket_account: Option<Pubkey>) -> Instruction {
+    ix(
+        accounts::ClaimRefund {
+            payer: *payer,
+            raffle: raffle.key,
+            mint: raffle.mint,
+            payer_ata: get_associated_token_address(payer, &raffle.mint),
+            escrow_ata: raffle.escrow,
+            ticket: *ticket,
+            holder_ticket_account,
+            token_program: spl_token::ID,
+            mrft: no_mrft(),
+            event_authority: event_authority(),
+            program: rwa_raffle::ID,
+        },
+        instruction::ClaimRefund {},
+    )
+}
+
+/// `claim_refund` asking for the MRFTs to be minted on `merkle_tree` right away. Only the
+/// `bubblegum` build mints them, against the real Bubblegum programs; this suite runs the
+/// default build, which must reject the request rather than fall back to the event.
+pub fn claim_refund_minting(raffle: &TestRaffle, payer: &Pubkey, ticket: &Pubkey, merkle_tree: &Pubkey) -> Instruction {
+    let collection_metadata = Pubkey::find_program_address(
+        &[b"metadata", TOKEN_METADATA_ID.as_ref(), MRFT_COLLECTION_MINT.as_ref()],
+        &TOKEN_METADATA_ID,
+    )
+    .0;
+    let collection_edition = Pubkey::find_program_address(
+        &[b"metadata", TOKEN_METADATA_ID.as_ref(), MRFT_COLLECTION_MINT.as_ref(), b"edition"],
+        &TOKEN_METADATA_ID,
+    )
+    .0;
+    ix(
+        accounts::ClaimRefund {
+            payer: *payer,
+            raffle: raffle.key,
+            mint: raffle.mint,
+            payer_ata: get_associated_token_address(payer, &raffle.mint),
+            escrow_ata: raffle.escrow,
+            ticket: *ticket,
+            holder_ticket_account: None,
+            token_program: spl_token::ID,
+            mrft: accounts::MrftMint {
+                tree_authority: Some(mrft_tree_authority().0),
+                tree_config: Some(Pubkey::find_program_address(&[merkle_tree.as_ref()], &BUBBLEGUM_ID).0),
+                merkle_tree: Some(*merkle_tree),
+                collection_mint: Some(MRFT_COLLECTION_MINT),
+                collection_metadata: Some(collection_metadata),
+                collection_edition: Some(collection_edition),
+                collection_authority_record: None,
+                bubblegum_signer: Some(Pubkey::find_program_address(&[b"collection_cpi"], &BUBBLEGUM_ID).0),
+                log_wrapper: Some(SPL_NOOP_ID),
+                compression_program: Some(SPL_ACCOUNT_COMPRESSION_ID),
+                token_metadata_program: Some(TOKEN_METADATA_ID),
+                bubblegum_program: Some(BUBBLEGUM_ID),
+                system_program: Some(system_program::ID),
+            },
+            event_authority: event_authority(),
+            program: rwa_raffle::ID,
+        },
+        instruction::ClaimRefund {},
+    )
+}
+
+/// `refund_batch` over `(ticket, owner_ata)` pairs (USDC refund mode).
+pub fn refund_batch(raffle: &TestRaffle, caller: &Pubkey, pairs: &[(Pubkey, Pubkey)]) -> Instruction {
+    let mut ix = ix(
+        accounts::RefundBatch {
+            caller: *caller,
+            raffle: raffle.key,
+            mint: raffle.mint,
+            escrow_ata: raffle.escrow,
+            token_program: spl_token::ID,
+            event_authority: event_authority(),
+            program: rwa_raffle::ID,
+        },
+        instruction::RefundBatch {},
+    );
+    for (ticket, owner_ata) in pairs {
+        ix.accounts.push(AccountMeta::new(*ticket, false));
+        ix.accounts.push(AccountMeta::new(*owner_ata, false));
+    }
+    ix
+}
+
+pub fn claim_win(raffle: &TestRaffle, winner: &Pubkey, ticket: &Pubkey) -> Instruction {
+    claim_win_with(raffle, winner, ticket, None)
+}
+
+pub fn claim_win_with(raffle: &TestRaffle, winner: &Pubkey, ticket: &Pubkey, holder_ticket_account: Option<Pubkey>) -> Instruction {
+    ix(
+        accounts::ClaimWin {
+            winner: *winner,
+            raffle: raffle.key,
+            ticket: *ticket,
+            holder_ticket_account,
+            event_authority: event_authority(),
+            program: rwa_raffle::ID,
+        },
+        instruction::ClaimWin {},
+    )
+}
+
+/// Plain SPL prizes: no Token Metadata `TransferV1` accounts.
+fn no_pnft() -> accounts::ProgrammableTransfer {
+    accounts::ProgrammableTransfer {
+        token_metadata_program: None,
+        source_token_record: None,
+        destination_token_record: None,
+        sysvar_instructions: None,
+        associated_token_program: None,
+        system_program: None,
+        authorization_rules_program: None,
+        authorization_rules: None,
+    }
+}
+
+fn no_mrft() -> accounts::MrftMint {
+    accounts::MrftMint {
+        tree_authority: None,
+        tree_config: None,
+        merkle_tree: None,
+        collection_mint: None,
+        collection_metadata: None,
+        collection_edition: None,
+        collection_authority_record: None,
+        bubblegum_signer: None,
+        log_wrapper: None,
+        compression_program: None,
+        token_metadata_program: None,
+        bubblegum_program: None,
+        system_program: None,
+    }
+}
+
+fn no_cnft() -> accounts::CompressedTransfer {
+    accounts::CompressedTransfer {
+        bubblegum_program: None,
+        tree_config: None,
+        merkle_tree: None,
+        log_wrapper: None,
+        compression_program: None,
+        leaf_owner: None,
+        system_program: None,
+    }
+}
+
+pub fn set_prize_nft(
+    raffle: &TestRaffle,
+    organizer: &Pubkey,
+    prize_mint: &Pubkey,
+    organizer_prize_ata: &Pubkey,
+    prize_escrow: &Pubkey,
+) -> Instruction {
+    ix(
+        accounts::SetPrizeNft {
+            organizer: *organizer,
+            raffle: raffle.key,
+            prize_mint: *prize_mint,
+            organizer_prize_ata: *organizer_prize_ata,
+            prize_escrow: *prize_escrow,
+            prize_metadata: None,
+            prize_master_edition: None,
+            token_program: spl_token::ID,
+            pnft: no_pnft(),
+            event_authority: event_authority(),
+            program: rwa_raffle::ID,
+        },
+        instruction::SetPrizeNft {},
+    )
+}
+
+pub fn claim_prize(
+    raffle: &TestRaffle,
+    winner: &Pubkey,
+    ticket: &Pubkey,
+    prize_mint: &Pubkey,
+    prize_escrow: &Pubkey,
+    winner_prize_ata: &Pubkey,
+) -> Instruction {
+    claim_prize_with(raffle, winner, ticket, prize_mint, prize_escrow, winner_prize_ata, None)
+}
+
+pub fn claim_prize_with(
+    raffle: &TestRaffle,
+    winner: &Pubkey,
+    ticket: &Pubkey,
+    prize_mint: &Pubkey,
+    prize_escrow: &Pubkey,
+    winner_prize_ata: &Pubkey,
+    holder_ticket_account: Option<Pubkey>,
+) -> Instruction {
+    ix(
+        accounts::ClaimPrize {
+            winner: *winner,
+            raffle: raffle.key,
+            prize_mint: Some(*prize_mint),
+            prize_escrow: Some(*prize_escrow),
+            winner_prize_ata: Some(*winner_prize_ata),
+            ticket: *ticket,
+            holder_ticket_account,
+            prize_metadata: None,
+            prize_master_edition: None,
+            token_program: spl_token::ID,
+            pnft: no_pnft(),
+            cnft: no_cnft(),
+            event_authority: event_authority(),
+            program: rwa_raffle::ID,
+        },
+        instruction::ClaimPrize { cnft_root: None },
+    )
+}
+
+/// `claim_prize` of a compressed prize held in `merkle_tree`, with no proof nodes.
+pub fn claim_prize_cnft(
+    raffle: &TestRaffle,
+    winner: &Pubkey,
+    ticket: &Pubkey,
+    merkle_tree: &Pubkey,
+    cnft_root: Option<[u8; 32]>,
+) -> Instruction {
+    ix(
+        accounts::ClaimPrize {
+            winner: *winner,
+            raffle: raffle.key,
+            prize_mint: None,
+            prize_escrow: None,
+            winner_prize_ata: None,
+            ticket: *ticket,
+            holder_ticket_account: None,
+            prize_metadata: None,
+            prize_master_edition: None,
+            token_program: spl_token::ID,
+            pnft: no_pnft(),
+            cnft: accounts::CompressedTransfer {
+                bubblegum_program: Some(BUBBLEGUM_ID),
+                tree_config: Some(Pubkey::find_program_address(&[merkle_tree.as_ref()], &BUBBLEGUM_ID).0),
+                merkle_tree: Some(*merkle_tree),
+                log_wrapper: Some(SPL_NOOP_ID),
+                compression_program: Some(SPL_ACCOUNT_COMPRESSION_ID),
+                leaf_owner: None,
+                system_program: Some(system_program::ID),
+            },
+            event_authority: event_authority(),
+            program: rwa_raffle::ID,
+        },
+        instruction::ClaimPrize { cnft_root },
+    )
+}
+
+pub fn collect_proceeds(raffle: &TestRaffle, organizer_ata: &Pubkey) -> Instruction {
+    ix(
+        accounts::CollectProceeds {
+            organizer: raffle.organizer,
+            raffle: raffle.key,
+            mint: raffle.mint,
+            organizer_ata: *organizer_ata,
+            escrow_ata: raffle.escrow,
+            token_program: spl_token::ID,
+        },
+        instruction::CollectProceeds {},
+    )
+}
+
+/// `payer` is the ticket's rent payer, which gets the rent back.
+pub fn close_ticket(raffle: &TestRaffle, caller: &Pubkey, payer: &Pubkey, ticket: &Pubkey) -> Instruction {
+    ix(
+        accounts::CloseTicket { caller: *caller, raffle: raffle.key, ticket: *ticket, payer: *payer },
+        instruction::CloseTicket {},
+    )
+}
+
+/// Tokenized ticket mint, new ticket PDA and `owner`'s NFT account for the ticket at `start`.
+pub fn ticket_nft_keys(raffle: &TestRaffle, owner: &Pubkey, start: u64) -> (Pubkey, Pubkey, Pubkey) {
+    let ticket_mint = Pubkey::find_program_address(&[TICKET_MINT_SEED, raffle.key.as_ref(), &start.to_le_bytes()], &rwa_raffle::ID).0;
+    let ticket = ticket_pda(&raffle.key, &ticket_mint, start);
+    (ticket_mint, ticket, nft_account(owner, &ticket_mint))
+}
+
+/// Token-2022 ATA of `owner` for `mint`.
+pub fn nft_account(owner: &Pubkey, mint: &Pubkey) -> Pubkey {
+    get_associated_token_address_with_program_id(owner, mint, &spl_token_2022::ID)
+}
+
+/// `payer` is the old ticket's rent payer, which gets the rent back.
+pub fn tokenize_ticket(raffle: &TestRaffle, owner: &Pubkey, ticket: &Pubkey, start: u64, payer: &Pubkey) -> Instruction {
+    let (ticket_mint, new_ticket, owner_ticket_account) = ticket_nft_keys(raffle, owner, start);
+    ix(
+        accounts::TokenizeTicket {
+            owner: *owner,
+            raffle: raffle.key,
+            slots: raffle.slots,
+            ticket: *ticket,
+            payer: *payer,
+            ticket_mint,
+            owner_ticket_account,
+            new_ticket,
+            token_2022_program: spl_token_2022::ID,
+            associated_token_program: anchor_spl::associated_token::ID,
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: rwa_raffle::ID,
+        },
+        instruction::TokenizeTicket {},
+    )
+}
+
+/// Returns the instruction and the recipient's new ticket PDA. `payer` is the old ticket's
+/// rent payer, which gets the rent back.
+pub fn transfer_ticket(
+    raffle: &TestRaffle,
+    owner: &Pubkey,
+    ticket: &Pubkey,
+    start: u64,
+    recipient: &Pubkey,
+    payer: &Pubkey,
+) -> (Instruction, Pubkey) {
+    let new_ticket = ticket_pda(&raffle.key, recipient, start);
+    let ix = ix(
+        accounts::TransferTicket {
+            owner: *owner,
+            raffle: raffle.key,
+            slots: raffle.slots,
+            ticket: *ticket,
+            payer: *payer,
+            recipient: *recipient,
+            new_ticket,
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: rwa_raffle::ID,
+        },
+        instruction::TransferTicket {},
+    );
+    (ix, new_ticket)
+}
+
+pub fn close_slots(raffle: &TestRaffle, caller: &Pubkey) -> Instruction {
+    ix(
+        accounts::CloseSlots { caller: *caller, raffle: raffle.key, slots: raffle.slots, organizer: raffle.organizer },
+        instruction::CloseSlots {},
+    )
+}
+
+pub fn close_raffle(raffle: &TestRaffle, caller: &Pubkey) -> Instruction {
+    ix(
+        accounts::CloseRaffle {
+            caller: *caller,
+            raffle: raffle.key,
+            escrow_ata: raffle.escrow,
+            organizer: raffle.organizer,
+            token_program: spl_token::ID,
+            event_authority: event_authority(),
+            program: rwa_raffle::ID,
+        },
+        instruction::CloseRaffle {},
+    )
+}
+
+pub fn sweep_mrft_backing(raffle: &TestRaffle, caller: &Pubkey, refund_vault: &Pubkey) -> Instruction {
+    ix(
+        accounts::SweepMrftBacking {
+            caller: *caller,
+            raffle: raffle.key,
+            mint: raffle.mint,
+            escrow_ata: raffle.escrow,
+            vault_authority: refund_vault_authority(),
+            refund_vault: *refund_vault,
+            token_program: spl_token::ID,
+            event_authority: event_authority(),
+            program: rwa_raffle::ID,
+        },
+        instruction::SweepMrftBacking {},
+    )
+}
+
+pub fn withdraw_refund_vault(authority: &Pubkey, mint: &Pubkey, refund_vault: &Pubkey, destination: &Pubkey, amount: u64) -> Instruction {
+    ix(
+        accounts::WithdrawRefundVault {
+            config: platform_config_pda(),
+            authority: *authority,
+            mint: *mint,
+            vault_authority: refund_vault_authority(),
+            refund_vault: *refund_vault,
+            destination: *destination,
+            token_program: spl_token::ID,
+            event_authority: event_authority(),
+            program: rwa_raffle::ID,
+        },
+        instruction::WithdrawRefundVault { amount },
+    )
+}
+
+pub fn set_platform_authority(authority: &Pubkey, new_authority: &Pubkey) -> Instruction {
+    ix(
+        accounts::SetPlatformAuthority {
+            config: platform_config_pda(),
+            authority: *authority,
+            event_authority: event_authority(),
+            program: rwa_raffle::ID,
+        },
+        instruction::SetPlatformAuthority { new_authority: *new_authority },
+    )
+}
+
+pub fn audit_raffle(raffle: &TestRaffle) -> Instruction {
+    ix(
+        accounts::AuditRaffle {
+            raffle: raffle.key,
+            mint: raffle.mint,
+            escrow_ata: raffle.escrow,
+            event_authority: event_authority(),
+            program: rwa_raffle::ID,
+        },
+        instruction::AuditRaffle {},
+    )
+}
+
+pub fn migrate_ticket(ticket: &Pubkey, raffle: &Pubkey, payer: &Pubkey) -> Instruction {
+    ix(
+        accounts::MigrateTicket {
+            payer: *payer,
+            ticket: *ticket,
+            raffle: *raffle,
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: rwa_raffle::ID,
+        },
+        instruction::MigrateTicket {},
+    )
+}
+
+pub fn migrate_raffle(raffle: &Pubkey, payer: &Pubkey) -> Instruction {
+    ix(
+        accounts::MigrateRaffle {
+            payer: *payer,
+            raffle: *raffle,
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: rwa_raffle::ID,
+        },
+        instruction::MigrateRaffle {},
+    )
+}
@@ -0,0 +1,681 @@
+//! Negative cases, one per `RaffleError` variant reachable in the default build.
+//!
+//! Not covered here:
+//! - `Overflow`: needs counters near `u64::MAX`.
+//! - `WrongRaffle` outside `migrate_ticket`: covered for `close_ticket` in `refunds.rs`;
+//!   the claim handlers run the same `ticket.raffle` check.
+//! - `InvalidSlot`, `SlotAlreadyTaken`, `SlippageExceeded`, `Pyth*`, `JupiterAccountsMissing`,
+//!   `TicketBurnRequired`, `InvalidProof`, `InvalidCollectionMint`, `InvalidCollection`:
+//!   `pyth-jupiter`, `bubblegum` and `metaplex` paths.
+//! - `TicketModeDisabled`, `SlotsHashMismatch`: not raised anywhere yet.
+
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use common::*;
+use rwa_raffle::{CompressedPrize, RaffleError, MRFT_BACKING_SWEEP_DELAY_SECS};
+use solana_sdk::signature::{Keypair, Signer};
+
+/// Addresses (and escrow) of a raffle that has not been initialized yet.
+fn uninitialized_raffle(f: &mut Fixture) -> (TestRaffle, Keypair) {
+    let organizer = f.organizer.insecure_clone();
+    let raffle = f.raffle_addresses(&f.usdc, &organizer.pubkey());
+    f.set_token_account(&raffle.escrow, &raffle.mint, &raffle.key, 0);
+    (raffle, organizer)
+}
+
+/// Raffle of `required` tickets where `buyer` bought tickets 1..=`bought`.
+fn raffle_with_buyer(f: &mut Fixture, required: u64, bought: u64) -> (TestRaffle, Keypair, Pubkey) {
+    let raffle = f.create_raffle(RaffleParams { required_tickets: required, ..Default::default() });
+    let buyer = f.user(bought);
+    let ticket = f.buy(&raffle, &buyer, bought);
+    (raffle, buyer, ticket)
+}
+
+/// Completed single-buyer raffle (winner ticket 1), optionally with an escrowed prize.
+fn completed_raffle(f: &mut Fixture, with_prize: bool) -> (TestRaffle, Keypair, Pubkey, Option<(Pubkey, Pubkey)>) {
+    let raffle = f.create_raffle(RaffleParams { required_tickets: 2, ..Default::default() });
+    let prize = with_prize.then(|| f.set_prize(&raffle));
+    let winner = f.user(2);
+    let ticket = f.buy(&raffle, &winner, 2);
+    let platform = f.platform.insecure_clone();
+    f.ok(&[settle_draw(&raffle, &platform.pubkey(), 1)], &[&platform]);
+    (raffle, winner, ticket, prize)
+}
+
+/// Unfilled raffle past its deadline with its only ticket refunded (settled).
+fn refunded_raffle(f: &mut Fixture) -> (TestRaffle, Keypair, Pubkey) {
+    let (raffle, buyer, ticket) = raffle_with_buyer(f, 10, 1);
+    f.warp_to(START_TS + DAY + 1);
+    f.ok(&[claim_refund(&raffle, &buyer.pubkey(), &ticket)], &[&buyer]);
+    (raffle, buyer, ticket)
+}
+
+// ---- initialize_raffle ----
+
+#[test]
+fn init_rejects_zero_tickets() {
+    let mut f = Fixture::new();
+    let (raffle, organizer) = uninitialized_raffle(&mut f);
+    let params = RaffleParams { required_tickets: 0, ..Default::default() };
+    assert_raffle_err(f.send(&[initialize_raffle(&raffle, &params)], &[&organizer]), RaffleError::InvalidAmount);
+}
+
+#[test]
+fn init_rejects_past_deadline() {
+    let mut f = Fixture::new();
+    let (raffle, organizer) = uninitialized_raffle(&mut f);
+    let params = RaffleParams { deadline: START_TS, ..Default::default() };
+    assert_raffle_err(f.send(&[initialize_raffle(&raffle, &params)], &[&organizer]), RaffleError::InvalidDeadline);
+}
+
+#[test]
+fn init_rejects_reveal_time_outside_now_to_deadline() {
+    let mut f = Fixture::new();
+    let (raffle, organizer) = uninitialized_raffle(&mut f);
+    let params = RaffleParams { reveal_time: Some(START_TS - 1), ..Default::default() };
+    assert_raffle_err(f.send(&[initialize_raffle(&raffle, &params)], &[&organizer]), RaffleError::InvalidRevealTime);
+    let params = RaffleParams { reveal_time: Some(START_TS + DAY + 1), ..Default::default() };
+    assert_raffle_err(f.send(&[initialize_raffle(&raffle, &params)], &[&organizer]), RaffleError::InvalidRevealTime);
+}
+
+#[test]
+fn permit_init_rejects_expired_permit() {
+    let mut f = Fixture::new();
+    let (raffle, organizer) = uninitialized_raffle(&mut f);
+    let ix = initialize_raffle_with_permit(&raffle, &RaffleParams::default(), [7; 16], START_TS);
+    assert_raffle_err(f.send(&[ix], &[&organizer]), RaffleError::PermitExpired);
+}
+
+#[test]
+fn permit_init_requires_backend_signature() {
+    let mut f = Fixture::new();
+    let (raffle, organizer) = uninitialized_raffle(&mut f);
+    let ix = initialize_raffle_with_permit(&raffle, &RaffleParams::default(), [7; 16], START_TS + 600);
+    assert_raffle_err(f.send(&[ix], &[&organizer]), RaffleError::PermitInvalid);
+}
+
+#[test]
+fn batch_create_caps_batch_size() {
+    let mut f = Fixture::new();
+    let organizer = f.organizer.insecure_clone();
+    let configs = vec![(10, START_TS + DAY, false, 0, None, [0u8; 32], Pubkey::default()); 6];
+    let permits = vec![([0u8; 16], START_TS + 600); 6];
+    assert_raffle_err(
+        f.send(&[batch_create_raffles(&organizer.pubkey(), configs, permits)], &[&organizer]),
+        RaffleError::BatchSizeExceeded,
+    );
+}
+
+// ---- deposit ----
+
+#[test]
+fn deposit_rejects_zero_amount() {
+    let mut f = Fixture::new();
+    let raffle = f.create_raffle(RaffleParams::default());
+    let alice = f.user(1);
+    assert_raffle_err(f.send(&[deposit(&raffle, &alice.pubkey(), 0, 1)], &[&alice]), RaffleError::InvalidAmount);
+}
+
+#[test]
+fn deposit_rejects_stale_start_index() {
+    let mut f = Fixture::new();
+    let (raffle, _, _) = raffle_with_buyer(&mut f, 10, 2);
+    let bob = f.user(1);
+    // next_ticket_index is 3 now
+    assert_raffle_err(f.send(&[deposit(&raffle, &bob.pubkey(), USDC, 1)], &[&bob]), RaffleError::ConcurrentDeposit);
+}
+
+#[test]
+fn deposit_rejects_fractional_tokens() {
+    let mut f = Fixture::new();
+    let raffle = f.create_raffle(RaffleParams::default());
+    let alice = f.user(2);
+    assert_raffle_err(
+        f.send(&[deposit(&raffle, &alice.pubkey(), USDC + USDC / 2, 1)], &[&alice]),
+        RaffleError::MustDepositWholeTokens,
+    );
+}
+
+#[test]
+fn deposit_rejects_over_subscription() {
+    let mut f = Fixture::new();
+    let raffle = f.create_raffle(RaffleParams::default());
+    let alice = f.user(11);
+    assert_raffle_err(f.send(&[deposit(&raffle, &alice.pubkey(), 11 * USDC, 1)], &[&alice]), RaffleError::OverSubscription);
+}
+
+#[test]
+fn deposit_rejects_past_deadline() {
+    let mut f = Fixture::new();
+    let raffle = f.create_raffle(RaffleParams::default());
+    let alice = f.user(1);
+    f.warp_to(START_TS + DAY + 1);
+    assert_raffle_err(f.send(&[deposit(&raffle, &alice.pubkey(), USDC, 1)], &[&alice]), RaffleError::PastDeadline);
+}
+
+#[test]
+fn deposit_rejects_filled_raffle() {
+    let mut f = Fixture::new();
+    let (raffle, _, _) = raffle_with_buyer(&mut f, 2, 2);
+    let bob = f.user(1);
+    assert_raffle_err(f.send(&[deposit(&raffle, &bob.pubkey(), USDC, 3)], &[&bob]), RaffleError::RaffleNotSelling);
+}
+
+// ---- draw ----
+
+#[test]
+fn draw_requires_drawing_status() {
+    let mut f = Fixture::new();
+    let organizer = f.organizer.insecure_clone();
+    let platform = f.platform.insecure_clone();
+    let (raffle, _, _) = raffle_with_buyer(&mut f, 10, 1);
+    assert_raffle_err(f.send(&[request_draw(&raffle, &organizer.pubkey())], &[&organizer]), RaffleError::WrongStatus);
+    assert_raffle_err(f.send(&[settle_draw(&raffle, &platform.pubkey(), 1)], &[&platform]), RaffleError::WrongStatus);
+}
+
+#[test]
+fn settle_rejects_out_of_range_winner() {
+    let mut f = Fixture::new();
+    let organizer = f.organizer.insecure_clone();
+    let platform = f.platform.insecure_clone();
+    let (raffle, _, _) = raffle_with_buyer(&mut f, 2, 2);
+    assert_raffle_err(f.send(&[settle_draw(&raffle, &organizer.pubkey(), 1)], &[&organizer]), RaffleError::Unauthorized);
+    assert_raffle_err(f.send(&[settle_draw(&raffle, &platform.pubkey(), 0)], &[&platform]), RaffleError::InvalidWinner);
+    assert_raffle_err(f.send(&[settle_draw(&raffle, &platform.pubkey(), 3)], &[&platform]), RaffleError::InvalidWinner);
+}
+
+// ---- claims ----
+
+#[test]
+fn claim_win_requires_completed_raffle() {
+    let mut f = Fixture::new();
+    let (raffle, buyer, ticket) = raffle_with_buyer(&mut f, 2, 2);
+    assert_raffle_err(f.send(&[claim_win(&raffle, &buyer.pubkey(), &ticket)], &[&buyer]), RaffleError::WrongStatus);
+}
+
+#[test]
+fn claim_win_only_once() {
+    let mut f = Fixture::new();
+    let (raffle, winner, ticket, _) = completed_raffle(&mut f, false);
+    f.ok(&[claim_win(&raffle, &winner.pubkey(), &ticket)], &[&winner]);
+    assert_raffle_err(f.send(&[claim_win(&raffle, &winner.pubkey(), &ticket)], &[&winner]), RaffleError::AlreadyClaimedWin);
+}
+
+#[test]
+fn claim_prize_requires_prize() {
+    let mut f = Fixture::new();
+    let (raffle, winner, ticket, _) = completed_raffle(&mut f, false);
+    f.ok(&[claim_win(&raffle, &winner.pubkey(), &ticket)], &[&winner]);
+
+    let stray_mint = f.create_mint(0);
+    let escrow = f.set_ata(&raffle.key, &stray_mint, 0);
+    let winner_ata = f.set_ata(&winner.pubkey(), &stray_mint, 0);
+    assert_raffle_err(
+        f.send(&[claim_prize(&raffle, &winner.pubkey(), &ticket, &stray_mint, &escrow, &winner_ata)], &[&winner]),
+        RaffleError::PrizeNotSet,
+    );
+}
+
+#[test]
+fn claim_prize_requires_claim_win_first() {
+    let mut f = Fixture::new();
+    let (raffle, winner, ticket, prize) = completed_raffle(&mut f, true);
+    let (prize_mint, prize_escrow) = prize.unwrap();
+    let winner_ata = f.set_ata(&winner.pubkey(), &prize_mint, 0);
+    assert_raffle_err(
+        f.send(&[claim_prize(&raffle, &winner.pubkey(), &ticket, &prize_mint, &prize_escrow, &winner_ata)], &[&winner]),
+        RaffleError::MustClaimWinFirst,
+    );
+}
+
+#[test]
+fn claim_prize_only_once() {
+    let mut f = Fixture::new();
+    let (raffle, winner, ticket, prize) = completed_raffle(&mut f, true);
+    let (prize_mint, prize_escrow) = prize.unwrap();
+    let winner_ata = f.set_ata(&winner.pubkey(), &prize_mint, 0);
+    let claim = claim_prize(&raffle, &winner.pubkey(), &ticket, &prize_mint, &prize_escrow, &winner_ata);
+    f.ok(&[claim_win(&raffle, &winner.pubkey(), &ticket), claim.clone()], &[&winner]);
+    assert_raffle_err(f.send(&[claim], &[&winner]), RaffleError::PrizeAlreadyClaimed);
+}
+
+// ---- prize setup ----
+
+#[test]
+fn set_prize_only_by_organizer() {
+    let mut f = Fixture::new();
+    let raffle = f.create_raffle(RaffleParams::default());
+    let mallory = f.user(0);
+    let (prize_mint, mallory_ata) = f.create_nft(&mallory.pubkey());
+    let escrow = f.set_ata(&raffle.key, &prize_mint, 0);
+    assert_raffle_err(
+        f.send(&[set_prize_nft(&raffle, &mallory.pubkey(), &prize_mint, &mallory_ata, &escrow)], &[&mallory]),
+        RaffleError::Unauthorized,
+    );
+}
+
+#[test]
+fn compressed_prize_stays_escrowed_without_bubblegum() {
+    let mut f = Fixture::new();
+    let (raffle, winner, ticket, _) = completed_raffle(&mut f, false);
+    // What `set_prize_cnft` records in the `bubblegum` build
+    let tree = Pubkey::new_unique();
+    f.patch_raffle(&raffle, |r| {
+        r.prize_set = true;
+        r.prize_cnft = CompressedPrize { merkle_tree: tree, data_hash: [1; 32], creator_hash: [2; 32], ..Default::default() };
+    });
+    f.ok(&[claim_win(&raffle, &winner.pubkey(), &ticket)], &[&winner]);
+
+    // The leaf can't be proven without its root, and only Bubblegum can move it
+    for root in [None, Some([3; 32])] {
+        assert_raffle_err(
+            f.send(&[claim_prize_cnft(&raffle, &winner.pubkey(), &ticket, &tree, root)], &[&winner]),
+            RaffleError::InvalidPrizeAccounts,
+        );
+    }
+    assert!(!f.raffle(&raffle).prize_claimed);
+}
+
+#[test]
+fn set_prize_only_once() {
+    let mut f = Fixture::new();
+    let organizer = f.organizer.insecure_clone();
+    let raffle = f.create_raffle(RaffleParams::default());
+    f.set_prize(&raffle);
+
+    let (second_mint, organizer_ata) = f.create_nft(&organizer.pubkey());
+    let escrow = f.set_ata(&raffle.key, &second_mint, 0);
+    assert_raffle_err(
+        f.send(&[set_prize_nft(&raffle, &organizer.pubkey(), &second_mint, &organizer_ata, &escrow)], &[&organizer]),
+        RaffleError::PrizeAlreadySet,
+    );
+}
+
+#[test]
+fn set_prize_requires_nft_mint() {
+    let mut f = Fixture::new();
+    let organizer = f.organizer.insecure_clone();
+    let raffle = f.create_raffle(RaffleParams::default());
+    let fungible = f.create_mint(6);
+    let organizer_ata = f.set_ata(&organizer.pubkey(), &fungible, USDC);
+    let escrow = f.set_ata(&raffle.key, &fungible, 0);
+    assert_raffle_err(
+        f.send(&[set_prize_nft(&raffle, &organizer.pubkey(), &fungible, &organizer_ata, &escrow)], &[&organizer]),
+        RaffleError::PrizeMustBeNft,
+    );
+}
+
+#[test]
+fn set_prize_locked_after_selling() {
+    let mut f = Fixture::new();
+    let organizer = f.organizer.insecure_clone();
+    let (raffle, _, _, _) = completed_raffle(&mut f, false);
+
+    let (prize_mint, organizer_ata) = f.create_nft(&organizer.pubkey());
+    let escrow = f.set_ata(&raffle.key, &prize_mint, 0);
+    assert_raffle_err(
+        f.send(&[set_prize_nft(&raffle, &organizer.pubkey(), &prize_mint, &organizer_ata, &escrow)], &[&organizer]),
+        RaffleError::PrizeLocked,
+    );
+}
+
+#[test]
+fn prize_backed_raffle_sells_only_after_escrow() {
+    let mut f = Fixture::new();
+    let organizer = f.organizer.insecure_clone();
+    let raffle = f.create_raffle(RaffleParams::default());
+    f.ok(&[set_prize_backed(&raffle, &organizer.pubkey(), true)], &[&organizer]);
+    assert!(f.raffle(&raffle).prize_backed);
+
+    let alice = f.user(2);
+    assert_raffle_err(
+        f.send(&[deposit(&raffle, &alice.pubkey(), USDC, 1)], &[&alice]),
+        RaffleError::PrizeNotEscrowed,
+    );
+
+    f.set_prize(&raffle);
+    f.buy(&raffle, &alice, 1);
+    assert_raffle_err(
+        f.send(&[set_prize_backed(&raffle, &organizer.pubkey(), false)], &[&organizer]),
+        RaffleError::PrizeBackingLocked,
+    );
+    f.audit(&raffle);
+}
+
+// ---- proceeds ----
+
+#[test]
+fn collect_requires_completed_raffle() {
+    let mut f = Fixture::new();
+    let organizer = f.organizer.insecure_clone();
+    let (raffle, _, _) = raffle_with_buyer(&mut f, 10, 1);
+    let organizer_ata = f.set_ata(&organizer.pubkey(), &raffle.mint, 0);
+    assert_raffle_err(f.send(&[collect_proceeds(&raffle, &organizer_ata)], &[&organizer]), RaffleError::WrongStatus);
+}
+
+#[test]
+fn collect_only_once() {
+    let mut f = Fixture::new();
+    let organizer = f.organizer.insecure_clone();
+    let (raffle, _, _, _) = completed_raffle(&mut f, false);
+    let organizer_ata = f.set_ata(&organizer.pubkey(), &raffle.mint, 0);
+    f.ok(&[collect_proceeds(&raffle, &organizer_ata)], &[&organizer]);
+    assert_raffle_err(f.send(&[collect_proceeds(&raffle, &organizer_ata)], &[&organizer]), RaffleError::AlreadyCollected);
+}
+
+// ---- refunds ----
+
+#[test]
+fn refund_batch_requires_ticket_ata_pairs() {
+    let mut f = Fixture::new();
+    let organizer = f.organizer.insecure_clone();
+    let (raffle, buyer, ticket) = raffle_with_buyer(&mut f, 10, 1);
+    f.warp_to(START_TS + DAY + 1);
+
+    let mut ix = refund_batch(&raffle, &organizer.pubkey(), &[(ticket, f.usdc_ata(&buyer.pubkey()))]);
+    ix.accounts.pop();
+    assert_raffle_err(f.send(&[ix], &[&organizer]), RaffleError::InvalidRemainingAccounts);
+}
+
+#[test]
+fn refund_batch_pays_only_the_ticket_owner() {
+    let mut f = Fixture::new();
+    let organizer = f.organizer.insecure_clone();
+    let (raffle, _, ticket) = raffle_with_buyer(&mut f, 10, 1);
+    let mallory = f.user(0);
+    f.warp_to(START_TS + DAY + 1);
+
+    let ix = refund_batch(&raffle, &organizer.pubkey(), &[(ticket, f.usdc_ata(&mallory.pubkey()))]);
+    assert_raffle_err(f.send(&[ix], &[&organizer]), RaffleError::Unauthorized);
+}
+
+#[test]
+fn refund_only_once() {
+    let mut f = Fixture::new();
+    let (raffle, buyer, ticket) = refunded_raffle(&mut f);
+    assert_raffle_err(f.send(&[claim_refund(&raffle, &buyer.pubkey(), &ticket)], &[&buyer]), RaffleError::AlreadyRefunded);
+}
+
+#[test]
+fn refund_waits_for_deadline() {
+    let mut f = Fixture::new();
+    let (raffle, buyer, ticket) = raffle_with_buyer(&mut f, 10, 1);
+    assert_raffle_err(f.send(&[claim_refund(&raffle, &buyer.pubkey(), &ticket)], &[&buyer]), RaffleError::NotRefundableYet);
+}
+
+// ---- closing ----
+
+#[test]
+fn close_requires_settled_raffle() {
+    let mut f = Fixture::new();
+    let organizer = f.organizer.insecure_clone();
+    let (raffle, buyer, ticket) = raffle_with_buyer(&mut f, 10, 1);
+    assert_raffle_err(
+        f.send(&[close_ticket(&raffle, &organizer.pubkey(), &buyer.pubkey(), &ticket)], &[&organizer]),
+        RaffleError::RaffleNotSettled,
+    );
+    assert_raffle_err(f.send(&[close_slots(&raffle, &organizer.pubkey())], &[&organizer]), RaffleError::RaffleNotSettled);
+    assert_raffle_err(f.send(&[close_raffle(&raffle, &organizer.pubkey())], &[&organizer]), RaffleError::RaffleNotSettled);
+}
+
+#[test]
+fn close_raffle_requires_closed_tickets() {
+    let mut f = Fixture::new();
+    let organizer = f.organizer.insecure_clone();
+    let (raffle, _, _) = refunded_raffle(&mut f);
+    f.ok(&[close_slots(&raffle, &organizer.pubkey())], &[&organizer]);
+    assert_raffle_err(f.send(&[close_raffle(&raffle, &organizer.pubkey())], &[&organizer]), RaffleError::TicketsStillOpen);
+}
+
+#[test]
+fn close_raffle_requires_closed_slots() {
+    let mut f = Fixture::new();
+    let organizer = f.organizer.insecure_clone();
+    let (raffle, buyer, ticket) = refunded_raffle(&mut f);
+    f.ok(&[close_ticket(&raffle, &organizer.pubkey(), &buyer.pubkey(), &ticket)], &[&organizer]);
+    assert_raffle_err(f.send(&[close_raffle(&raffle, &organizer.pubkey())], &[&organizer]), RaffleError::SlotsStillOpen);
+}
+
+#[test]
+fn close_raffle_requires_empty_escrow() {
+    let mut f = Fixture::new();
+    let organizer = f.organizer.insecure_clone();
+    let (raffle, buyer, ticket) = refunded_raffle(&mut f);
+    f.ok(
+        &[
+            close_ticket(&raffle, &organizer.pubkey(), &buyer.pubkey(), &ticket),
+            close_slots(&raffle, &organizer.pubkey()),
+        ],
+        &[&organizer],
+    );
+    // Someone donates to the escrow after the last refund
+    f.set_token_account(&raffle.escrow, &raffle.mint, &raffle.key, 1);
+    assert_raffle_err(f.send(&[close_raffle(&raffle, &organizer.pubkey())], &[&organizer]), RaffleError::EscrowNotEmpty);
+}
+
+// ---- presale ----
+
+/// Raffle with a presale ending half a day in, `alice` allowlisted for 2 tickets.
+fn presale_raffle(f: &mut Fixture) -> (TestRaffle, Keypair, Allowlist) {
+    let raffle = f.create_raffle(RaffleParams::default());
+    let alice = f.user(3);
+    let allowlist = f.open_presale(&raffle, vec![(alice.pubkey(), 2), (Pubkey::new_unique(), 5)], START_TS + DAY / 2);
+    (raffle, alice, allowlist)
+}
+
+#[test]
+fn allowlist_locked_after_first_sale() {
+    let mut f = Fixture::new();
+    let organizer = f.organizer.insecure_clone();
+    let (raffle, _, _) = raffle_with_buyer(&mut f, 10, 1);
+    assert_raffle_err(
+        f.send(&[set_allowlist(&raffle, &organizer.pubkey(), [7u8; 32], START_TS + 60)], &[&organizer]),
+        RaffleError::AllowlistLocked,
+    );
+}
+
+#[test]
+fn presale_must_end_before_deadline() {
+    let mut f = Fixture::new();
+    let organizer = f.organizer.insecure_clone();
+    let raffle = f.create_raffle(RaffleParams::default());
+    assert_raffle_err(
+        f.send(&[set_allowlist(&raffle, &organizer.pubkey(), [7u8; 32], START_TS + DAY + 1)], &[&organizer]),
+        RaffleError::InvalidPresaleEnd,
+    );
+}
+
+#[test]
+fn register_rejects_inflated_allocation() {
+    let mut f = Fixture::new();
+    let (raffle, alice, allowlist) = presale_raffle(&mut f);
+    let (_, proof) = allowlist.proof(&alice.pubkey());
+    assert_raffle_err(
+        f.send(&[register_allowlist(&raffle, &alice.pubkey(), 3, proof)], &[&alice]),
+        RaffleError::InvalidAllowlistProof,
+    );
+}
+
+#[test]
+fn register_closes_with_the_presale() {
+    let mut f = Fixture::new();
+    let (raffle, alice, allowlist) = presale_raffle(&mut f);
+    f.warp_to(START_TS + DAY / 2);
+    let (max_allocation, proof) = allowlist.proof(&alice.pubkey());
+    assert_raffle_err(
+        f.send(&[register_allowlist(&raffle, &alice.pubkey(), max_allocation, proof)], &[&alice]),
+        RaffleError::PresaleEnded,
+    );
+}
+
+#[test]
+fn presale_deposit_needs_allocation() {
+    let mut f = Fixture::new();
+    let (raffle, alice, _) = presale_raffle(&mut f);
+    assert_raffle_err(f.send(&[deposit(&raffle, &alice.pubkey(), USDC, 1)], &[&alice]), RaffleError::NotAllowlisted);
+}
+
+#[test]
+fn presale_deposit_capped_by_allocation() {
+    let mut f = Fixture::new();
+    let (raffle, alice, allowlist) = presale_raffle(&mut f);
+    f.register(&raffle, &alice, &allowlist);
+    assert_raffle_err(
+        f.send(&[presale_deposit(&raffle, &alice.pubkey(), 3 * USDC, 1)], &[&alice]),
+        RaffleError::AllocationExceeded,
+    );
+}
+
+#[test]
+fn allocation_open_until_presale_ends() {
+    let mut f = Fixture::new();
+    let organizer = f.organizer.insecure_clone();
+    let (raffle, alice, allowlist) = presale_raffle(&mut f);
+    f.register(&raffle, &alice, &allowlist);
+    assert_raffle_err(
+        f.send(&[close_allocation(&raffle, &organizer.pubkey(), &alice.pubkey())], &[&organizer]),
+        RaffleError::PresaleActive,
+    );
+}
+
+// ---- token gate ----
+
+#[test]
+fn gate_locked_after_first_sale() {
+    let mut f = Fixture::new();
+    let organizer = f.organizer.insecure_clone();
+    let (raffle, _, _) = raffle_with_buyer(&mut f, 10, 1);
+    let moga = f.create_mint(9);
+    assert_raffle_err(
+        f.send(&[set_gate(&raffle, &organizer.pubkey(), 1, &moga, 1)], &[&organizer]),
+        RaffleError::GateLocked,
+    );
+}
+
+#[test]
+fn gate_rejects_unknown_modes() {
+    let mut f = Fixture::new();
+    let organizer = f.organizer.insecure_clone();
+    let raffle = f.create_raffle(RaffleParams::default());
+    let moga = f.create_mint(9);
+    for (mode, min_amount) in [(1, 0), (3, 1)] {
+        assert_raffle_err(
+            f.send(&[set_gate(&raffle, &organizer.pubkey(), mode, &moga, min_amount)], &[&organizer]),
+            RaffleError::InvalidGate,
+        );
+    }
+}
+
+#[test]
+fn gated_deposit_needs_gate_account() {
+    let mut f = Fixture::new();
+    let organizer = f.organizer.insecure_clone();
+    let raffle = f.create_raffle(RaffleParams::default());
+    let moga = f.create_mint(9);
+    f.ok(&[set_gate(&raffle, &organizer.pubkey(), 1, &moga, 1)], &[&organizer]);
+    let alice = f.user(1);
+    assert_raffle_err(f.send(&[deposit(&raffle, &alice.pubkey(), USDC, 1)], &[&alice]), RaffleError::GateNotMet);
+
+    // Someone else's holding does not count
+    let bob = f.user(0);
+    let bob_moga = f.set_ata(&bob.pubkey(), &moga, 10);
+    assert_raffle_err(
+        f.send(&[gated_deposit(&raffle, &alice.pubkey(), USDC, 1, &bob_moga)], &[&alice]),
+        RaffleError::GateNotMet,
+    );
+}
+
+// ---- audit ----
+
+#[test]
+fn audit_flags_escrow_drift() {
+    let mut f = Fixture::new();
+    let organizer = f.organizer.insecure_clone();
+    let (raffle, _, _) = raffle_with_buyer(&mut f, 10, 3);
+    f.audit(&raffle);
+
+    // Escrow drained behind the program's back
+    f.set_token_account(&raffle.escrow, &raffle.mint, &raffle.key, 2 * USDC);
+    assert_raffle_err(f.send(&[audit_raffle(&raffle)], &[&organizer]), RaffleError::EscrowMismatch);
+
+    // Unaccounted surplus is drift too
+    f.set_token_account(&raffle.escrow, &raffle.mint, &raffle.key, 3 * USDC + 1);
+    assert_raffle_err(f.send(&[audit_raffle(&raffle)], &[&organizer]), RaffleError::EscrowMismatch);
+}
+
+// ---- migration ----
+
+#[test]
+fn migrate_rejects_current_layout() {
+    let mut f = Fixture::new();
+    let organizer = f.organizer.insecure_clone();
+    let raffle = f.create_raffle(RaffleParams::default());
+    assert_raffle_err(
+        f.send(&[migrate_raffle(&raffle.key, &organizer.pubkey())], &[&organizer]),
+        RaffleError::AlreadyMigrated,
+    );
+}
+
+#[test]
+fn migrate_ticket_rejects_current_layout_and_other_accounts() {
+    let mut f = Fixture::new();
+    let organizer = f.organizer.insecure_clone();
+    let (raffle, _, ticket) = raffle_with_buyer(&mut f, 10, 1);
+    assert_raffle_err(
+        f.send(&[migrate_ticket(&ticket, &raffle.key, &organizer.pubkey())], &[&organizer]),
+        RaffleError::AlreadyMigrated,
+    );
+    assert_raffle_err(
+        f.send(&[migrate_ticket(&raffle.key, &raffle.key, &organizer.pubkey())], &[&organizer]),
+        RaffleError::UnknownTicketLayout,
+    );
+
+    // Legacy refunds are counted on the ticket's own raffle only
+    let other_mint = f.create_mint(USDC_DECIMALS);
+    let other = f.create_raffle_for_mint(&other_mint, RaffleParams::default());
+    f.make_legacy_ticket(&ticket, true);
+    assert_raffle_err(
+        f.send(&[migrate_ticket(&ticket, &other.key, &organizer.pubkey())], &[&organizer]),
+        RaffleError::WrongRaffle,
+    );
+}
+
+#[test]
+fn sweep_mrft_backing_waits_for_settled_refunds_and_the_delay() {
+    let mut f = Fixture::new();
+    let (raffle, buyer, ticket) = raffle_with_buyer(&mut f, 10, 1);
+    let usdc = f.usdc;
+    let vault = f.set_ata(&refund_vault_authority(), &usdc, 0);
+    let keeper = f.user(0);
+    f.patch_raffle(&raffle, |r| r.refund_mode = 1);
+
+    f.warp_to(START_TS + DAY + 1);
+    assert_raffle_err(
+        f.send(&[sweep_mrft_backing(&raffle, &keeper.pubkey(), &vault)], &[&keeper]),
+        RaffleError::WrongStatus,
+    );
+    f.ok(&[claim_refund(&raffle, &buyer.pubkey(), &ticket)], &[&buyer]);
+    assert_raffle_err(
+        f.send(&[sweep_mrft_backing(&raffle, &keeper.pubkey(), &vault)], &[&keeper]),
+        RaffleError::SweepTooEarly,
+    );
+
+    f.warp_to(START_TS + DAY + MRFT_BACKING_SWEEP_DELAY_SECS);
+    f.ok(&[sweep_mrft_backing(&raffle, &keeper.pubkey(), &vault)], &[&keeper]);
+    assert_raffle_err(
+        f.send(&[sweep_mrft_backing(&raffle, &keeper.pubkey(), &vault)], &[&keeper]),
+        RaffleError::AlreadySwept,
+    );
+}
+
+#[test]
+fn migrate_rejects_non_raffle_accounts() {
+    let mut f = Fixture::new();
+    let organizer = f.organizer.insecure_clone();
+    let (_, _, ticket) = raffle_with_buyer(&mut f, 10, 1);
+    assert_raffle_err(
+        f.send(&[migrate_raffle(&ticket, &organizer.pubkey())], &[&organizer]),
+        RaffleError::UnknownRaffleLayout,
+    );
+}
@@ -0,0 +1,37 @@
+//! Token-gated entry: only wallets holding enough of the gate token may buy.
+//! (Collection gates need the `metaplex` build and are not covered here.)
+
+mod common;
+
+use common::*;
+use rwa_raffle::RaffleError;
+use solana_sdk::signature::Signer;
+
+const MOGA: u64 = 1_000_000_000; // 9 decimals
+
+#[test]
+fn balance_gate_admits_holders_only() {
+    let mut f = Fixture::new();
+    let organizer = f.organizer.insecure_clone();
+    let raffle = f.create_raffle(RaffleParams::default());
+    let moga = f.create_mint(9);
+    f.ok(&[set_gate(&raffle, &organizer.pubkey(), 1, &moga, 100 * MOGA)], &[&organizer]);
+    let state = f.raffle(&raffle);
+    assert_eq!((state.gate_mode, state.gate_mint, state.gate_min_amount), (1, moga, 100 * MOGA));
+
+    let whale = f.user(2);
+    let whale_moga = f.set_ata(&whale.pubkey(), &moga, 150 * MOGA);
+    f.ok(&[gated_deposit(&raffle, &whale.pubkey(), 2 * USDC, 1, &whale_moga)], &[&whale]);
+    assert_eq!(f.raffle(&raffle).tickets_sold, 2);
+
+    // Holding the gate token does not spend it
+    assert_eq!(f.token_balance(&whale_moga), 150 * MOGA);
+
+    let minnow = f.user(1);
+    let minnow_moga = f.set_ata(&minnow.pubkey(), &moga, 99 * MOGA);
+    assert_raffle_err(
+        f.send(&[gated_deposit(&raffle, &minnow.pubkey(), USDC, 3, &minnow_moga)], &[&minnow]),
+        RaffleError::GateNotMet,
+    );
+    f.audit(&raffle);
+}
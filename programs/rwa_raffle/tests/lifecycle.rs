@@ -0,0 +1,324 @@
+//! Happy path: init → set prize → deposits → threshold → draw → settle → claim_win →
+//! claim_prize → collect_proceeds → close.
+
+mod common;
+
+use common::*;
+use rwa_raffle::{
+    RaffleError, RaffleStatus, Ticket, TicketV0, DEFAULT_CLAIM_WINDOW_SECS, KEEPER_BOUNTY_LAMPORTS, RAFFLE_VERSION,
+};
+use solana_sdk::signature::{Keypair, Signer};
+
+#[test]
+fn full_lifecycle_pays_winner_and_organizer() {
+    let mut f = Fixture::new();
+    let organizer = f.organizer.insecure_clone();
+    let platform = f.platform.insecure_clone();
+    let raffle = f.create_raffle(RaffleParams { crank_reserve: 100_000, ..Default::default() });
+
+    let state = f.raffle(&raffle);
+    assert_eq!(state.version, RAFFLE_VERSION);
+    assert_eq!(state.status, RaffleStatus::Selling as u8);
+    assert_eq!(state.next_ticket_index, 1);
+    assert_eq!(state.crank_reserve, 100_000);
+    f.audit(&raffle);
+
+    let (prize_mint, prize_escrow) = f.set_prize(&raffle);
+    assert_eq!(f.token_balance(&prize_escrow), 1);
+
+    // Alice holds tickets 1..=4, Bob 5..=10; the last deposit fills the raffle
+    let alice = f.user(4);
+    let bob = f.user(6);
+    let alice_ticket = f.buy(&raffle, &alice, 4);
+    assert_eq!(f.raffle(&raffle).status, RaffleStatus::Selling as u8);
+    let bob_ticket = f.buy(&raffle, &bob, 6);
+
+    let state = f.raffle(&raffle);
+    assert_eq!(state.status, RaffleStatus::Drawing as u8);
+    assert_eq!(state.tickets_sold, 10);
+    assert_eq!(state.open_tickets, 10);
+    assert_eq!(f.token_balance(&raffle.escrow), 10 * USDC);
+    f.audit(&raffle);
+    let ticket = f.ticket(&bob_ticket);
+    assert_eq!((ticket.start, ticket.count), (5, 6));
+
+    // Any keeper may request the draw and earns the bounty once
+    let keeper = Keypair::new();
+    f.svm.airdrop(&keeper.pubkey(), SOL).unwrap();
+    let before = f.lamports(&keeper.pubkey());
+    f.ok(&[request_draw(&raffle, &keeper.pubkey())], &[&organizer, &keeper]);
+    assert_eq!(f.lamports(&keeper.pubkey()), before + KEEPER_BOUNTY_LAMPORTS);
+    assert!(f.raffle(&raffle).draw_requested);
+
+    f.ok(&[request_draw(&raffle, &keeper.pubkey())], &[&organizer, &keeper]);
+    assert_eq!(f.lamports(&keeper.pubkey()), before + KEEPER_BOUNTY_LAMPORTS);
+    assert_eq!(f.raffle(&raffle).crank_reserve, 100_000 - KEEPER_BOUNTY_LAMPORTS);
+
+    f.ok(&[settle_draw(&raffle, &platform.pubkey(), 7)], &[&platform]);
+    let state = f.raffle(&raffle);
+    assert_eq!(state.status, RaffleStatus::Completed as u8);
+    assert_eq!(state.winner_ticket, 7);
+    f.audit(&raffle);
+
+    // Alice's range does not contain ticket 7
+    assert_raffle_err(
+        f.send(&[claim_win(&raffle, &alice.pubkey(), &alice_ticket)], &[&alice]),
+        RaffleError::NotWinningTicket,
+    );
+
+    f.ok(&[claim_win(&raffle, &bob.pubkey(), &bob_ticket)], &[&bob]);
+    assert!(f.ticket(&bob_ticket).claimed_win);
+
+    let bob_prize_ata = f.set_ata(&bob.pubkey(), &prize_mint, 0);
+    f.ok(
+        &[claim_prize(&raffle, &bob.pubkey(), &bob_ticket, &prize_mint, &prize_escrow, &bob_prize_ata)],
+        &[&bob],
+    );
+    assert_eq!(f.token_balance(&bob_prize_ata), 1);
+    assert_eq!(f.token_balance(&prize_escrow), 0);
+    assert!(f.raffle(&raffle).prize_claimed);
+    f.audit(&raffle);
+
+    let organizer_ata = f.set_ata(&organizer.pubkey(), &raffle.mint, 0);
+    f.ok(&[collect_proceeds(&raffle, &organizer_ata)], &[&organizer]);
+    assert_eq!(f.token_balance(&organizer_ata), 10 * USDC);
+    assert_eq!(f.token_balance(&raffle.escrow), 0);
+    assert!(f.raffle(&raffle).is_settled());
+    f.audit(&raffle);
+
+    // Permissionless sweep: ticket rent back to buyers, the rest to the organizer
+    let alice_lamports = f.lamports(&alice.pubkey());
+    f.ok(
+        &[
+            close_ticket(&raffle, &keeper.pubkey(), &alice.pubkey(), &alice_ticket),
+            close_ticket(&raffle, &keeper.pubkey(), &bob.pubkey(), &bob_ticket),
+            close_slots(&raffle, &keeper.pubkey()),
+        ],
+        &[&keeper],
+    );
+    assert!(f.lamports(&alice.pubkey()) > alice_lamports);
+    assert!(!f.exists(&alice_ticket));
+    assert!(!f.exists(&raffle.slots));
+    assert_eq!(f.raffle(&raffle).open_tickets, 0);
+
+    let organizer_lamports = f.lamports(&organizer.pubkey());
+    f.ok(&[close_raffle(&raffle, &keeper.pubkey())], &[&keeper]);
+    assert!(!f.exists(&raffle.key));
+    assert!(!f.exists(&raffle.escrow));
+    assert!(f.lamports(&organizer.pubkey()) > organizer_lamports);
+}
+
+#[test]
+fn auto_draw_moves_to_drawing_on_last_ticket() {
+    let mut f = Fixture::new();
+    let raffle = f.create_raffle(RaffleParams { required_tickets: 3, auto_draw: true, ..Default::default() });
+    let alice = f.user(3);
+
+    f.buy(&raffle, &alice, 1);
+    f.buy(&raffle, &alice, 2);
+
+    let state = f.raffle(&raffle);
+    assert_eq!(state.status, RaffleStatus::Drawing as u8);
+    assert_eq!(state.next_ticket_index, 4);
+}
+
+#[test]
+fn scheduled_reveal_blocks_draw_until_reveal_time() {
+    let mut f = Fixture::new();
+    let organizer = f.organizer.insecure_clone();
+    let platform = f.platform.insecure_clone();
+    let reveal = START_TS + DAY / 2;
+    let raffle = f.create_raffle(RaffleParams { required_tickets: 2, reveal_time: Some(reveal), ..Default::default() });
+    let alice = f.user(2);
+    f.buy(&raffle, &alice, 2);
+    assert_eq!(f.raffle(&raffle).status, RaffleStatus::Drawing as u8);
+
+    assert_raffle_err(
+        f.send(&[request_draw(&raffle, &organizer.pubkey())], &[&organizer]),
+        RaffleError::RevealTimeNotReached,
+    );
+    assert_raffle_err(
+        f.send(&[settle_draw(&raffle, &platform.pubkey(), 1)], &[&platform]),
+        RaffleError::RevealTimeNotReached,
+    );
+
+    f.warp_to(reveal);
+    f.ok(&[request_draw(&raffle, &organizer.pubkey())], &[&organizer]);
+    f.ok(&[settle_draw(&raffle, &platform.pubkey(), 2)], &[&platform]);
+    assert_eq!(f.raffle(&raffle).winner_ticket, 2);
+}
+
+#[test]
+fn transferred_ticket_wins_for_new_owner() {
+    let mut f = Fixture::new();
+    let platform = f.platform.insecure_clone();
+    let raffle = f.create_raffle(RaffleParams { required_tickets: 4, ..Default::default() });
+    let (prize_mint, prize_escrow) = f.set_prize(&raffle);
+    let alice = f.user(3);
+    let bob = f.user(1);
+    let carol = f.user(0);
+    let alice_ticket = f.buy(&raffle, &alice, 3);
+
+    let (ix, carol_ticket) = transfer_ticket(&raffle, &alice.pubkey(), &alice_ticket, 1, &carol.pubkey(), &alice.pubkey());
+    f.ok(&[ix], &[&alice]);
+    assert!(!f.exists(&alice_ticket));
+    let ticket = f.ticket(&carol_ticket);
+    assert_eq!((ticket.owner, ticket.start, ticket.count), (carol.pubkey(), 1, 3));
+    // Alice paid for the new ticket too, so its rent will come back to her
+    assert_eq!(ticket.payer, alice.pubkey());
+    let slots = f.slots(&raffle);
+    assert!((1..=3).all(|n| slots.owner_of(n) == Some(carol.pubkey())));
+
+    // Only the current owner can move it, and never to themselves
+    let (ix, _) = transfer_ticket(&raffle, &bob.pubkey(), &carol_ticket, 1, &bob.pubkey(), &alice.pubkey());
+    assert_raffle_err(f.send(&[ix], &[&bob]), RaffleError::Unauthorized);
+    let (ix, _) = transfer_ticket(&raffle, &carol.pubkey(), &carol_ticket, 1, &carol.pubkey(), &alice.pubkey());
+    assert_raffle_err(f.send(&[ix], &[&carol]), RaffleError::InvalidRecipient);
+
+    f.buy(&raffle, &bob, 1);
+    f.ok(&[settle_draw(&raffle, &platform.pubkey(), 2)], &[&platform]);
+    assert_raffle_err(
+        f.send(&[claim_win(&raffle, &alice.pubkey(), &carol_ticket)], &[&alice]),
+        RaffleError::Unauthorized,
+    );
+    f.ok(&[claim_win(&raffle, &carol.pubkey(), &carol_ticket)], &[&carol]);
+    let carol_prize_ata = f.set_ata(&carol.pubkey(), &prize_mint, 0);
+    f.ok(
+        &[claim_prize(&raffle, &carol.pubkey(), &carol_ticket, &prize_mint, &prize_escrow, &carol_prize_ata)],
+        &[&carol],
+    );
+    assert_eq!(f.token_balance(&carol_prize_ata), 1);
+
+    // Positions freeze once the raffle completes
+    let (ix, _) = transfer_ticket(&raffle, &carol.pubkey(), &carol_ticket, 1, &alice.pubkey(), &alice.pubkey());
+    assert_raffle_err(f.send(&[ix], &[&carol]), RaffleError::TicketNotTransferable);
+    f.audit(&raffle);
+}
+
+#[test]
+fn offchain_prize_raffle_closes_after_claim_win() {
+    let mut f = Fixture::new();
+    let organizer = f.organizer.insecure_clone();
+    let platform = f.platform.insecure_clone();
+    let raffle = f.create_raffle(RaffleParams { required_tickets: 2, ..Default::default() });
+    let alice = f.user(2);
+    let alice_ticket = f.buy(&raffle, &alice, 2);
+    f.ok(&[settle_draw(&raffle, &platform.pubkey(), 1)], &[&platform]);
+
+    let organizer_ata = f.set_ata(&organizer.pubkey(), &raffle.mint, 0);
+    f.ok(&[collect_proceeds(&raffle, &organizer_ata)], &[&organizer]);
+    assert!(!f.raffle(&raffle).is_settled());
+    f.ok(&[claim_win(&raffle, &alice.pubkey(), &alice_ticket)], &[&alice]);
+    assert!(f.raffle(&raffle).is_settled());
+
+    let keeper = Keypair::new();
+    f.svm.airdrop(&keeper.pubkey(), SOL).unwrap();
+    f.ok(
+        &[
+            close_ticket(&raffle, &keeper.pubkey(), &alice.pubkey(), &alice_ticket),
+            close_slots(&raffle, &keeper.pubkey()),
+            close_raffle(&raffle, &keeper.pubkey()),
+        ],
+        &[&keeper],
+    );
+    assert!(!f.exists(&raffle.key));
+}
+
+#[test]
+fn slot_join_winner_claims_win_and_prize() {
+    let mut f = Fixture::new();
+    let platform = f.platform.insecure_clone();
+    let raffle = f.create_raffle(RaffleParams { required_tickets: 4, ..Default::default() });
+    let (prize_mint, prize_escrow) = f.set_prize(&raffle);
+    let alice = f.user(3);
+    let bob = f.user(1);
+    f.buy(&raffle, &bob, 1);
+    let alice_ticket = f.buy(&raffle, &alice, 3);
+    let alice_ticket = f.make_slot_ticket(&alice_ticket);
+
+    f.ok(&[settle_draw(&raffle, &platform.pubkey(), 3)], &[&platform]);
+    f.ok(&[claim_win(&raffle, &alice.pubkey(), &alice_ticket)], &[&alice]);
+    let alice_prize_ata = f.set_ata(&alice.pubkey(), &prize_mint, 0);
+    f.ok(
+        &[claim_prize(&raffle, &alice.pubkey(), &alice_ticket, &prize_mint, &prize_escrow, &alice_prize_ata)],
+        &[&alice],
+    );
+    assert_eq!(f.token_balance(&alice_prize_ata), 1);
+    f.audit(&raffle);
+}
+
+#[test]
+fn unclaimed_win_is_forfeited_and_redrawn_without_the_owner() {
+    let mut f = Fixture::new();
+    let platform = f.platform.insecure_clone();
+    let raffle = f.create_raffle(RaffleParams { required_tickets: 4, crank_reserve: 100_000, ..Default::default() });
+    assert_eq!(f.raffle(&raffle).claim_window_secs, DEFAULT_CLAIM_WINDOW_SECS);
+    let (prize_mint, prize_escrow) = f.set_prize(&raffle);
+    let alice = f.user(2);
+    let bob = f.user(2);
+    let alice_ticket = f.buy(&raffle, &alice, 2);
+    let bob_ticket = f.buy(&raffle, &bob, 2);
+    f.ok(&[settle_draw(&raffle, &platform.pubkey(), 1)], &[&platform]);
+
+    let keeper = Keypair::new();
+    f.svm.airdrop(&keeper.pubkey(), SOL).unwrap();
+    assert_raffle_err(
+        f.send(&[forfeit_winner(&raffle, &keeper.pubkey())], &[&keeper]),
+        RaffleError::ClaimWindowOpen,
+    );
+
+    // Alice sits out her window: anyone may forfeit the win and reopen the draw
+    f.warp_to(START_TS + DEFAULT_CLAIM_WINDOW_SECS + 1);
+    assert_raffle_err(
+        f.send(&[claim_win(&raffle, &alice.pubkey(), &alice_ticket)], &[&alice]),
+        RaffleError::ClaimWindowExpired,
+    );
+    let before = f.lamports(&keeper.pubkey());
+    f.ok(&[forfeit_winner(&raffle, &keeper.pubkey())], &[&keeper]);
+    assert_eq!(f.lamports(&keeper.pubkey()), before + KEEPER_BOUNTY_LAMPORTS);
+    let state = f.raffle(&raffle);
+    assert_eq!(state.status, RaffleStatus::Drawing as u8);
+    assert_eq!(state.winner_ticket, 0);
+    assert_eq!(state.forfeited_owners, vec![alice.pubkey()]);
+
+    // Her tickets are out of the redraw and can't be moved to dodge that
+    let (ix, _) = transfer_ticket(&raffle, &alice.pubkey(), &alice_ticket, 1, &bob.pubkey(), &alice.pubkey());
+    assert_raffle_err(f.send(&[ix], &[&alice]), RaffleError::TicketNotTransferable);
+    f.ok(&[request_draw(&raffle, &keeper.pubkey())], &[&keeper]);
+    assert_raffle_err(f.send(&[settle_draw(&raffle, &platform.pubkey(), 2)], &[&platform]), RaffleError::ExcludedWinner);
+    f.ok(&[settle_draw(&raffle, &platform.pubkey(), 3)], &[&platform]);
+
+    f.ok(&[claim_win(&raffle, &bob.pubkey(), &bob_ticket)], &[&bob]);
+    assert!(f.raffle(&raffle).win_claimed);
+    let bob_prize_ata = f.set_ata(&bob.pubkey(), &prize_mint, 0);
+    f.ok(
+        &[claim_prize(&raffle, &bob.pubkey(), &bob_ticket, &prize_mint, &prize_escrow, &bob_prize_ata)],
+        &[&bob],
+    );
+    assert_eq!(f.token_balance(&bob_prize_ata), 1);
+
+    // A claimed win is final
+    f.warp_to(START_TS + 3 * DEFAULT_CLAIM_WINDOW_SECS);
+    assert_raffle_err(
+        f.send(&[forfeit_winner(&raffle, &keeper.pubkey())], &[&keeper]),
+        RaffleError::AlreadyClaimedWin,
+    );
+    f.audit(&raffle);
+}
+
+#[test]
+fn legacy_ticket_migrates_with_its_owner_as_rent_payer() {
+    let mut f = Fixture::new();
+    let organizer = f.organizer.insecure_clone();
+    let raffle = f.create_raffle(RaffleParams::default());
+    let alice = f.user(2);
+    let ticket = f.buy(&raffle, &alice, 2);
+
+    f.make_legacy_ticket(&ticket, false);
+    assert_eq!(f.svm.get_account(&ticket).unwrap().data.len(), 8 + TicketV0::LEN);
+
+    f.ok(&[migrate_ticket(&ticket, &raffle.key, &organizer.pubkey())], &[&organizer]);
+    let state = f.ticket(&ticket);
+    assert_eq!((state.owner, state.payer, state.start, state.count), (alice.pubkey(), alice.pubkey(), 1, 2));
+    assert_eq!(f.svm.get_account(&ticket).unwrap().data.len(), 8 + Ticket::LEN);
+}
@@ -0,0 +1,67 @@
+//! Allowlist presale: allowlisted wallets buy within their allocation until the presale
+//! ends, then the raffle is public.
+
+mod common;
+
+use common::*;
+use rwa_raffle::RaffleError;
+use solana_sdk::signature::{Keypair, Signer};
+
+#[test]
+fn presale_then_public_sale() {
+    let mut f = Fixture::new();
+    let raffle = f.create_raffle(RaffleParams::default());
+    let alice = f.user(5);
+    let bob = f.user(2);
+    let carol = f.user(2);
+    let presale_end = START_TS + DAY / 2;
+    let allowlist = f.open_presale(&raffle, vec![(alice.pubkey(), 3), (bob.pubkey(), 2)], presale_end);
+
+    let state = f.raffle(&raffle);
+    assert_eq!(state.allowlist_root, allowlist.root());
+    assert_eq!(state.presale_end_unix_ts, presale_end);
+
+    // Allowlisted wallets spend their allocation across several deposits
+    let alice_allocation = f.register(&raffle, &alice, &allowlist);
+    f.presale_buy(&raffle, &alice, 2);
+    f.presale_buy(&raffle, &alice, 1);
+    let allocation = f.allocation(&alice_allocation);
+    assert_eq!((allocation.max_allocation, allocation.used), (3, 3));
+    assert_raffle_err(
+        f.send(&[presale_deposit(&raffle, &alice.pubkey(), USDC, 4)], &[&alice]),
+        RaffleError::AllocationExceeded,
+    );
+
+    f.register(&raffle, &bob, &allowlist);
+    f.presale_buy(&raffle, &bob, 2);
+    f.audit(&raffle);
+
+    // Carol is not on the list
+    assert_raffle_err(f.send(&[deposit(&raffle, &carol.pubkey(), USDC, 6)], &[&carol]), RaffleError::NotAllowlisted);
+
+    // Public sale: anyone buys, allocations no longer apply
+    f.warp_to(presale_end);
+    f.buy(&raffle, &carol, 2);
+    f.buy(&raffle, &alice, 2);
+    assert_eq!(f.raffle(&raffle).tickets_sold, 9);
+    assert_eq!(f.allocation(&alice_allocation).used, 3);
+    f.audit(&raffle);
+
+    // Spent allocations can be swept; rent goes back to the wallet
+    let keeper = Keypair::new();
+    f.svm.airdrop(&keeper.pubkey(), SOL).unwrap();
+    let alice_lamports = f.lamports(&alice.pubkey());
+    f.ok(&[close_allocation(&raffle, &keeper.pubkey(), &alice.pubkey())], &[&keeper]);
+    assert!(!f.exists(&alice_allocation));
+    assert!(f.lamports(&alice.pubkey()) > alice_lamports);
+}
+
+#[test]
+fn raffles_without_allowlist_ignore_allocations() {
+    let mut f = Fixture::new();
+    let raffle = f.create_raffle(RaffleParams::default());
+    let alice = f.user(2);
+    assert!(!f.raffle(&raffle).is_presale(f.now()));
+    f.buy(&raffle, &alice, 2);
+    assert_eq!(f.raffle(&raffle).tickets_sold, 2);
+}
@@ -0,0 +1,356 @@
+//! Deadline → refund path: unfilled raffles refund every buyer in USDC, either
+//! self-service (`claim_refund`) or via the keeper crank (`refund_batch`). MRFT-refund
+//! raffles close once their unredeemed backing is swept to the refund vault.
+//!
+//! On-chain MRFT minting (`claim_refund` with the `MrftMint` accounts) needs the `bubblegum`
+//! build plus the Bubblegum, Account Compression, Noop and Token Metadata programs, none
+//! of which this suite loads; here it only checks the default build refuses it cleanly.
+
+mod common;
+
+use common::*;
+use rwa_raffle::{RaffleError, RaffleStatus, KEEPER_BOUNTY_LAMPORTS, KEEPER_REFUND_BOUNTY_LAMPORTS, MRFT_BACKING_SWEEP_DELAY_SECS};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+
+#[test]
+fn unfilled_raffle_refunds_every_ticket() {
+    let mut f = Fixture::new();
+    let organizer = f.organizer.insecure_clone();
+    let raffle = f.create_raffle(RaffleParams { crank_reserve: 100_000, ..Default::default() });
+
+    let alice = f.user(3);
+    let bob = f.user(2);
+    let carol = f.user(1);
+    let alice_ticket = f.buy(&raffle, &alice, 3);
+    let bob_ticket = f.buy(&raffle, &bob, 2);
+    let carol_ticket = f.buy(&raffle, &carol, 1);
+    assert_eq!(f.token_balance(&raffle.escrow), 6 * USDC);
+    f.audit(&raffle);
+
+    // Nothing is refundable before the deadline
+    assert_raffle_err(
+        f.send(&[claim_refund(&raffle, &alice.pubkey(), &alice_ticket)], &[&alice]),
+        RaffleError::NotRefundableYet,
+    );
+    let keeper = Keypair::new();
+    f.svm.airdrop(&keeper.pubkey(), SOL).unwrap();
+    assert_raffle_err(
+        f.send(&[start_refunding(&raffle, &keeper.pubkey())], &[&organizer, &keeper]),
+        RaffleError::NotRefundableYet,
+    );
+
+    f.warp_to(START_TS + DAY + 1);
+    let keeper_lamports = f.lamports(&keeper.pubkey());
+    f.ok(&[start_refunding(&raffle, &keeper.pubkey())], &[&organizer, &keeper]);
+    assert_eq!(f.raffle(&raffle).status, RaffleStatus::Refunding as u8);
+    assert_eq!(f.lamports(&keeper.pubkey()), keeper_lamports + KEEPER_BOUNTY_LAMPORTS);
+
+    // Deposits are closed for good
+    assert_raffle_err(
+        f.send(&[deposit(&raffle, &alice.pubkey(), USDC, 7)], &[&alice]),
+        RaffleError::RaffleNotSelling,
+    );
+
+    // Alice refunds herself
+    f.ok(&[claim_refund(&raffle, &alice.pubkey(), &alice_ticket)], &[&alice]);
+    assert_eq!(f.token_balance(&f.usdc_ata(&alice.pubkey())), 3 * USDC);
+    assert!(f.ticket(&alice_ticket).refunded);
+    f.audit(&raffle);
+    assert_raffle_err(
+        f.send(&[claim_refund(&raffle, &alice.pubkey(), &alice_ticket)], &[&alice]),
+        RaffleError::AlreadyRefunded,
+    );
+
+    // The keeper refunds the rest; Alice's already-refunded ticket is skipped
+    let keeper_lamports = f.lamports(&keeper.pubkey());
+    let pairs = [
+        (alice_ticket, f.usdc_ata(&alice.pubkey())),
+        (bob_ticket, f.usdc_ata(&bob.pubkey())),
+        (carol_ticket, f.usdc_ata(&carol.pubkey())),
+    ];
+    f.ok(&[refund_batch(&raffle, &keeper.pubkey(), &pairs)], &[&organizer, &keeper]);
+    assert_eq!(f.lamports(&keeper.pubkey()), keeper_lamports + 2 * KEEPER_REFUND_BOUNTY_LAMPORTS);
+    assert_eq!(f.token_balance(&f.usdc_ata(&alice.pubkey())), 3 * USDC);
+    assert_eq!(f.token_balance(&f.usdc_ata(&bob.pubkey())), 2 * USDC);
+    assert_eq!(f.token_balance(&f.usdc_ata(&carol.pubkey())), USDC);
+    assert_eq!(f.token_balance(&raffle.escrow), 0);
+    f.audit(&raffle);
+
+    let state = f.raffle(&raffle);
+    assert_eq!(state.tickets_refunded, 6);
+    assert!(state.is_settled());
+
+    // Everything can be closed once every ticket is refunded
+    f.ok(
+        &[
+            close_ticket(&raffle, &keeper.pubkey(), &alice.pubkey(), &alice_ticket),
+            close_ticket(&raffle, &keeper.pubkey(), &bob.pubkey(), &bob_ticket),
+            close_ticket(&raffle, &keeper.pubkey(), &carol.pubkey(), &carol_ticket),
+            close_slots(&raffle, &keeper.pubkey()),
+            close_raffle(&raffle, &keeper.pubkey()),
+        ],
+        &[&keeper],
+    );
+    assert!(!f.exists(&raffle.key));
+}
+
+#[test]
+fn slot_join_tickets_close_so_the_raffle_can() {
+    let mut f = Fixture::new();
+    let organizer = f.organizer.insecure_clone();
+    let raffle = f.create_raffle(RaffleParams::default());
+    let alice = f.user(2);
+    let bob = f.user(1);
+    let alice_ticket = f.buy(&raffle, &alice, 2);
+    let alice_ticket = f.make_slot_ticket(&alice_ticket);
+    let bob_ticket = f.buy(&raffle, &bob, 1);
+    let other_mint = f.create_mint(USDC_DECIMALS);
+    let other = f.create_raffle_for_mint(&other_mint, RaffleParams::default());
+
+    f.warp_to(START_TS + DAY + 1);
+    let keeper = Keypair::new();
+    f.svm.airdrop(&keeper.pubkey(), SOL).unwrap();
+    f.ok(&[start_refunding(&raffle, &keeper.pubkey())], &[&organizer, &keeper]);
+    let pairs = [(alice_ticket, f.usdc_ata(&alice.pubkey())), (bob_ticket, f.usdc_ata(&bob.pubkey()))];
+    f.ok(&[refund_batch(&raffle, &keeper.pubkey(), &pairs)], &[&organizer, &keeper]);
+    assert!(f.raffle(&raffle).is_settled());
+
+    // A ticket is only closed against its own raffle, whatever its PDA layout
+    assert_raffle_err(
+        f.send(&[close_ticket(&other, &keeper.pubkey(), &alice.pubkey(), &alice_ticket)], &[&keeper]),
+        RaffleError::WrongRaffle,
+    );
+
+    f.ok(
+        &[
+            close_ticket(&raffle, &keeper.pubkey(), &alice.pubkey(), &alice_ticket),
+            close_ticket(&raffle, &keeper.pubkey(), &bob.pubkey(), &bob_ticket),
+            close_slots(&raffle, &keeper.pubkey()),
+            close_raffle(&raffle, &keeper.pubkey()),
+        ],
+        &[&keeper],
+    );
+    assert!(!f.exists(&alice_ticket));
+    assert!(!f.exists(&raffle.key));
+}
+
+#[test]
+fn slot_join_ticket_claims_its_own_refund() {
+    let mut f = Fixture::new();
+    let raffle = f.create_raffle(RaffleParams::default());
+    let alice = f.user(2);
+    let alice_ticket = f.buy(&raffle, &alice, 2);
+    let alice_ticket = f.make_slot_ticket(&alice_ticket);
+
+    f.warp_to(START_TS + DAY + 1);
+    f.ok(&[claim_refund(&raffle, &alice.pubkey(), &alice_ticket)], &[&alice]);
+    assert_eq!(f.token_balance(&f.usdc_ata(&alice.pubkey())), 2 * USDC);
+    assert!(f.ticket(&alice_ticket).refunded);
+    f.audit(&raffle);
+}
+
+#[test]
+fn first_claim_after_deadline_locks_refunding() {
+    let mut f = Fixture::new();
+    let raffle = f.create_raffle(RaffleParams::default());
+    let alice = f.user(2);
+    let ticket = f.buy(&raffle, &alice, 2);
+
+    f.warp_to(START_TS + DAY + 1);
+    f.ok(&[claim_refund(&raffle, &alice.pubkey(), &ticket)], &[&alice]);
+
+    let state = f.raffle(&raffle);
+    assert_eq!(state.status, RaffleStatus::Refunding as u8);
+    assert_eq!(state.tickets_refunded, 2);
+    assert_eq!(f.token_balance(&f.usdc_ata(&alice.pubkey())), 2 * USDC);
+    f.audit(&raffle);
+}
+
+#[test]
+fn exhausted_crank_reserve_pays_nothing() {
+    let mut f = Fixture::new();
+    let organizer = f.organizer.insecure_clone();
+    let raffle = f.create_raffle(RaffleParams::default());
+    let alice = f.user(1);
+    f.buy(&raffle, &alice, 1);
+
+    f.warp_to(START_TS + DAY + 1);
+    let keeper = Keypair::new();
+    f.svm.airdrop(&keeper.pubkey(), SOL).unwrap();
+    let before = f.lamports(&keeper.pubkey());
+    f.ok(&[start_refunding(&raffle, &keeper.pubkey())], &[&organizer, &keeper]);
+    assert_eq!(f.lamports(&keeper.pubkey()), before);
+    assert_eq!(f.raffle(&raffle).status, RaffleStatus::Refunding as u8);
+}
+
+#[test]
+fn gifted_ticket_refunds_to_beneficiary() {
+    let mut f = Fixture::new();
+    let raffle = f.create_raffle(RaffleParams::default());
+    let sponsor = f.user(3);
+    let alice = f.user(0);
+    let ticket = f.gift(&raffle, &sponsor, &alice.pubkey(), 3);
+
+    let state = f.ticket(&ticket);
+    assert_eq!((state.owner, state.start, state.count), (alice.pubkey(), 1, 3));
+    assert_eq!(f.slots(&raffle).owner_of(1), Some(alice.pubkey()));
+    assert_eq!(f.token_balance(&f.usdc_ata(&sponsor.pubkey())), 0);
+    f.audit(&raffle);
+
+    // The sponsor paid but holds no ticket of their own
+    f.warp_to(START_TS + DAY + 1);
+    assert_raffle_err(
+        f.send(&[claim_refund(&raffle, &sponsor.pubkey(), &ticket)], &[&sponsor]),
+        RaffleError::Unauthorized,
+    );
+
+    f.ok(&[claim_refund(&raffle, &alice.pubkey(), &ticket)], &[&alice]);
+    assert_eq!(f.token_balance(&f.usdc_ata(&alice.pubkey())), 3 * USDC);
+    assert_eq!(f.token_balance(&f.usdc_ata(&sponsor.pubkey())), 0);
+    f.audit(&raffle);
+
+    // The sponsor funded the ticket account, so its rent goes back to them
+    let organizer = f.organizer.insecure_clone();
+    assert_raffle_err(
+        f.send(&[close_ticket(&raffle, &organizer.pubkey(), &alice.pubkey(), &ticket)], &[&organizer]),
+        RaffleError::Unauthorized,
+    );
+    let sponsor_lamports = f.lamports(&sponsor.pubkey());
+    f.ok(&[close_ticket(&raffle, &organizer.pubkey(), &sponsor.pubkey(), &ticket)], &[&organizer]);
+    assert!(f.lamports(&sponsor.pubkey()) > sponsor_lamports);
+}
+
+#[test]
+fn mrft_refund_keeps_the_backing_and_never_half_mints() {
+    let mut f = Fixture::new();
+    let raffle = f.create_raffle(RaffleParams::default());
+    let alice = f.user(2);
+    let bob = f.user(1);
+    let alice_ticket = f.buy(&raffle, &alice, 2);
+    let bob_ticket = f.buy(&raffle, &bob, 1);
+    f.patch_raffle(&raffle, |r| r.refund_mode = 1);
+    f.warp_to(START_TS + DAY + 1);
+
+    // Asking for the MRFTs on-chain without Bubblegum support fails as a whole: the ticket
+    // stays refundable instead of being flagged with nothing minted for it
+    let tree = Pubkey::new_unique();
+    assert_raffle_err(
+        f.send(&[claim_refund_minting(&raffle, &alice.pubkey(), &alice_ticket, &tree)], &[&alice]),
+        RaffleError::InvalidMrftAccounts,
+    );
+    assert!(!f.ticket(&alice_ticket).refunded);
+    assert_eq!(f.raffle(&raffle).tickets_refunded, 0);
+
+    // Without the mint accounts the worker mints from `RefundTicketsRequested`; the USDC
+    // stays in escrow as the MRFTs' backing
+    f.ok(&[claim_refund(&raffle, &alice.pubkey(), &alice_ticket)], &[&alice]);
+    f.ok(&[claim_refund(&raffle, &bob.pubkey(), &bob_ticket)], &[&bob]);
+    assert!(f.ticket(&alice_ticket).refunded);
+    assert_eq!(f.token_balance(&f.usdc_ata(&alice.pubkey())), 0);
+    assert_eq!(f.token_balance(&raffle.escrow), 3 * USDC);
+    let state = f.raffle(&raffle);
+    assert_eq!((state.status, state.tickets_refunded), (RaffleStatus::Refunding as u8, 3));
+    assert!(state.is_settled());
+    assert_raffle_err(
+        f.send(&[claim_refund(&raffle, &alice.pubkey(), &alice_ticket)], &[&alice]),
+        RaffleError::AlreadyRefunded,
+    );
+    f.audit(&raffle);
+}
+
+#[test]
+fn legacy_mrft_refunds_settle_and_close_after_the_sweep() {
+    let mut f = Fixture::new();
+    let organizer = f.organizer.insecure_clone();
+    let raffle = f.create_raffle(RaffleParams::default());
+    let alice = f.user(2);
+    let bob = f.user(1);
+    let alice_ticket = f.buy(&raffle, &alice, 2);
+    let bob_ticket = f.buy(&raffle, &bob, 1);
+
+    // Before versioning, alice's refund only flagged her ticket and left the USDC in escrow
+    f.warp_to(START_TS + DAY + 1);
+    f.make_legacy_raffle(&raffle, RaffleStatus::Refunding as u8);
+    f.make_legacy_ticket(&alice_ticket, true);
+    f.make_legacy_ticket(&bob_ticket, false);
+    f.ok(&[migrate_raffle(&raffle.key, &organizer.pubkey())], &[&organizer]);
+    let state = f.raffle(&raffle);
+    assert_eq!((state.refund_mode, state.tickets_refunded), (1, 0));
+
+    f.ok(
+        &[
+            migrate_ticket(&alice_ticket, &raffle.key, &organizer.pubkey()),
+            migrate_ticket(&bob_ticket, &raffle.key, &organizer.pubkey()),
+        ],
+        &[&organizer],
+    );
+    assert_eq!(f.raffle(&raffle).tickets_refunded, 2);
+    f.ok(&[claim_refund(&raffle, &bob.pubkey(), &bob_ticket)], &[&bob]);
+    assert_eq!(f.token_balance(&f.usdc_ata(&bob.pubkey())), 0);
+    assert!(f.raffle(&raffle).is_settled());
+    f.audit(&raffle);
+
+    // The MRFT backing keeps the escrow open until it is swept
+    let keeper = f.user(0);
+    f.ok(
+        &[
+            close_ticket(&raffle, &keeper.pubkey(), &alice.pubkey(), &alice_ticket),
+            close_ticket(&raffle, &keeper.pubkey(), &bob.pubkey(), &bob_ticket),
+            close_slots(&raffle, &keeper.pubkey()),
+        ],
+        &[&keeper],
+    );
+    assert_raffle_err(
+        f.send(&[close_raffle(&raffle, &keeper.pubkey())], &[&keeper]),
+        RaffleError::EscrowNotEmpty,
+    );
+
+    let usdc = f.usdc;
+    let vault = f.set_ata(&refund_vault_authority(), &usdc, 0);
+    f.warp_to(START_TS + DAY + MRFT_BACKING_SWEEP_DELAY_SECS);
+    f.ok(&[sweep_mrft_backing(&raffle, &keeper.pubkey(), &vault)], &[&keeper]);
+    assert_eq!(f.token_balance(&vault), 3 * USDC);
+    assert_eq!(f.token_balance(&raffle.escrow), 0);
+    f.audit(&raffle);
+
+    f.ok(&[close_raffle(&raffle, &keeper.pubkey())], &[&keeper]);
+    assert!(!f.exists(&raffle.key));
+}
+
+#[test]
+fn platform_authority_withdraws_what_the_refund_vault_keeps() {
+    let mut f = Fixture::new();
+    let platform = f.platform.insecure_clone();
+    let organizer = f.organizer.insecure_clone();
+    let usdc = f.usdc;
+    // Haircuts and swept backing land here; a top-up is a plain transfer
+    let vault = f.set_ata(&refund_vault_authority(), &usdc, 5 * USDC);
+    let treasury = f.set_ata(&platform.pubkey(), &usdc, 0);
+
+    assert_raffle_err(
+        f.send(&[withdraw_refund_vault(&organizer.pubkey(), &usdc, &vault, &treasury, USDC)], &[&organizer]),
+        RaffleError::Unauthorized,
+    );
+    assert_raffle_err(
+        f.send(&[withdraw_refund_vault(&platform.pubkey(), &usdc, &vault, &treasury, 0)], &[&platform]),
+        RaffleError::InvalidAmount,
+    );
+    f.ok(&[withdraw_refund_vault(&platform.pubkey(), &usdc, &vault, &treasury, 2 * USDC)], &[&platform]);
+    assert_eq!((f.token_balance(&vault), f.token_balance(&treasury)), (3 * USDC, 2 * USDC));
+
+    // Handing the authority over locks the old wallet out
+    let successor = f.user(0);
+    assert_raffle_err(
+        f.send(&[set_platform_authority(&organizer.pubkey(), &organizer.pubkey())], &[&organizer]),
+        RaffleError::Unauthorized,
+    );
+    f.ok(&[set_platform_authority(&platform.pubkey(), &successor.pubkey())], &[&platform]);
+    assert_raffle_err(
+        f.send(&[withdraw_refund_vault(&platform.pubkey(), &usdc, &vault, &treasury, USDC)], &[&platform]),
+        RaffleError::Unauthorized,
+    );
+    let successor_ata = f.set_ata(&successor.pubkey(), &usdc, 0);
+    f.ok(&[withdraw_refund_vault(&successor.pubkey(), &usdc, &vault, &successor_ata, 3 * USDC)], &[&successor]);
+    assert_eq!(f.token_balance(&vault), 0);
+}
@@ -0,0 +1,123 @@
+//! Tokenized tickets: a ticket minted as a Token-2022 NFT trades like any NFT, and claims
+//! and refunds follow whoever holds it. Ticket rent still goes back to its payer.
+
+mod common;
+
+use anchor_spl::token_2022::spl_token_2022::extension::StateWithExtensions;
+use anchor_spl::token_2022::spl_token_2022::state::Mint;
+use common::*;
+use rwa_raffle::{RaffleError, DEFAULT_CLAIM_WINDOW_SECS};
+use solana_sdk::program_option::COption;
+use solana_sdk::signature::{Keypair, Signer};
+
+#[test]
+fn nft_holder_claims_the_prize() {
+    let mut f = Fixture::new();
+    let organizer = f.organizer.insecure_clone();
+    let platform = f.platform.insecure_clone();
+    let raffle = f.create_raffle(RaffleParams { required_tickets: 4, ..Default::default() });
+    let (prize_mint, prize_escrow) = f.set_prize(&raffle);
+    let alice = f.user(3);
+    let bob = f.user(1);
+    let carol = f.user(0);
+    let alice_ticket = f.buy(&raffle, &alice, 3);
+
+    let (ticket_mint, ticket, alice_nft) = f.tokenize(&raffle, &alice, &alice_ticket);
+    assert!(!f.exists(&alice_ticket));
+    assert_eq!(f.nft_balance(&alice_nft), 1);
+    let state = f.ticket(&ticket);
+    assert_eq!((state.owner, state.start, state.count), (ticket_mint, 1, 3));
+    assert_eq!(f.slots(&raffle).owner_of(3), Some(ticket_mint));
+    let mint = f.svm.get_account(&ticket_mint).unwrap();
+    let mint = StateWithExtensions::<Mint>::unpack(&mint.data).unwrap().base;
+    assert_eq!((mint.supply, mint.decimals, mint.mint_authority), (1, 0, COption::None));
+
+    // Alice sells the entry before the draw
+    let carol_nft = f.send_nft(&alice, &ticket_mint, &carol.pubkey());
+    f.buy(&raffle, &bob, 1);
+    f.ok(&[settle_draw(&raffle, &platform.pubkey(), 2)], &[&platform]);
+
+    assert_raffle_err(
+        f.send(&[claim_win_with(&raffle, &alice.pubkey(), &ticket, Some(alice_nft))], &[&alice]),
+        RaffleError::Unauthorized,
+    );
+    assert_raffle_err(f.send(&[claim_win(&raffle, &carol.pubkey(), &ticket)], &[&carol]), RaffleError::Unauthorized);
+    f.ok(&[claim_win_with(&raffle, &carol.pubkey(), &ticket, Some(carol_nft))], &[&carol]);
+
+    let carol_prize_ata = f.set_ata(&carol.pubkey(), &prize_mint, 0);
+    f.ok(
+        &[claim_prize_with(&raffle, &carol.pubkey(), &ticket, &prize_mint, &prize_escrow, &carol_prize_ata, Some(carol_nft))],
+        &[&carol],
+    );
+    assert_eq!(f.token_balance(&carol_prize_ata), 1);
+
+    let organizer_ata = f.set_ata(&organizer.pubkey(), &raffle.mint, 0);
+    f.ok(&[collect_proceeds(&raffle, &organizer_ata)], &[&organizer]);
+    f.audit(&raffle);
+
+    // Ticket rent goes back to Alice, who paid for it, not to the NFT holder
+    let keeper = Keypair::new();
+    f.svm.airdrop(&keeper.pubkey(), SOL).unwrap();
+    assert_eq!(f.ticket(&ticket).payer, alice.pubkey());
+    assert_raffle_err(
+        f.send(&[close_ticket(&raffle, &keeper.pubkey(), &carol.pubkey(), &ticket)], &[&keeper]),
+        RaffleError::Unauthorized,
+    );
+    let alice_lamports = f.lamports(&alice.pubkey());
+    f.ok(&[close_ticket(&raffle, &keeper.pubkey(), &alice.pubkey(), &ticket)], &[&keeper]);
+    assert!(f.lamports(&alice.pubkey()) > alice_lamports);
+}
+
+#[test]
+fn nft_holder_refunds_and_keepers_skip_tokenized_tickets() {
+    let mut f = Fixture::new();
+    let organizer = f.organizer.insecure_clone();
+    let raffle = f.create_raffle(RaffleParams::default());
+    let alice = f.user(2);
+    let carol = f.user(0);
+    let alice_ticket = f.buy(&raffle, &alice, 2);
+    let (ticket_mint, ticket, _) = f.tokenize(&raffle, &alice, &alice_ticket);
+    let carol_nft = f.send_nft(&alice, &ticket_mint, &carol.pubkey());
+
+    f.warp_to(START_TS + DAY + 1);
+    let keeper = Keypair::new();
+    f.svm.airdrop(&keeper.pubkey(), SOL).unwrap();
+    f.ok(&[refund_batch(&raffle, &keeper.pubkey(), &[(ticket, f.usdc_ata(&alice.pubkey()))])], &[&organizer, &keeper]);
+    assert!(!f.ticket(&ticket).refunded);
+    assert_eq!(f.token_balance(&raffle.escrow), 2 * USDC);
+
+    f.ok(&[claim_refund_with(&raffle, &carol.pubkey(), &ticket, Some(carol_nft))], &[&carol]);
+    assert_eq!(f.token_balance(&f.usdc_ata(&carol.pubkey())), 2 * USDC);
+    assert_eq!(f.token_balance(&f.usdc_ata(&alice.pubkey())), 0);
+    f.audit(&raffle);
+}
+
+#[test]
+fn forfeited_nft_win_excludes_only_that_ticket() {
+    let mut f = Fixture::new();
+    let platform = f.platform.insecure_clone();
+    let raffle = f.create_raffle(RaffleParams { required_tickets: 4, crank_reserve: 100_000, ..Default::default() });
+    let alice = f.user(3);
+    let bob = f.user(1);
+    let alice_ticket = f.buy(&raffle, &alice, 2);
+    f.buy(&raffle, &alice, 1);
+    f.buy(&raffle, &bob, 1);
+    let (ticket_mint, _, _) = f.tokenize(&raffle, &alice, &alice_ticket);
+    f.ok(&[settle_draw(&raffle, &platform.pubkey(), 1)], &[&platform]);
+
+    // The slot owner of a tokenized ticket is its mint, so that is what gets recorded
+    let keeper = Keypair::new();
+    f.svm.airdrop(&keeper.pubkey(), SOL).unwrap();
+    f.warp_to(START_TS + DEFAULT_CLAIM_WINDOW_SECS + 1);
+    f.ok(&[forfeit_winner(&raffle, &keeper.pubkey())], &[&keeper]);
+    assert_eq!(f.raffle(&raffle).forfeited_owners, vec![ticket_mint]);
+
+    // Only the NFT's range sits out the redraw; Alice's plain ticket can still win
+    f.ok(&[request_draw(&raffle, &keeper.pubkey())], &[&keeper]);
+    assert_raffle_err(
+        f.send(&[settle_draw(&raffle, &platform.pubkey(), 2)], &[&platform]),
+        RaffleError::ExcludedWinner,
+    );
+    f.ok(&[settle_draw(&raffle, &platform.pubkey(), 3)], &[&platform]);
+    assert_eq!(f.slots(&raffle).owner_of(3), Some(alice.pubkey()));
+}
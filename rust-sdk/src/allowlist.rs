@@ -0,0 +1,51 @@
+//! Presale allowlist Merkle trees.
+//!
+//! Leaves and parents are hashed exactly like the program verifies them
+//! (`rwa_raffle::allowlist_leaf` / `allowlist_node`), so roots and proofs built here
+//! can be passed straight to `set_allowlist` and `register_allowlist`.
+
+use anchor_lang::solana_program::pubkey::Pubkey;
+use rwa_raffle::{allowlist_leaf, allowlist_node};
+
+/// Allowlist of `(wallet, max_allocation)` entries. An odd node out on a layer is
+/// carried up unchanged.
+#[derive(Clone, Debug)]
+pub struct AllowlistTree {
+    entries: Vec<(Pubkey, u64)>,
+    layers: Vec<Vec<[u8; 32]>>,
+}
+
+impl AllowlistTree {
+    /// Build the tree; `entries` must not be empty.
+    pub fn new(entries: Vec<(Pubkey, u64)>) -> Self {
+        assert!(!entries.is_empty(), "allowlist needs at least one entry");
+        let mut layers = vec![entries.iter().map(|(wallet, max)| allowlist_leaf(wallet, *max)).collect::<Vec<_>>()];
+        while layers.last().map_or(0, Vec::len) > 1 {
+            let next = layers[layers.len() - 1]
+                .chunks(2)
+                .map(|pair| if pair.len() == 2 { allowlist_node(&pair[0], &pair[1]) } else { pair[0] })
+                .collect();
+            layers.push(next);
+        }
+        Self { entries, layers }
+    }
+
+    /// Root to pass to `set_allowlist`.
+    pub fn root(&self) -> [u8; 32] {
+        self.layers[self.layers.len() - 1][0]
+    }
+
+    /// `(max_allocation, proof)` for `wallet`, ready for `register_allowlist`.
+    pub fn proof(&self, wallet: &Pubkey) -> Option<(u64, Vec<[u8; 32]>)> {
+        let mut index = self.entries.iter().position(|(w, _)| w == wallet)?;
+        let max_allocation = self.entries[index].1;
+        let mut proof = Vec::new();
+        for layer in &self.layers[..self.layers.len() - 1] {
+            if let Some(sibling) = layer.get(index ^ 1) {
+                proof.push(*sibling);
+            }
+            index /= 2;
+        }
+        Some((max_allocation, proof))
+    }
+}
@@ -0,0 +1,184 @@
+//! `direct_sell` instruction builders.
+
+#[cfg(feature = "pyth-jupiter")]
+use anchor_lang::solana_program::instruction::AccountMeta;
+use anchor_lang::solana_program::{instruction::Instruction, pubkey::Pubkey};
+use anchor_lang::system_program;
+#[cfg(not(feature = "test-bypass"))]
+use anchor_lang::solana_program::sysvar;
+use anchor_spl::token::ID as TOKEN_PROGRAM_ID;
+use direct_sell::{accounts, instruction};
+
+use crate::build_ix;
+use crate::pda::find_listing;
+#[cfg(not(feature = "test-bypass"))]
+use crate::Permit;
+
+/// `create_listing_with_permit`. Must be preceded by the ed25519 instruction for
+/// [`crate::permit::listing_permit_message`] with `direct_sell::ID`.
+/// `listing_nft_escrow` must be owned by the listing PDA.
+#[cfg(not(feature = "test-bypass"))]
+pub fn create_listing_with_permit(
+    seller: &Pubkey,
+    nft_mint: &Pubkey,
+    payment_mint: &Pubkey,
+    seller_nft_ata: &Pubkey,
+    listing_nft_escrow: &Pubkey,
+    price: u64,
+    permit: &Permit,
+) -> Instruction {
+    build_ix(
+        direct_sell::ID,
+        accounts::CreateListingWithPermit {
+            seller: *seller,
+            nft_mint: *nft_mint,
+            payment_mint: *payment_mint,
+            seller_nft_ata: *seller_nft_ata,
+            listing_nft_escrow: *listing_nft_escrow,
+            listing: find_listing(nft_mint, seller).0,
+            instructions_sysvar: sysvar::instructions::ID,
+            system_program: system_program::ID,
+            token_program: TOKEN_PROGRAM_ID,
+        },
+        instruction::CreateListingWithPermit {
+            price,
+            permit_nonce: permit.nonce,
+            permit_expiry_unix_ts: permit.expiry_unix_ts,
+        },
+    )
+}
+
+/// `create_listing_test` (test-bypass builds only).
+#[cfg(feature = "test-bypass")]
+pub fn create_listing_test(
+    seller: &Pubkey,
+    nft_mint: &Pubkey,
+    payment_mint: &Pubkey,
+    seller_nft_ata: &Pubkey,
+    listing_nft_escrow: &Pubkey,
+    price: u64,
+) -> Instruction {
+    build_ix(
+        direct_sell::ID,
+        accounts::CreateListingTest {
+            seller: *seller,
+            nft_mint: *nft_mint,
+            payment_mint: *payment_mint,
+            seller_nft_ata: *seller_nft_ata,
+            listing_nft_escrow: *listing_nft_escrow,
+            listing: find_listing(nft_mint, seller).0,
+            system_program: system_program::ID,
+            token_program: TOKEN_PROGRAM_ID,
+        },
+        instruction::CreateListingTest { price },
+    )
+}
+
+/// `buy_listing` with the listing's payment mint.
+#[allow(clippy::too_many_arguments)]
+pub fn buy_listing(
+    buyer: &Pubkey,
+    seller: &Pubkey,
+    nft_mint: &Pubkey,
+    payment_mint: &Pubkey,
+    buyer_payment_ata: &Pubkey,
+    seller_payment_ata: &Pubkey,
+    listing_nft_escrow: &Pubkey,
+    buyer_nft_ata: &Pubkey,
+) -> Instruction {
+    build_ix(
+        direct_sell::ID,
+        accounts::BuyListing {
+            buyer: *buyer,
+            seller: *seller,
+            listing: find_listing(nft_mint, seller).0,
+            nft_mint: *nft_mint,
+            payment_mint: *payment_mint,
+            buyer_payment_ata: *buyer_payment_ata,
+            seller_payment_ata: *seller_payment_ata,
+            listing_nft_escrow: *listing_nft_escrow,
+            buyer_nft_ata: *buyer_nft_ata,
+            token_program: TOKEN_PROGRAM_ID,
+        },
+        instruction::BuyListing {},
+    )
+}
+
+/// `cancel_listing`: return the NFT and close the listing.
+pub fn cancel_listing(seller: &Pubkey, nft_mint: &Pubkey, seller_nft_ata: &Pubkey, listing_nft_escrow: &Pubkey) -> Instruction {
+    build_ix(
+        direct_sell::ID,
+        accounts::CancelListing {
+            seller: *seller,
+            listing: find_listing(nft_mint, seller).0,
+            nft_mint: *nft_mint,
+            seller_nft_ata: *seller_nft_ata,
+            listing_nft_escrow: *listing_nft_escrow,
+            token_program: TOKEN_PROGRAM_ID,
+        },
+        instruction::CancelListing {},
+    )
+}
+
+/// Accounts for [`buy_listing_with_moga`] that can't be derived.
+#[cfg(feature = "pyth-jupiter")]
+#[derive(Clone, Debug)]
+pub struct BuyWithMogaKeys {
+    pub buyer: Pubkey,
+    pub seller: Pubkey,
+    pub nft_mint: Pubkey,
+    pub usdc_mint: Pubkey,
+    pub moga_mint: Pubkey,
+    pub pyth_moga_price: Pubkey,
+    pub buyer_moga_ata: Pubkey,
+    pub buyer_usdc_ata: Pubkey,
+    pub seller_payment_ata: Pubkey,
+    pub listing_nft_escrow: Pubkey,
+    pub buyer_nft_ata: Pubkey,
+}
+
+/// `buy_listing_with_moga`. `jupiter_accounts` are passed through as remaining accounts.
+#[cfg(feature = "pyth-jupiter")]
+pub fn buy_listing_with_moga(keys: &BuyWithMogaKeys, max_moga_in: u64, jupiter_accounts: Vec<AccountMeta>) -> Instruction {
+    let mut ix = build_ix(
+        direct_sell::ID,
+        accounts::BuyListingWithMoga {
+            buyer: keys.buyer,
+            seller: keys.seller,
+            listing: find_listing(&keys.nft_mint, &keys.seller).0,
+            nft_mint: keys.nft_mint,
+            usdc_mint: keys.usdc_mint,
+            moga_mint: keys.moga_mint,
+            pyth_moga_price: keys.pyth_moga_price,
+            buyer_moga_ata: keys.buyer_moga_ata,
+            buyer_usdc_ata: keys.buyer_usdc_ata,
+            seller_payment_ata: keys.seller_payment_ata,
+            listing_nft_escrow: keys.listing_nft_escrow,
+            buyer_nft_ata: keys.buyer_nft_ata,
+            token_program: TOKEN_PROGRAM_ID,
+        },
+        instruction::BuyListingWithMoga { max_moga_in },
+    );
+    ix.accounts.extend(jupiter_accounts);
+    ix
+}
+
+/// `batch_create_listings`. Needs one ed25519 instruction per entry over
+/// [`crate::permit::batch_listing_permit_message`].
+#[cfg(not(feature = "test-bypass"))]
+pub fn batch_create_listings(seller: &Pubkey, payment_mint: &Pubkey, listings: &[(u64, Permit)]) -> Instruction {
+    build_ix(
+        direct_sell::ID,
+        accounts::BatchCreateListings {
+            seller: *seller,
+            payment_mint: *payment_mint,
+            instructions_sysvar: sysvar::instructions::ID,
+            system_program: system_program::ID,
+            token_program: TOKEN_PROGRAM_ID,
+        },
+        instruction::BatchCreateListings {
+            prices: listings.iter().map(|(price, _)| *price).collect(),
+            permit_data: listings.iter().map(|(_, p)| (p.nonce, p.expiry_unix_ts)).collect(),
+        },
+    )
+}
@@ -0,0 +1,234 @@
+//! `direct_sell_anchor` instruction builders.
+//!
+//! `batch_create_listings` sits behind a `batch` feature the program doesn't
+//! declare, so it is never compiled in and has no builder here.
+
+#[cfg(feature = "pyth-jupiter")]
+use anchor_lang::solana_program::instruction::AccountMeta;
+use anchor_lang::solana_program::{instruction::Instruction, pubkey::Pubkey};
+use anchor_lang::system_program;
+#[cfg(not(feature = "test-bypass"))]
+use anchor_lang::solana_program::sysvar;
+use anchor_spl::token::ID as TOKEN_PROGRAM_ID;
+use direct_sell_anchor::{accounts, instruction};
+
+use crate::build_ix;
+use crate::pda::find_platform_config;
+#[cfg(not(feature = "test-bypass"))]
+use crate::Permit;
+
+/// `create_listing_with_permit`. Unlike `direct_sell`, the listing here is a fresh
+/// keypair account (not a PDA), so `listing` must also sign the transaction.
+/// Must be preceded by the ed25519 instruction for
+/// [`crate::permit::listing_permit_message`] with `direct_sell_anchor::ID`.
+#[cfg(not(feature = "test-bypass"))]
+#[allow(clippy::too_many_arguments)]
+pub fn create_listing_with_permit(
+    seller: &Pubkey,
+    listing: &Pubkey,
+    nft_mint: &Pubkey,
+    payment_mint: &Pubkey,
+    seller_nft_ata: &Pubkey,
+    listing_nft_escrow: &Pubkey,
+    price: u64,
+    permit: &Permit,
+) -> Instruction {
+    build_ix(
+        direct_sell_anchor::ID,
+        accounts::CreateListingWithPermit {
+            seller: *seller,
+            nft_mint: *nft_mint,
+            payment_mint: *payment_mint,
+            listing: *listing,
+            seller_nft_ata: *seller_nft_ata,
+            listing_nft_escrow: *listing_nft_escrow,
+            instructions_sysvar: sysvar::instructions::ID,
+            system_program: system_program::ID,
+            token_program: TOKEN_PROGRAM_ID,
+        },
+        instruction::CreateListingWithPermit {
+            price,
+            permit_nonce: permit.nonce.to_vec(),
+            permit_expiry_unix_ts: permit.expiry_unix_ts,
+        },
+    )
+}
+
+/// `create_listing_test` (test-bypass builds only). The listing is a PDA on this path.
+#[cfg(feature = "test-bypass")]
+pub fn create_listing_test(
+    seller: &Pubkey,
+    nft_mint: &Pubkey,
+    payment_mint: &Pubkey,
+    seller_nft_ata: &Pubkey,
+    listing_nft_escrow: &Pubkey,
+    price: u64,
+) -> Instruction {
+    build_ix(
+        direct_sell_anchor::ID,
+        accounts::CreateListingTest {
+            seller: *seller,
+            nft_mint: *nft_mint,
+            payment_mint: *payment_mint,
+            seller_nft_ata: *seller_nft_ata,
+            listing_nft_escrow: *listing_nft_escrow,
+            listing: crate::pda::find_anchor_listing(nft_mint, seller).0,
+            system_program: system_program::ID,
+            token_program: TOKEN_PROGRAM_ID,
+        },
+        instruction::CreateListingTest { price },
+    )
+}
+
+/// `initialize_config` (admin).
+pub fn initialize_config(authority: &Pubkey, fee_wallet: &Pubkey, fee_bps: u16) -> Instruction {
+    build_ix(
+        direct_sell_anchor::ID,
+        accounts::InitializeConfig {
+            authority: *authority,
+            config: find_platform_config().0,
+            fee_wallet: *fee_wallet,
+            system_program: system_program::ID,
+        },
+        instruction::InitializeConfig { fee_bps },
+    )
+}
+
+/// `update_config` (admin). `None` leaves a field unchanged.
+pub fn update_config(authority: &Pubkey, new_fee_bps: Option<u16>, new_fee_wallet: Option<Pubkey>) -> Instruction {
+    build_ix(
+        direct_sell_anchor::ID,
+        accounts::UpdateConfig {
+            config: find_platform_config().0,
+            authority: *authority,
+        },
+        instruction::UpdateConfig { new_fee_bps, new_fee_wallet },
+    )
+}
+
+/// `withdraw_fees` (admin + fee wallet signatures).
+pub fn withdraw_fees(
+    authority: &Pubkey,
+    fee_wallet: &Pubkey,
+    payment_mint: &Pubkey,
+    fee_wallet_ata: &Pubkey,
+    destination_ata: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    build_ix(
+        direct_sell_anchor::ID,
+        accounts::WithdrawFees {
+            config: find_platform_config().0,
+            authority: *authority,
+            fee_wallet: *fee_wallet,
+            payment_mint: *payment_mint,
+            fee_wallet_ata: *fee_wallet_ata,
+            destination_ata: *destination_ata,
+            token_program: TOKEN_PROGRAM_ID,
+        },
+        instruction::WithdrawFees { amount },
+    )
+}
+
+/// Accounts for [`buy_listing`] that can't be derived.
+#[derive(Clone, Debug)]
+pub struct BuyListingKeys {
+    pub buyer: Pubkey,
+    pub seller: Pubkey,
+    pub listing: Pubkey,
+    pub nft_mint: Pubkey,
+    pub payment_mint: Pubkey,
+    pub buyer_payment_ata: Pubkey,
+    pub seller_payment_ata: Pubkey,
+    pub fee_wallet_ata: Pubkey,
+    pub listing_nft_escrow: Pubkey,
+    pub buyer_nft_ata: Pubkey,
+}
+
+/// `buy_listing` with the platform fee split to `fee_wallet_ata`.
+pub fn buy_listing(keys: &BuyListingKeys) -> Instruction {
+    build_ix(
+        direct_sell_anchor::ID,
+        accounts::BuyListing {
+            buyer: keys.buyer,
+            seller: keys.seller,
+            listing: keys.listing,
+            config: find_platform_config().0,
+            nft_mint: keys.nft_mint,
+            payment_mint: keys.payment_mint,
+            buyer_payment_ata: keys.buyer_payment_ata,
+            seller_payment_ata: keys.seller_payment_ata,
+            fee_wallet_ata: keys.fee_wallet_ata,
+            listing_nft_escrow: keys.listing_nft_escrow,
+            buyer_nft_ata: keys.buyer_nft_ata,
+            token_program: TOKEN_PROGRAM_ID,
+        },
+        instruction::BuyListing {},
+    )
+}
+
+/// `cancel_listing`.
+pub fn cancel_listing(
+    seller: &Pubkey,
+    listing: &Pubkey,
+    nft_mint: &Pubkey,
+    seller_nft_ata: &Pubkey,
+    listing_nft_escrow: &Pubkey,
+) -> Instruction {
+    build_ix(
+        direct_sell_anchor::ID,
+        accounts::CancelListing {
+            seller: *seller,
+            listing: *listing,
+            nft_mint: *nft_mint,
+            seller_nft_ata: *seller_nft_ata,
+            listing_nft_escrow: *listing_nft_escrow,
+            token_program: TOKEN_PROGRAM_ID,
+        },
+        instruction::CancelListing {},
+    )
+}
+
+/// Accounts for [`buy_listing_with_moga`] that can't be derived.
+#[cfg(feature = "pyth-jupiter")]
+#[derive(Clone, Debug)]
+pub struct BuyWithMogaKeys {
+    pub buyer: Pubkey,
+    pub seller: Pubkey,
+    pub listing: Pubkey,
+    pub nft_mint: Pubkey,
+    pub usdc_mint: Pubkey,
+    pub moga_mint: Pubkey,
+    pub pyth_moga_price: Pubkey,
+    pub buyer_moga_ata: Pubkey,
+    pub buyer_usdc_ata: Pubkey,
+    pub seller_payment_ata: Pubkey,
+    pub listing_nft_escrow: Pubkey,
+    pub buyer_nft_ata: Pubkey,
+}
+
+/// `buy_listing_with_moga`. `jupiter_accounts` are passed through as remaining accounts.
+#[cfg(feature = "pyth-jupiter")]
+pub fn buy_listing_with_moga(keys: &BuyWithMogaKeys, max_moga_in: u64, jupiter_accounts: Vec<AccountMeta>) -> Instruction {
+    let mut ix = build_ix(
+        direct_sell_anchor::ID,
+        accounts::BuyListingWithMoga {
+            buyer: keys.buyer,
+            seller: keys.seller,
+            listing: keys.listing,
+            nft_mint: keys.nft_mint,
+            usdc_mint: keys.usdc_mint,
+            moga_mint: keys.moga_mint,
+            pyth_moga_price: keys.pyth_moga_price,
+            buyer_moga_ata: keys.buyer_moga_ata,
+            buyer_usdc_ata: keys.buyer_usdc_ata,
+            seller_payment_ata: keys.seller_payment_ata,
+            listing_nft_escrow: keys.listing_nft_escrow,
+            buyer_nft_ata: keys.buyer_nft_ata,
+            token_program: TOKEN_PROGRAM_ID,
+        },
+        instruction::BuyListingWithMoga { max_moga_in },
+    );
+    ix.accounts.extend(jupiter_accounts);
+    ix
+}
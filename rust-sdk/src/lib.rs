@@ -0,0 +1,48 @@
+//! Rust client SDK for the RWA programs.
+//!
+//! - [`pda`]: PDA derivation for every program seed
+//! - [`allowlist`]: presale allowlist Merkle trees and proofs
+//! - [`permit`]: canonical backend permit messages and ed25519 precompile instructions
+//! - [`raffle`], [`direct_sell`], [`direct_sell_anchor`], [`redeem`]: instruction builders
+//!
+//! Builders derive the PDAs they can and take every other account explicitly, the
+//! same way `ts-sdk` does. Enable the SDK features that match how the programs were
+//! built (`test-bypass`, `pyth-jupiter`, `metaplex`, `bubblegum`).
+
+pub mod allowlist;
+pub mod direct_sell;
+pub mod direct_sell_anchor;
+pub mod pda;
+pub mod permit;
+pub mod raffle;
+pub mod redeem;
+
+pub use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+pub use anchor_lang::solana_program::pubkey::Pubkey;
+
+/// Program ids, re-exported for convenience.
+pub mod program_ids {
+    pub use direct_sell::ID as DIRECT_SELL;
+    pub use direct_sell_anchor::ID as DIRECT_SELL_ANCHOR;
+    pub use rwa_raffle::ID as RWA_RAFFLE;
+    pub use rwa_redeem::ID as RWA_REDEEM;
+}
+
+/// Backend permit nonce + expiry, passed to the program alongside the signed message.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Permit {
+    pub nonce: [u8; 16],
+    pub expiry_unix_ts: i64,
+}
+
+fn build_ix(
+    program_id: Pubkey,
+    accounts: impl anchor_lang::ToAccountMetas,
+    data: impl anchor_lang::InstructionData,
+) -> Instruction {
+    Instruction {
+        program_id,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
@@ -0,0 +1,176 @@
+//! PDA derivation. Seeds come straight from the program crates so they can't drift.
+
+use anchor_lang::solana_program::{bpf_loader_upgradeable, pubkey::Pubkey};
+
+/// Raffle PDA: `[RAFFLE_SEED, mint, organizer]`.
+pub fn find_raffle(mint: &Pubkey, organizer: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[rwa_raffle::RAFFLE_SEED, mint.as_ref(), organizer.as_ref()],
+        &rwa_raffle::ID,
+    )
+}
+
+/// Ticket PDA: `[TICKET_SEED, raffle, owner, start_le]`.
+///
+/// `start` is the ticket's first index, as passed to `deposit` in `start_index`.
+/// Slot-join tickets use [`find_slot_ticket`] instead.
+pub fn find_ticket(raffle: &Pubkey, owner: &Pubkey, start: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[rwa_raffle::TICKET_SEED, raffle.as_ref(), owner.as_ref(), &start.to_le_bytes()],
+        &rwa_raffle::ID,
+    )
+}
+
+/// Ticket PDA for slot-based joins (`join_with_moga` / `join_with_ticket`), which
+/// seed with `slots[0]` as a u32.
+pub fn find_slot_ticket(raffle: &Pubkey, owner: &Pubkey, first_slot: u32) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[rwa_raffle::TICKET_SEED, raffle.as_ref(), owner.as_ref(), &first_slot.to_le_bytes()],
+        &rwa_raffle::ID,
+    )
+}
+
+/// Tokenized ticket mint PDA: `[TICKET_MINT_SEED, raffle, start_le]` (see `tokenize_ticket`).
+/// Once tokenized, the ticket lives at `find_ticket(raffle, ticket_mint, start)`.
+pub fn find_ticket_mint(raffle: &Pubkey, start: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[rwa_raffle::TICKET_MINT_SEED, raffle.as_ref(), &start.to_le_bytes()],
+        &rwa_raffle::ID,
+    )
+}
+
+/// Slots PDA: `[SLOTS_SEED, raffle]`.
+pub fn find_slots(raffle: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[rwa_raffle::SLOTS_SEED, raffle.as_ref()], &rwa_raffle::ID)
+}
+
+/// Presale allocation PDA: `[ALLOCATION_SEED, raffle, wallet]`.
+pub fn find_allocation(raffle: &Pubkey, wallet: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[rwa_raffle::ALLOCATION_SEED, raffle.as_ref(), wallet.as_ref()],
+        &rwa_raffle::ID,
+    )
+}
+
+/// Metaplex Token Metadata program.
+pub const TOKEN_METADATA_PROGRAM_ID: Pubkey = anchor_lang::solana_program::pubkey!("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
+
+/// Metaplex metadata PDA: `["metadata", TOKEN_METADATA_PROGRAM_ID, mint]`.
+pub fn find_metadata(mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"metadata", TOKEN_METADATA_PROGRAM_ID.as_ref(), mint.as_ref()],
+        &TOKEN_METADATA_PROGRAM_ID,
+    )
+}
+
+/// Metaplex master edition PDA: `["metadata", TOKEN_METADATA_PROGRAM_ID, mint, "edition"]`.
+pub fn find_master_edition(mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"metadata", TOKEN_METADATA_PROGRAM_ID.as_ref(), mint.as_ref(), b"edition"],
+        &TOKEN_METADATA_PROGRAM_ID,
+    )
+}
+
+/// Metaplex Token Auth Rules program (pNFT rule sets).
+pub const TOKEN_AUTH_RULES_PROGRAM_ID: Pubkey = anchor_lang::solana_program::pubkey!("auth9SigNpDKz4sJJ1DfCTuZrZNSAgh9sFD3rboVmgg");
+
+/// Metaplex pNFT token record PDA: `["metadata", TOKEN_METADATA_PROGRAM_ID, mint, "token_record", token]`.
+pub fn find_token_record(mint: &Pubkey, token: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"metadata", TOKEN_METADATA_PROGRAM_ID.as_ref(), mint.as_ref(), b"token_record", token.as_ref()],
+        &TOKEN_METADATA_PROGRAM_ID,
+    )
+}
+
+/// Metaplex Bubblegum program (compressed NFTs).
+pub const BUBBLEGUM_PROGRAM_ID: Pubkey = anchor_lang::solana_program::pubkey!("BGUMAp9Gq7iTEuizy4pqaxsTyUCBK68MDfK752saRPUY");
+
+/// SPL Account Compression program, which owns Bubblegum merkle trees.
+pub const SPL_ACCOUNT_COMPRESSION_PROGRAM_ID: Pubkey =
+    anchor_lang::solana_program::pubkey!("cmtDvXumGCrqC1Age74AVPhSRVXJMd8PJS91L8KbNCK");
+
+/// SPL Noop program, Bubblegum's log wrapper.
+pub const SPL_NOOP_PROGRAM_ID: Pubkey = anchor_lang::solana_program::pubkey!("noopb9bkMVfRPU8AsbpTUg8AQkHtKwMYZiFUjNRtMmV");
+
+/// Bubblegum tree config PDA: `[merkle_tree]`.
+pub fn find_tree_config(merkle_tree: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[merkle_tree.as_ref()], &BUBBLEGUM_PROGRAM_ID)
+}
+
+/// Bubblegum asset id of a cNFT: `["asset", merkle_tree, nonce_le]`.
+pub fn find_asset_id(merkle_tree: &Pubkey, nonce: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"asset", merkle_tree.as_ref(), &nonce.to_le_bytes()], &BUBBLEGUM_PROGRAM_ID)
+}
+
+/// Bubblegum's collection CPI signer: `["collection_cpi"]`.
+pub fn find_bubblegum_signer() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"collection_cpi"], &BUBBLEGUM_PROGRAM_ID)
+}
+
+/// Metaplex collection authority record PDA:
+/// `["metadata", TOKEN_METADATA_PROGRAM_ID, mint, "collection_authority", authority]`.
+pub fn find_collection_authority_record(mint: &Pubkey, authority: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"metadata", TOKEN_METADATA_PROGRAM_ID.as_ref(), mint.as_ref(), b"collection_authority", authority.as_ref()],
+        &TOKEN_METADATA_PROGRAM_ID,
+    )
+}
+
+/// MRFT tree authority PDA: `[MRFT_TREE_AUTHORITY_SEED]`.
+pub fn find_mrft_tree_authority() -> (Pubkey, u8) {
+    rwa_raffle::mrft_tree_authority()
+}
+
+/// Platform refund vault authority PDA: `[REFUND_VAULT_SEED]`; the vault is its ATA.
+pub fn find_refund_vault_authority() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[rwa_raffle::REFUND_VAULT_SEED], &rwa_raffle::ID)
+}
+
+/// Raffle platform config PDA: `[PLATFORM_CONFIG_SEED]`.
+pub fn find_raffle_platform_config() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[rwa_raffle::PLATFORM_CONFIG_SEED], &rwa_raffle::ID)
+}
+
+/// ProgramData account of the upgradeable `rwa_raffle` deployment.
+pub fn find_raffle_program_data() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[rwa_raffle::ID.as_ref()], &bpf_loader_upgradeable::ID)
+}
+
+/// Prize collection authority PDA: `[COLLECTION_AUTHORITY_SEED]`.
+pub fn find_collection_authority() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[rwa_raffle::COLLECTION_AUTHORITY_SEED], &rwa_raffle::ID)
+}
+
+/// `direct_sell` listing PDA: `[LISTING_SEED, nft_mint, seller]`.
+pub fn find_listing(nft_mint: &Pubkey, seller: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[direct_sell::LISTING_SEED, nft_mint.as_ref(), seller.as_ref()],
+        &direct_sell::ID,
+    )
+}
+
+/// `direct_sell_anchor` listing PDA: `[LISTING_SEED, nft_mint, seller]`.
+pub fn find_anchor_listing(nft_mint: &Pubkey, seller: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[direct_sell_anchor::LISTING_SEED, nft_mint.as_ref(), seller.as_ref()],
+        &direct_sell_anchor::ID,
+    )
+}
+
+/// `direct_sell_anchor` platform config PDA: `[CONFIG_SEED]`.
+pub fn find_platform_config() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[direct_sell_anchor::CONFIG_SEED], &direct_sell_anchor::ID)
+}
+
+/// Redemption PDA: `[REDEMPTION_SEED, nft_mint, holder]`.
+pub fn find_redemption(nft_mint: &Pubkey, holder: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[rwa_redeem::REDEMPTION_SEED, nft_mint.as_ref(), holder.as_ref()],
+        &rwa_redeem::ID,
+    )
+}
+
+/// Anchor event authority PDA used by `emit_cpi!`: `[b"__event_authority"]`.
+pub fn find_event_authority(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"__event_authority"], program_id)
+}
@@ -0,0 +1,199 @@
+//! Canonical backend permit messages and the ed25519 precompile instruction the
+//! programs look for in the instructions sysvar.
+//!
+//! The programs only accept a single-signature ed25519 instruction with the
+//! signature, pubkey and message all inline (instruction index `u16::MAX`),
+//! signed by `BACKEND_SIGNER`. Put the returned verify instruction in the same
+//! transaction as the instruction it authorizes.
+
+use anchor_lang::solana_program::{ed25519_program, instruction::Instruction, pubkey::Pubkey};
+use solana_keypair::Keypair;
+use solana_signer::Signer;
+
+use crate::raffle::{PrizeMetadata, RaffleConfig};
+use crate::Permit;
+
+/// Size of the ed25519 instruction header (count, padding, 7 × u16 offsets).
+const ED25519_HEADER_LEN: usize = 16;
+
+/// `b"RWA_RAFFLE_PERMIT" || organizer || nonce || expiry || required_tickets ||
+/// deadline || program_id || auto_draw || ticket_mode || reveal_time (0 = none)`;
+/// the programs only accept it with the prize fields of [`raffle_prize_permit_message`].
+fn raffle_permit_message(organizer: &Pubkey, permit: &Permit, config: &RaffleConfig) -> Vec<u8> {
+    let mut msg: Vec<u8> = b"RWA_RAFFLE_PERMIT".to_vec();
+    msg.extend_from_slice(organizer.as_ref());
+    msg.extend_from_slice(&permit.nonce);
+    msg.extend_from_slice(&permit.expiry_unix_ts.to_le_bytes());
+    msg.extend_from_slice(&config.required_tickets.to_le_bytes());
+    msg.extend_from_slice(&config.deadline_unix_ts.to_le_bytes());
+    msg.extend_from_slice(rwa_raffle::ID.as_ref());
+    msg.push(config.auto_draw as u8);
+    msg.push(config.ticket_mode);
+    msg.extend_from_slice(&config.reveal_time_unix_ts.unwrap_or(0).to_le_bytes());
+    msg
+}
+
+/// `initialize_raffle_with_permit` and `batch_create_raffles` permit: the raffle layout followed by
+/// `rwa_raffle::prize_metadata_hash(prize_metadata)` (32 bytes) and the accepted
+/// MRFT collection (32 bytes; `Pubkey::default()` = `rwa_raffle::MRFT_COLLECTION_MINT`).
+pub fn raffle_prize_permit_message(
+    organizer: &Pubkey,
+    permit: &Permit,
+    config: &RaffleConfig,
+    prize_metadata: &PrizeMetadata,
+    mrft_collection: &Pubkey,
+) -> Vec<u8> {
+    let mut msg = raffle_permit_message(organizer, permit, config);
+    msg.extend_from_slice(&rwa_raffle::prize_metadata_hash(prize_metadata));
+    msg.extend_from_slice(mrft_collection.as_ref());
+    msg
+}
+
+/// `b"DIRECT_SELL_CREATE_PERMIT" || seller || nft_mint || price || payment_mint ||
+/// nonce || expiry || program_id`
+///
+/// `program_id` is `direct_sell::ID` or `direct_sell_anchor::ID`; both programs
+/// use the same layout.
+pub fn listing_permit_message(
+    program_id: &Pubkey,
+    seller: &Pubkey,
+    nft_mint: &Pubkey,
+    price: u64,
+    payment_mint: &Pubkey,
+    permit: &Permit,
+) -> Vec<u8> {
+    let mut msg: Vec<u8> = b"DIRECT_SELL_CREATE_PERMIT".to_vec();
+    msg.extend_from_slice(seller.as_ref());
+    msg.extend_from_slice(nft_mint.as_ref());
+    msg.extend_from_slice(&price.to_le_bytes());
+    msg.extend_from_slice(payment_mint.as_ref());
+    msg.extend_from_slice(&permit.nonce);
+    msg.extend_from_slice(&permit.expiry_unix_ts.to_le_bytes());
+    msg.extend_from_slice(program_id.as_ref());
+    msg
+}
+
+/// `batch_create_listings` permit: the listing layout without `nft_mint`
+/// (`b"DIRECT_SELL_CREATE_PERMIT" || seller || price || payment_mint || nonce || expiry || program_id`).
+pub fn batch_listing_permit_message(
+    program_id: &Pubkey,
+    seller: &Pubkey,
+    price: u64,
+    payment_mint: &Pubkey,
+    permit: &Permit,
+) -> Vec<u8> {
+    let mut msg: Vec<u8> = b"DIRECT_SELL_CREATE_PERMIT".to_vec();
+    msg.extend_from_slice(seller.as_ref());
+    msg.extend_from_slice(&price.to_le_bytes());
+    msg.extend_from_slice(payment_mint.as_ref());
+    msg.extend_from_slice(&permit.nonce);
+    msg.extend_from_slice(&permit.expiry_unix_ts.to_le_bytes());
+    msg.extend_from_slice(program_id.as_ref());
+    msg
+}
+
+/// `b"RWA_REDEEM_PERMIT" || holder || nft_mint || redemption_type || nonce || expiry || program_id`
+pub fn redeem_permit_message(holder: &Pubkey, nft_mint: &Pubkey, redemption_type: u8, permit: &Permit) -> Vec<u8> {
+    let mut msg: Vec<u8> = b"RWA_REDEEM_PERMIT".to_vec();
+    msg.extend_from_slice(holder.as_ref());
+    msg.extend_from_slice(nft_mint.as_ref());
+    msg.push(redemption_type);
+    msg.extend_from_slice(&permit.nonce);
+    msg.extend_from_slice(&permit.expiry_unix_ts.to_le_bytes());
+    msg.extend_from_slice(rwa_redeem::ID.as_ref());
+    msg
+}
+
+/// Build an ed25519 precompile instruction for a signature produced elsewhere
+/// (e.g. returned by the backend permit endpoints).
+///
+/// Layout: `num_signatures(1) || padding || sig_offset || sig_ix(u16::MAX) ||
+/// pk_offset || pk_ix(u16::MAX) || msg_offset || msg_len || msg_ix(u16::MAX) ||
+/// signature(64) || pubkey(32) || message`
+pub fn ed25519_verify_ix(pubkey: &Pubkey, signature: &[u8; 64], message: &[u8]) -> Instruction {
+    let sig_off = ED25519_HEADER_LEN;
+    let pk_off = sig_off + 64;
+    let msg_off = pk_off + 32;
+    assert!(msg_off + message.len() <= u16::MAX as usize, "permit message too long");
+
+    let mut data = Vec::with_capacity(msg_off + message.len());
+    data.push(1u8);
+    data.push(0u8);
+    for field in [
+        sig_off as u16,
+        u16::MAX,
+        pk_off as u16,
+        u16::MAX,
+        msg_off as u16,
+        message.len() as u16,
+        u16::MAX,
+    ] {
+        data.extend_from_slice(&field.to_le_bytes());
+    }
+    data.extend_from_slice(signature);
+    data.extend_from_slice(pubkey.as_ref());
+    data.extend_from_slice(message);
+
+    Instruction {
+        program_id: ed25519_program::ID,
+        accounts: vec![],
+        data,
+    }
+}
+
+/// Sign `message` with `signer` and wrap it in an ed25519 precompile instruction.
+/// Only useful for the backend itself or for local validators whose programs were
+/// built with a test `BACKEND_SIGNER`.
+pub fn sign_permit(signer: &Keypair, message: &[u8]) -> Instruction {
+    let signature: [u8; 64] = signer.sign_message(message).into();
+    ed25519_verify_ix(&signer.pubkey(), &signature, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ed25519_ix_uses_inline_offsets() {
+        let pk = Pubkey::new_unique();
+        let msg = b"hello permit".to_vec();
+        let ix = ed25519_verify_ix(&pk, &[7u8; 64], &msg);
+        let d = &ix.data;
+        let u16_at = |i: usize| u16::from_le_bytes([d[i], d[i + 1]]);
+
+        assert_eq!(d[0], 1);
+        assert_eq!(u16_at(4), u16::MAX);
+        assert_eq!(u16_at(8), u16::MAX);
+        assert_eq!(u16_at(14), u16::MAX);
+        let (sig_off, pk_off, msg_off, msg_len) = (u16_at(2) as usize, u16_at(6) as usize, u16_at(10) as usize, u16_at(12) as usize);
+        assert_eq!(&d[sig_off..sig_off + 64], &[7u8; 64]);
+        assert_eq!(&d[pk_off..pk_off + 32], pk.as_ref());
+        assert_eq!(&d[msg_off..msg_off + msg_len], msg.as_slice());
+    }
+
+    #[test]
+    fn raffle_permit_message_layout() {
+        let organizer = Pubkey::new_unique();
+        let permit = Permit { nonce: [9u8; 16], expiry_unix_ts: 1_700_000_000 };
+        let config = RaffleConfig {
+            required_tickets: 100,
+            deadline_unix_ts: 1_800_000_000,
+            auto_draw: true,
+            ticket_mode: 2,
+            reveal_time_unix_ts: None,
+            crank_reserve_lamports: 0,
+        };
+        let msg = raffle_permit_message(&organizer, &permit, &config);
+        assert_eq!(msg.len(), 17 + 32 + 16 + 8 + 8 + 8 + 32 + 1 + 1 + 8);
+        assert!(msg.starts_with(b"RWA_RAFFLE_PERMIT"));
+        assert_eq!(&msg[msg.len() - 10..msg.len() - 8], &[1, 2]);
+        assert_eq!(&msg[msg.len() - 8..], &0i64.to_le_bytes());
+
+        let prize = PrizeMetadata { name: "Prize".into(), uri: "https://example.com/prize.json".into(), ..Default::default() };
+        let mrft_collection = Pubkey::new_unique();
+        let with_prize = raffle_prize_permit_message(&organizer, &permit, &config, &prize, &mrft_collection);
+        assert_eq!(&with_prize[..msg.len()], msg.as_slice());
+        assert_eq!(&with_prize[msg.len()..msg.len() + 32], &rwa_raffle::prize_metadata_hash(&prize));
+        assert_eq!(&with_prize[msg.len() + 32..], mrft_collection.as_ref());
+    }
+}
@@ -0,0 +1,1217 @@
+//! `rwa_raffle` instruction builders.
+
+use anchor_lang::solana_program::{instruction::{AccountMeta, Instruction}, pubkey::Pubkey, sysvar};
+use anchor_lang::system_program;
+use anchor_spl::associated_token::get_associated_token_address_with_program_id;
+use anchor_spl::token::ID as TOKEN_PROGRAM_ID;
+use anchor_spl::token_2022::ID as TOKEN_2022_PROGRAM_ID;
+use rwa_raffle::{accounts, instruction};
+
+use crate::pda::{
+    find_allocation, find_bubblegum_signer, find_collection_authority_record, find_event_authority, find_master_edition,
+    find_metadata, find_mrft_tree_authority, find_raffle, find_slots, find_ticket, find_ticket_mint, find_token_record,
+    find_tree_config, BUBBLEGUM_PROGRAM_ID, SPL_ACCOUNT_COMPRESSION_PROGRAM_ID, SPL_NOOP_PROGRAM_ID,
+    TOKEN_AUTH_RULES_PROGRAM_ID, TOKEN_METADATA_PROGRAM_ID,
+};
+use crate::{build_ix, Permit};
+
+pub use rwa_raffle::{CompressedPrize, MrftLeaf, PrizeCreator, PrizeMetadata};
+
+/// Raffle parameters shared by `initialize_raffle`, the permit variant and the permit message.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RaffleConfig {
+    pub required_tickets: u64,
+    pub deadline_unix_ts: i64,
+    pub auto_draw: bool,
+    pub ticket_mode: u8, // 0=disabled, 1=accept_without_burn, 2=require_burn
+    pub reveal_time_unix_ts: Option<i64>,
+    pub crank_reserve_lamports: u64,
+}
+
+/// Optional accounts of raffle entries (`deposit`, `join_with_moga`, `join_with_ticket`).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct EntryAccounts {
+    /// Charge the entry against the payer's presale `Allocation` (see [`register_allowlist`]).
+    pub presale: bool,
+    /// Payer's token account proving the raffle's gate: gate mint balance or collection NFT.
+    pub gate_token_account: Option<Pubkey>,
+    /// Metaplex metadata of the gating NFT, for collection gates (see [`crate::pda::find_metadata`]).
+    pub gate_metadata: Option<Pubkey>,
+}
+
+impl EntryAccounts {
+    fn allocation(&self, raffle: &Pubkey, payer: &Pubkey) -> Option<Pubkey> {
+        self.presale.then(|| find_allocation(raffle, payer).0)
+    }
+}
+
+/// The prize is a Metaplex programmable NFT (pNFT), moved with Token Metadata's `TransferV1`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ProgrammablePrize {
+    /// The pNFT's authorization rule set, if it has one.
+    pub rule_set: Option<Pubkey>,
+}
+
+fn event_authority() -> Pubkey {
+    find_event_authority(&rwa_raffle::ID).0
+}
+
+/// `pnft` accounts for moving the prize `mint` from `source` to `destination`; all empty for regular NFTs.
+fn programmable_transfer(
+    mint: &Pubkey,
+    source: &Pubkey,
+    destination: &Pubkey,
+    programmable: Option<&ProgrammablePrize>,
+) -> accounts::ProgrammableTransfer {
+    let Some(prize) = programmable else {
+        return accounts::ProgrammableTransfer {
+            token_metadata_program: None,
+            source_token_record: None,
+            destination_token_record: None,
+            sysvar_instructions: None,
+            associated_token_program: None,
+            system_program: None,
+            authorization_rules_program: None,
+            authorization_rules: None,
+        };
+    };
+    accounts::ProgrammableTransfer {
+        token_metadata_program: Some(TOKEN_METADATA_PROGRAM_ID),
+        source_token_record: Some(find_token_record(mint, source).0),
+        destination_token_record: Some(find_token_record(mint, destination).0),
+        sysvar_instructions: Some(sysvar::instructions::ID),
+        associated_token_program: Some(anchor_spl::associated_token::ID),
+        system_program: Some(system_program::ID),
+        authorization_rules_program: prize.rule_set.map(|_| TOKEN_AUTH_RULES_PROGRAM_ID),
+        authorization_rules: prize.rule_set,
+    }
+}
+
+/// Bubblegum accounts moving `prize` out of the raffle's escrow (see [`claim_prize_cnft`]).
+/// `organizer` is still the leaf owner of delegated prizes.
+fn compressed_transfer(prize: Option<&CompressedPrize>, organizer: &Pubkey) -> accounts::CompressedTransfer {
+    let Some(prize) = prize else {
+        return accounts::CompressedTransfer {
+            bubblegum_program: None,
+            tree_config: None,
+            merkle_tree: None,
+            log_wrapper: None,
+            compression_program: None,
+            leaf_owner: None,
+            system_program: None,
+        };
+    };
+    accounts::CompressedTransfer {
+        bubblegum_program: Some(BUBBLEGUM_PROGRAM_ID),
+        tree_config: Some(find_tree_config(&prize.merkle_tree).0),
+        merkle_tree: Some(prize.merkle_tree),
+        log_wrapper: Some(SPL_NOOP_PROGRAM_ID),
+        compression_program: Some(SPL_ACCOUNT_COMPRESSION_PROGRAM_ID),
+        leaf_owner: prize.delegated.then_some(*organizer),
+        system_program: Some(system_program::ID),
+    }
+}
+
+/// Bubblegum accounts for minting MRFTs into `merkle_tree` in `claim_refund`; `None`
+/// leaves minting to the off-chain worker.
+fn mrft_mint(merkle_tree: Option<&Pubkey>) -> accounts::MrftMint {
+    let Some(merkle_tree) = merkle_tree else {
+        return accounts::MrftMint {
+            tree_authority: None,
+            tree_config: None,
+            merkle_tree: None,
+            collection_mint: None,
+            collection_metadata: None,
+            collection_edition: None,
+            collection_authority_record: None,
+            bubblegum_signer: None,
+            log_wrapper: None,
+            compression_program: None,
+            token_metadata_program: None,
+            bubblegum_program: None,
+            system_program: None,
+        };
+    };
+    let tree_authority = find_mrft_tree_authority().0;
+    let collection = rwa_raffle::MRFT_COLLECTION_MINT;
+    accounts::MrftMint {
+        tree_authority: Some(tree_authority),
+        tree_config: Some(find_tree_config(merkle_tree).0),
+        merkle_tree: Some(*merkle_tree),
+        collection_mint: Some(collection),
+        collection_metadata: Some(find_metadata(&collection).0),
+        collection_edition: Some(find_master_edition(&collection).0),
+        collection_authority_record: Some(find_collection_authority_record(&collection, &tree_authority).0),
+        bubblegum_signer: Some(find_bubblegum_signer().0),
+        log_wrapper: Some(SPL_NOOP_PROGRAM_ID),
+        compression_program: Some(SPL_ACCOUNT_COMPRESSION_PROGRAM_ID),
+        token_metadata_program: Some(TOKEN_METADATA_PROGRAM_ID),
+        bubblegum_program: Some(BUBBLEGUM_PROGRAM_ID),
+        system_program: Some(system_program::ID),
+    }
+}
+
+/// Bubblegum accounts for redeeming MRFTs from `merkle_tree`.
+#[cfg(feature = "bubblegum")]
+fn mrft_redeem(merkle_tree: &Pubkey) -> accounts::MrftRedeem {
+    accounts::MrftRedeem {
+        tree_authority: find_mrft_tree_authority().0,
+        merkle_tree: *merkle_tree,
+        tree_config: find_tree_config(merkle_tree).0,
+        log_wrapper: SPL_NOOP_PROGRAM_ID,
+        compression_program: SPL_ACCOUNT_COMPRESSION_PROGRAM_ID,
+        bubblegum_program: BUBBLEGUM_PROGRAM_ID,
+        system_program: system_program::ID,
+    }
+}
+
+/// Appends a cNFT proof path (tree nodes, leaf first) as remaining accounts.
+fn push_proof(ix: &mut Instruction, proof: &[Pubkey]) {
+    ix.accounts.extend(proof.iter().map(|node| AccountMeta::new_readonly(*node, false)));
+}
+
+/// `initialize_raffle`. The raffle PDA is derived from `mint` and `organizer`;
+/// `escrow_ata` must already exist, be owned by that PDA and hold `mint`.
+pub fn initialize_raffle(organizer: &Pubkey, mint: &Pubkey, escrow_ata: &Pubkey, config: &RaffleConfig) -> Instruction {
+    let raffle = find_raffle(mint, organizer).0;
+    build_ix(
+        rwa_raffle::ID,
+        accounts::InitializeRaffle {
+            organizer: *organizer,
+            mint: *mint,
+            escrow_ata: *escrow_ata,
+            raffle,
+            slots: find_slots(&raffle).0,
+            system_program: system_program::ID,
+            token_program: TOKEN_PROGRAM_ID,
+            event_authority: event_authority(),
+            program: rwa_raffle::ID,
+        },
+        instruction::InitializeRaffle {
+            required_tickets: config.required_tickets,
+            deadline_unix_ts: config.deadline_unix_ts,
+            auto_draw: config.auto_draw,
+            ticket_mode: config.ticket_mode,
+            reveal_time_unix_ts: config.reveal_time_unix_ts,
+            crank_reserve_lamports: config.crank_reserve_lamports,
+        },
+    )
+}
+
+/// `initialize_raffle_with_permit`. Must be preceded by the ed25519 instruction for
+/// [`crate::permit::raffle_prize_permit_message`] over the same `permit`, `config`,
+/// `prize_metadata` (what `claim_prize_mint` gives the minted prize) and `mrft_collection`
+/// (MRFTs `join_with_ticket` accepts; `Pubkey::default()` for the platform collection).
+#[allow(clippy::too_many_arguments)]
+pub fn initialize_raffle_with_permit(
+    organizer: &Pubkey,
+    mint: &Pubkey,
+    escrow_ata: &Pubkey,
+    config: &RaffleConfig,
+    permit: &Permit,
+    prize_collection_mint: &Pubkey,
+    refund_mode: u8,
+    prize_metadata: &PrizeMetadata,
+    mrft_collection: &Pubkey,
+) -> Instruction {
+    let raffle = find_raffle(mint, organizer).0;
+    build_ix(
+        rwa_raffle::ID,
+        accounts::InitializeRaffleWithPermit {
+            organizer: *organizer,
+            mint: *mint,
+            escrow_ata: *escrow_ata,
+            raffle,
+            slots: find_slots(&raffle).0,
+            instructions_sysvar: sysvar::instructions::ID,
+            system_program: system_program::ID,
+            token_program: TOKEN_PROGRAM_ID,
+            event_authority: event_authority(),
+            program: rwa_raffle::ID,
+        },
+        instruction::InitializeRaffleWithPermit {
+            required_tickets: config.required_tickets,
+            deadline_unix_ts: config.deadline_unix_ts,
+            permit_nonce: permit.nonce,
+            permit_expiry_unix_ts: permit.expiry_unix_ts,
+            auto_draw: config.auto_draw,
+            ticket_mode: config.ticket_mode,
+            prize_collection_mint: *prize_collection_mint,
+            refund_mode,
+            reveal_time_unix_ts: config.reveal_time_unix_ts,
+            crank_reserve_lamports: config.crank_reserve_lamports,
+            prize_metadata: prize_metadata.clone(),
+            mrft_collection: *mrft_collection,
+        },
+    )
+}
+
+/// `batch_create_raffles`. Needs one ed25519 instruction per raffle, for
+/// [`crate::permit::raffle_prize_permit_message`] over its `config`, `permit`, prize
+/// metadata and MRFT collection.
+pub fn batch_create_raffles(organizer: &Pubkey, raffles: &[(RaffleConfig, Permit, PrizeMetadata, Pubkey)]) -> Instruction {
+    build_ix(
+        rwa_raffle::ID,
+        accounts::BatchCreateRaffles {
+            organizer: *organizer,
+            instructions_sysvar: sysvar::instructions::ID,
+            system_program: system_program::ID,
+        },
+        instruction::BatchCreateRaffles {
+            configs: raffles
+                .iter()
+                .map(|(c, _, prize_metadata, mrft_collection)| {
+                    (
+                        c.required_tickets,
+                        c.deadline_unix_ts,
+                        c.auto_draw,
+                        c.ticket_mode,
+                        c.reveal_time_unix_ts,
+                        rwa_raffle::prize_metadata_hash(prize_metadata),
+                        *mrft_collection,
+                    )
+                })
+                .collect(),
+            permit_data: raffles.iter().map(|(_, p, _, _)| (p.nonce, p.expiry_unix_ts)).collect(),
+        },
+    )
+}
+
+/// `deposit`: buy `amount` worth of tickets starting at `start_index`, which must begin a
+/// free range of the raffle's `RaffleSlots` (see `RaffleSlots::first_free_run`).
+pub fn deposit(
+    payer: &Pubkey,
+    raffle: &Pubkey,
+    mint: &Pubkey,
+    payer_ata: &Pubkey,
+    escrow_ata: &Pubkey,
+    amount: u64,
+    start_index: u64,
+) -> Instruction {
+    deposit_with(payer, payer, raffle, mint, payer_ata, escrow_ata, amount, start_index, &EntryAccounts::default())
+}
+
+/// [`deposit`] paid by `payer` for tickets owned by `beneficiary` (gifts, airdropped entries).
+/// Refunds and prize claims go to the beneficiary.
+#[allow(clippy::too_many_arguments)]
+pub fn gift_deposit(
+    payer: &Pubkey,
+    beneficiary: &Pubkey,
+    raffle: &Pubkey,
+    mint: &Pubkey,
+    payer_ata: &Pubkey,
+    escrow_ata: &Pubkey,
+    amount: u64,
+    start_index: u64,
+) -> Instruction {
+    deposit_with(payer, beneficiary, raffle, mint, payer_ata, escrow_ata, amount, start_index, &EntryAccounts::default())
+}
+
+/// [`deposit`] during the allowlist presale; charged against the payer's `Allocation`
+/// (see [`register_allowlist`]).
+pub fn presale_deposit(
+    payer: &Pubkey,
+    raffle: &Pubkey,
+    mint: &Pubkey,
+    payer_ata: &Pubkey,
+    escrow_ata: &Pubkey,
+    amount: u64,
+    start_index: u64,
+) -> Instruction {
+    let entry = EntryAccounts { presale: true, ..Default::default() };
+    deposit_with(payer, payer, raffle, mint, payer_ata, escrow_ata, amount, start_index, &entry)
+}
+
+/// [`deposit`] with presale and gate accounts. The allocation and gate are the beneficiary's.
+#[allow(clippy::too_many_arguments)]
+pub fn deposit_with(
+    payer: &Pubkey,
+    beneficiary: &Pubkey,
+    raffle: &Pubkey,
+    mint: &Pubkey,
+    payer_ata: &Pubkey,
+    escrow_ata: &Pubkey,
+    amount: u64,
+    start_index: u64,
+    entry: &EntryAccounts,
+) -> Instruction {
+    build_ix(
+        rwa_raffle::ID,
+        accounts::Deposit {
+            payer: *payer,
+            raffle: *raffle,
+            mint: *mint,
+            payer_ata: *payer_ata,
+            escrow_ata: *escrow_ata,
+            slots: find_slots(raffle).0,
+            allocation: entry.allocation(raffle, beneficiary),
+            gate_token_account: entry.gate_token_account,
+            gate_metadata: entry.gate_metadata,
+            ticket: find_ticket(raffle, beneficiary, start_index).0,
+            system_program: system_program::ID,
+            token_program: TOKEN_PROGRAM_ID,
+            event_authority: event_authority(),
+            program: rwa_raffle::ID,
+        },
+        instruction::Deposit { amount, start_index, beneficiary: *beneficiary },
+    )
+}
+
+/// Accounts for [`join_with_moga`] that can't be derived.
+#[cfg(feature = "pyth-jupiter")]
+#[derive(Clone, Debug)]
+pub struct JoinWithMogaKeys {
+    pub payer: Pubkey,
+    pub raffle: Pubkey,
+    pub usdc_mint: Pubkey,
+    pub moga_mint: Pubkey,
+    pub payer_moga_ata: Pubkey,
+    pub payer_usdc_ata: Pubkey,
+    pub escrow_ata: Pubkey,
+    pub pyth_price_account: Pubkey,
+    pub entry: EntryAccounts,
+}
+
+/// `join_with_moga`. `slots` must be one ascending run of 0-based slots. `jupiter_accounts`
+/// is the Jupiter program followed by the route accounts; they are passed through as
+/// remaining accounts.
+#[cfg(feature = "pyth-jupiter")]
+pub fn join_with_moga(keys: &JoinWithMogaKeys, slots: Vec<u32>, max_moga_in: u64, jupiter_accounts: Vec<AccountMeta>) -> Instruction {
+    let first_slot = *slots.first().expect("at least one slot");
+    let mut ix = build_ix(
+        rwa_raffle::ID,
+        accounts::JoinWithMoga {
+            payer: keys.payer,
+            raffle: keys.raffle,
+            slots: find_slots(&keys.raffle).0,
+            allocation: keys.entry.allocation(&keys.raffle, &keys.payer),
+            gate_token_account: keys.entry.gate_token_account,
+            gate_metadata: keys.entry.gate_metadata,
+            usdc_mint: keys.usdc_mint,
+            moga_mint: keys.moga_mint,
+            payer_moga_ata: keys.payer_moga_ata,
+            payer_usdc_ata: keys.payer_usdc_ata,
+            escrow_ata: keys.escrow_ata,
+            ticket: crate::pda::find_slot_ticket(&keys.raffle, &keys.payer, first_slot).0,
+            pyth_price_account: keys.pyth_price_account,
+            system_program: system_program::ID,
+            token_program: TOKEN_PROGRAM_ID,
+            mint: keys.usdc_mint,
+            event_authority: event_authority(),
+            program: rwa_raffle::ID,
+        },
+        instruction::JoinWithMoga { slots, max_moga_in },
+    );
+    ix.accounts.extend(jupiter_accounts);
+    ix
+}
+
+/// `join_with_ticket`: join with MRFT tickets instead of USDC. `slots` must be one
+/// ascending run of 0-based slots, one MRFT each; all `mrfts` live in `merkle_tree` and
+/// come from the same failed raffle, whose escrow (`origin_escrow`) pays their backing into
+/// `escrow_ata`. `proofs[i]` is the proof path of `mrfts[i]` (its `proof_len` is set from it).
+#[cfg(feature = "bubblegum")]
+#[allow(clippy::too_many_arguments)]
+pub fn join_with_ticket(
+    payer: &Pubkey,
+    raffle: &Pubkey,
+    mint: &Pubkey,
+    escrow_ata: &Pubkey,
+    origin_escrow: &Pubkey,
+    merkle_tree: &Pubkey,
+    slots: Vec<u32>,
+    mrfts: &[MrftLeaf],
+    proofs: &[Vec<Pubkey>],
+    entry: &EntryAccounts,
+) -> Instruction {
+    let first_slot = *slots.first().expect("at least one slot");
+    let origin_raffle = mrfts.first().expect("one MRFT per slot").raffle;
+    let mrfts = mrfts
+        .iter()
+        .zip(proofs)
+        .map(|(mrft, proof)| MrftLeaf { proof_len: proof.len() as u8, ..*mrft })
+        .collect();
+    let mut ix = build_ix(
+        rwa_raffle::ID,
+        accounts::JoinWithTicket {
+            payer: *payer,
+            raffle: *raffle,
+            mint: *mint,
+            escrow_ata: *escrow_ata,
+            origin_raffle,
+            origin_escrow: *origin_escrow,
+            slots: find_slots(raffle).0,
+            allocation: entry.allocation(raffle, payer),
+            gate_token_account: entry.gate_token_account,
+            gate_metadata: entry.gate_metadata,
+            ticket: crate::pda::find_slot_ticket(raffle, payer, first_slot).0,
+            bubblegum: mrft_redeem(merkle_tree),
+            token_program: TOKEN_PROGRAM_ID,
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: rwa_raffle::ID,
+        },
+        instruction::JoinWithTicket { slots, mrfts },
+    );
+    for proof in proofs {
+        push_proof(&mut ix, proof);
+    }
+    ix
+}
+
+/// `buy_back_mrfts`: `holder` sells MRFTs back to the platform refund vault (the vault
+/// authority's ATA for `mint`) for their `usdc_value`, minus the time-based haircut. All
+/// `mrfts` live in `merkle_tree` and come from the failed raffle whose escrow is `origin_escrow`;
+/// `proofs[i]` is the proof path of `mrfts[i]`.
+#[cfg(feature = "bubblegum")]
+pub fn buy_back_mrfts(
+    holder: &Pubkey,
+    mint: &Pubkey,
+    origin_escrow: &Pubkey,
+    merkle_tree: &Pubkey,
+    mrfts: &[MrftLeaf],
+    proofs: &[Vec<Pubkey>],
+) -> Instruction {
+    let origin_raffle = mrfts.first().expect("at least one MRFT").raffle;
+    let vault_authority = crate::pda::find_refund_vault_authority().0;
+    let mrfts = mrfts
+        .iter()
+        .zip(proofs)
+        .map(|(mrft, proof)| MrftLeaf { proof_len: proof.len() as u8, ..*mrft })
+        .collect();
+    let mut ix = build_ix(
+        rwa_raffle::ID,
+        accounts::BuyBackMrfts {
+            holder: *holder,
+            origin_raffle,
+            mint: *mint,
+            origin_escrow: *origin_escrow,
+            vault_authority,
+            refund_vault: get_associated_token_address_with_program_id(&vault_authority, mint, &TOKEN_PROGRAM_ID),
+            holder_ata: get_associated_token_address_with_program_id(holder, mint, &TOKEN_PROGRAM_ID),
+            bubblegum: mrft_redeem(merkle_tree),
+            token_program: TOKEN_PROGRAM_ID,
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: rwa_raffle::ID,
+        },
+        instruction::BuyBackMrfts { mrfts },
+    );
+    for proof in proofs {
+        push_proof(&mut ix, proof);
+    }
+    ix
+}
+
+/// `initialize_platform_config`: the program's upgrade authority names the platform authority.
+pub fn initialize_platform_config(upgrade_authority: &Pubkey, authority: &Pubkey) -> Instruction {
+    build_ix(
+        rwa_raffle::ID,
+        accounts::InitializePlatformConfig {
+            upgrade_authority: *upgrade_authority,
+            config: crate::pda::find_raffle_platform_config().0,
+            raffle_program: rwa_raffle::ID,
+            program_data: crate::pda::find_raffle_program_data().0,
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: rwa_raffle::ID,
+        },
+        instruction::InitializePlatformConfig { authority: *authority },
+    )
+}
+
+/// `set_platform_authority`: the platform authority hands over to `new_authority`.
+pub fn set_platform_authority(authority: &Pubkey, new_authority: &Pubkey) -> Instruction {
+    build_ix(
+        rwa_raffle::ID,
+        accounts::SetPlatformAuthority {
+            config: crate::pda::find_raffle_platform_config().0,
+            authority: *authority,
+            event_authority: event_authority(),
+            program: rwa_raffle::ID,
+        },
+        instruction::SetPlatformAuthority { new_authority: *new_authority },
+    )
+}
+
+/// `withdraw_refund_vault`: the platform authority moves `amount` of `mint` out of the
+/// refund vault (the vault authority's ATA) to `destination`.
+pub fn withdraw_refund_vault(authority: &Pubkey, mint: &Pubkey, destination: &Pubkey, amount: u64) -> Instruction {
+    let vault_authority = crate::pda::find_refund_vault_authority().0;
+    build_ix(
+        rwa_raffle::ID,
+        accounts::WithdrawRefundVault {
+            config: crate::pda::find_raffle_platform_config().0,
+            authority: *authority,
+            mint: *mint,
+            vault_authority,
+            refund_vault: get_associated_token_address_with_program_id(&vault_authority, mint, &TOKEN_PROGRAM_ID),
+            destination: *destination,
+            token_program: TOKEN_PROGRAM_ID,
+            event_authority: event_authority(),
+            program: rwa_raffle::ID,
+        },
+        instruction::WithdrawRefundVault { amount },
+    )
+}
+
+/// `set_allowlist`: organizer opens an allowlist presale; send it together with
+/// `initialize_raffle`, before the first sale.
+pub fn set_allowlist(organizer: &Pubkey, raffle: &Pubkey, allowlist_root: [u8; 32], presale_end_unix_ts: i64) -> Instruction {
+    build_ix(
+        rwa_raffle::ID,
+        accounts::SetAllowlist {
+            organizer: *organizer,
+            raffle: *raffle,
+            event_authority: event_authority(),
+            program: rwa_raffle::ID,
+        },
+        instruction::SetAllowlist { allowlist_root, presale_end_unix_ts },
+    )
+}
+
+/// `set_gate`: organizer restricts entries to holders of `gate_mint` (`gate_mode == 1`,
+/// at least `gate_min_amount`) or of an NFT in the verified collection `gate_mint`
+/// (`gate_mode == 2`). Send it together with `initialize_raffle`, before the first sale.
+pub fn set_gate(organizer: &Pubkey, raffle: &Pubkey, gate_mode: u8, gate_mint: &Pubkey, gate_min_amount: u64) -> Instruction {
+    build_ix(
+        rwa_raffle::ID,
+        accounts::SetGate {
+            organizer: *organizer,
+            raffle: *raffle,
+            event_authority: event_authority(),
+            program: rwa_raffle::ID,
+        },
+        instruction::SetGate { gate_mode, gate_mint: *gate_mint, gate_min_amount },
+    )
+}
+
+/// `set_prize_backed`: organizer blocks entries until the prize is escrowed with
+/// `set_prize_nft`. Raffles that mint their prize after the draw pass the metadata of
+/// their `prize_collection_mint` as `collection_metadata` instead.
+pub fn set_prize_backed(organizer: &Pubkey, raffle: &Pubkey, prize_backed: bool, collection_metadata: Option<Pubkey>) -> Instruction {
+    build_ix(
+        rwa_raffle::ID,
+        accounts::SetPrizeBacked {
+            organizer: *organizer,
+            raffle: *raffle,
+            collection_metadata,
+            event_authority: event_authority(),
+            program: rwa_raffle::ID,
+        },
+        instruction::SetPrizeBacked { prize_backed },
+    )
+}
+
+/// `set_claim_window`: organizer sets how long the winner has for `claim_win`
+/// (0 = no deadline); only before the first sale.
+pub fn set_claim_window(organizer: &Pubkey, raffle: &Pubkey, claim_window_secs: i64) -> Instruction {
+    build_ix(
+        rwa_raffle::ID,
+        accounts::SetClaimWindow {
+            organizer: *organizer,
+            raffle: *raffle,
+            event_authority: event_authority(),
+            program: rwa_raffle::ID,
+        },
+        instruction::SetClaimWindow { claim_window_secs },
+    )
+}
+
+/// `register_allowlist`: `payer` proves its `(wallet, max_allocation)` leaf
+/// (see [`crate::allowlist::AllowlistTree::proof`]).
+pub fn register_allowlist(payer: &Pubkey, raffle: &Pubkey, max_allocation: u64, proof: Vec<[u8; 32]>) -> Instruction {
+    build_ix(
+        rwa_raffle::ID,
+        accounts::RegisterAllowlist {
+            payer: *payer,
+            raffle: *raffle,
+            allocation: find_allocation(raffle, payer).0,
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: rwa_raffle::ID,
+        },
+        instruction::RegisterAllowlist { max_allocation, proof },
+    )
+}
+
+/// `close_allocation`: permissionless once the presale is over; rent goes to `wallet`.
+pub fn close_allocation(caller: &Pubkey, raffle: &Pubkey, wallet: &Pubkey) -> Instruction {
+    build_ix(
+        rwa_raffle::ID,
+        accounts::CloseAllocation {
+            caller: *caller,
+            raffle: *raffle,
+            allocation: find_allocation(raffle, wallet).0,
+            wallet: *wallet,
+        },
+        instruction::CloseAllocation {},
+    )
+}
+
+/// `request_draw`: permissionless; the first caller earns the keeper bounty.
+pub fn request_draw(caller: &Pubkey, raffle: &Pubkey) -> Instruction {
+    build_ix(
+        rwa_raffle::ID,
+        accounts::RequestDraw {
+            caller: *caller,
+            raffle: *raffle,
+            event_authority: event_authority(),
+            program: rwa_raffle::ID,
+        },
+        instruction::RequestDraw {},
+    )
+}
+
+/// `start_refunding`: permissionless once the deadline passed without selling out.
+pub fn start_refunding(caller: &Pubkey, raffle: &Pubkey) -> Instruction {
+    build_ix(
+        rwa_raffle::ID,
+        accounts::StartRefunding {
+            caller: *caller,
+            raffle: *raffle,
+            event_authority: event_authority(),
+            program: rwa_raffle::ID,
+        },
+        instruction::StartRefunding {},
+    )
+}
+
+/// `forfeit_winner`: permissionless once the winner's claim window ran out; reopens the
+/// draw without them and pays the caller the keeper bounty.
+pub fn forfeit_winner(caller: &Pubkey, raffle: &Pubkey) -> Instruction {
+    build_ix(
+        rwa_raffle::ID,
+        accounts::ForfeitWinner {
+            caller: *caller,
+            raffle: *raffle,
+            slots: find_slots(raffle).0,
+            event_authority: event_authority(),
+            program: rwa_raffle::ID,
+        },
+        instruction::ForfeitWinner {},
+    )
+}
+
+/// `settle_draw`, signed by the platform authority. On a redraw, `winner_ticket` must not
+/// belong to a forfeited winner.
+pub fn settle_draw(authority: &Pubkey, raffle: &Pubkey, winner_ticket: u64) -> Instruction {
+    build_ix(
+        rwa_raffle::ID,
+        accounts::SettleDraw {
+            config: crate::pda::find_raffle_platform_config().0,
+            authority: *authority,
+            raffle: *raffle,
+            slots: find_slots(raffle).0,
+            event_authority: event_authority(),
+            program: rwa_raffle::ID,
+        },
+        instruction::SettleDraw { winner_ticket },
+    )
+}
+
+/// Token-2022 account of `holder` for the ticket NFT of the ticket starting at `ticket_start`.
+pub fn holder_ticket_account(holder: &Pubkey, raffle: &Pubkey, ticket_start: u64) -> Pubkey {
+    let ticket_mint = find_ticket_mint(raffle, ticket_start).0;
+    get_associated_token_address_with_program_id(holder, &ticket_mint, &TOKEN_2022_PROGRAM_ID)
+}
+
+/// Ticket PDA and holder token account for `holder`'s ticket at `ticket_start`; tokenized
+/// tickets live under their ticket mint and are proven by the holder's NFT account.
+fn held_ticket(holder: &Pubkey, raffle: &Pubkey, ticket_start: u64, tokenized: bool) -> (Pubkey, Option<Pubkey>) {
+    if tokenized {
+        let ticket_mint = find_ticket_mint(raffle, ticket_start).0;
+        (find_ticket(raffle, &ticket_mint, ticket_start).0, Some(holder_ticket_account(holder, raffle, ticket_start)))
+    } else {
+        (find_ticket(raffle, holder, ticket_start).0, None)
+    }
+}
+
+/// `tokenize_ticket`: mint `owner`'s ticket (PDA `ticket`, starting at `ticket_start`) as a
+/// Token-2022 NFT into [`holder_ticket_account`]. `payer` is the old ticket's `payer`, which
+/// gets its rent back.
+pub fn tokenize_ticket(owner: &Pubkey, raffle: &Pubkey, ticket: &Pubkey, ticket_start: u64, payer: &Pubkey) -> Instruction {
+    let ticket_mint = find_ticket_mint(raffle, ticket_start).0;
+    build_ix(
+        rwa_raffle::ID,
+        accounts::TokenizeTicket {
+            owner: *owner,
+            raffle: *raffle,
+            slots: find_slots(raffle).0,
+            ticket: *ticket,
+            payer: *payer,
+            ticket_mint,
+            owner_ticket_account: holder_ticket_account(owner, raffle, ticket_start),
+            new_ticket: find_ticket(raffle, &ticket_mint, ticket_start).0,
+            token_2022_program: TOKEN_2022_PROGRAM_ID,
+            associated_token_program: anchor_spl::associated_token::ID,
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: rwa_raffle::ID,
+        },
+        instruction::TokenizeTicket {},
+    )
+}
+
+/// `claim_refund` for the ticket `payer` bought starting at `ticket_start`.
+pub fn claim_refund(
+    payer: &Pubkey,
+    raffle: &Pubkey,
+    mint: &Pubkey,
+    payer_ata: &Pubkey,
+    escrow_ata: &Pubkey,
+    ticket_start: u64,
+) -> Instruction {
+    claim_refund_with(payer, raffle, mint, payer_ata, escrow_ata, ticket_start, false, None)
+}
+
+/// [`claim_refund`]; with `tokenized`, for the ticket NFT `payer` holds. MRFT-refund raffles
+/// mint the MRFTs into `mrft_tree` in the same transaction when it is given.
+#[allow(clippy::too_many_arguments)]
+pub fn claim_refund_with(
+    payer: &Pubkey,
+    raffle: &Pubkey,
+    mint: &Pubkey,
+    payer_ata: &Pubkey,
+    escrow_ata: &Pubkey,
+    ticket_start: u64,
+    tokenized: bool,
+    mrft_tree: Option<&Pubkey>,
+) -> Instruction {
+    let (ticket, holder_ticket_account) = held_ticket(payer, raffle, ticket_start, tokenized);
+    build_ix(
+        rwa_raffle::ID,
+        accounts::ClaimRefund {
+            payer: *payer,
+            raffle: *raffle,
+            mint: *mint,
+            payer_ata: *payer_ata,
+            escrow_ata: *escrow_ata,
+            ticket,
+            holder_ticket_account,
+            token_program: TOKEN_PROGRAM_ID,
+            mrft: mrft_mint(mrft_tree),
+            event_authority: event_authority(),
+            program: rwa_raffle::ID,
+        },
+        instruction::ClaimRefund {},
+    )
+}
+
+/// `refund_batch`. Each entry is `(ticket, owner_ata)`; pass `Some(owner_ata)` for
+/// USDC-refund raffles (`refund_mode == 0`) and `None` otherwise. Tokenized tickets are
+/// skipped; their holders refund through [`claim_refund_with`].
+pub fn refund_batch(
+    caller: &Pubkey,
+    raffle: &Pubkey,
+    mint: &Pubkey,
+    escrow_ata: &Pubkey,
+    tickets: &[(Pubkey, Option<Pubkey>)],
+) -> Instruction {
+    let mut ix = build_ix(
+        rwa_raffle::ID,
+        accounts::RefundBatch {
+            caller: *caller,
+            raffle: *raffle,
+            mint: *mint,
+            escrow_ata: *escrow_ata,
+            token_program: TOKEN_PROGRAM_ID,
+            event_authority: event_authority(),
+            program: rwa_raffle::ID,
+        },
+        instruction::RefundBatch {},
+    );
+    for (ticket, owner_ata) in tickets {
+        ix.accounts.push(AccountMeta::new(*ticket, false));
+        if let Some(owner_ata) = owner_ata {
+            ix.accounts.push(AccountMeta::new(*owner_ata, false));
+        }
+    }
+    ix
+}
+
+/// `claim_win`.
+pub fn claim_win(winner: &Pubkey, raffle: &Pubkey, ticket_start: u64) -> Instruction {
+    claim_win_with(winner, raffle, ticket_start, false)
+}
+
+/// [`claim_win`]; with `tokenized`, for the ticket NFT `winner` holds.
+pub fn claim_win_with(winner: &Pubkey, raffle: &Pubkey, ticket_start: u64, tokenized: bool) -> Instruction {
+    let (ticket, holder_ticket_account) = held_ticket(winner, raffle, ticket_start, tokenized);
+    build_ix(
+        rwa_raffle::ID,
+        accounts::ClaimWin {
+            winner: *winner,
+            raffle: *raffle,
+            ticket,
+            holder_ticket_account,
+            event_authority: event_authority(),
+            program: rwa_raffle::ID,
+        },
+        instruction::ClaimWin {},
+    )
+}
+
+/// `set_prize_nft`: escrow the prize NFT into `prize_escrow` (owned by the raffle PDA).
+/// Passes the prize's Metaplex metadata and master edition for the collection check;
+/// pass `programmable` for pNFT prizes.
+pub fn set_prize_nft(
+    organizer: &Pubkey,
+    raffle: &Pubkey,
+    prize_mint: &Pubkey,
+    organizer_prize_ata: &Pubkey,
+    prize_escrow: &Pubkey,
+    programmable: Option<&ProgrammablePrize>,
+) -> Instruction {
+    build_ix(
+        rwa_raffle::ID,
+        accounts::SetPrizeNft {
+            organizer: *organizer,
+            raffle: *raffle,
+            prize_mint: *prize_mint,
+            organizer_prize_ata: *organizer_prize_ata,
+            prize_escrow: *prize_escrow,
+            prize_metadata: Some(find_metadata(prize_mint).0),
+            prize_master_edition: Some(find_master_edition(prize_mint).0),
+            token_program: TOKEN_PROGRAM_ID,
+            pnft: programmable_transfer(prize_mint, organizer_prize_ata, prize_escrow, programmable),
+            event_authority: event_authority(),
+            program: rwa_raffle::ID,
+        },
+        instruction::SetPrizeNft {},
+    )
+}
+
+/// `claim_prize`; pass `programmable` for pNFT prizes.
+pub fn claim_prize(
+    winner: &Pubkey,
+    raffle: &Pubkey,
+    prize_mint: &Pubkey,
+    prize_escrow: &Pubkey,
+    winner_prize_ata: &Pubkey,
+    ticket_start: u64,
+    programmable: Option<&ProgrammablePrize>,
+) -> Instruction {
+    claim_prize_with(winner, raffle, prize_mint, prize_escrow, winner_prize_ata, ticket_start, false, programmable)
+}
+
+/// [`claim_prize`]; with `tokenized`, for the ticket NFT `winner` holds.
+#[allow(clippy::too_many_arguments)]
+pub fn claim_prize_with(
+    winner: &Pubkey,
+    raffle: &Pubkey,
+    prize_mint: &Pubkey,
+    prize_escrow: &Pubkey,
+    winner_prize_ata: &Pubkey,
+    ticket_start: u64,
+    tokenized: bool,
+    programmable: Option<&ProgrammablePrize>,
+) -> Instruction {
+    let (ticket, holder_ticket_account) = held_ticket(winner, raffle, ticket_start, tokenized);
+    build_ix(
+        rwa_raffle::ID,
+        accounts::ClaimPrize {
+            winner: *winner,
+            raffle: *raffle,
+            prize_mint: Some(*prize_mint),
+            prize_escrow: Some(*prize_escrow),
+            winner_prize_ata: Some(*winner_prize_ata),
+            ticket,
+            holder_ticket_account,
+            prize_metadata: Some(find_metadata(prize_mint).0),
+            prize_master_edition: Some(find_master_edition(prize_mint).0),
+            token_program: TOKEN_PROGRAM_ID,
+            pnft: programmable_transfer(prize_mint, prize_escrow, winner_prize_ata, programmable),
+            cnft: compressed_transfer(None, &Pubkey::default()),
+            event_authority: event_authority(),
+            program: rwa_raffle::ID,
+        },
+        instruction::ClaimPrize { cnft_root: None },
+    )
+}
+
+/// `set_prize_cnft`: escrow the Bubblegum leaf described by `prize`, transferring it to the
+/// raffle PDA or, when `prize.delegated`, delegating it. `proof` is the leaf's proof path
+/// against `root` (e.g. from DAS `getAssetProof`, minus the tree's canopy).
+#[cfg(feature = "bubblegum")]
+pub fn set_prize_cnft(
+    organizer: &Pubkey,
+    raffle: &Pubkey,
+    prize: &CompressedPrize,
+    root: [u8; 32],
+    proof: &[Pubkey],
+) -> Instruction {
+    let mut ix = build_ix(
+        rwa_raffle::ID,
+        accounts::SetPrizeCnft {
+            organizer: *organizer,
+            raffle: *raffle,
+            merkle_tree: prize.merkle_tree,
+            tree_config: find_tree_config(&prize.merkle_tree).0,
+            log_wrapper: SPL_NOOP_PROGRAM_ID,
+            compression_program: SPL_ACCOUNT_COMPRESSION_PROGRAM_ID,
+            bubblegum_program: BUBBLEGUM_PROGRAM_ID,
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: rwa_raffle::ID,
+        },
+        instruction::SetPrizeCnft {
+            root,
+            data_hash: prize.data_hash,
+            creator_hash: prize.creator_hash,
+            nonce: prize.nonce,
+            index: prize.index,
+            delegate: prize.delegated,
+        },
+    );
+    push_proof(&mut ix, proof);
+    ix
+}
+
+/// [`claim_prize`] for a compressed prize: `prize` is the raffle's `prize_cnft`, `root` the
+/// tree's current root and `proof` the leaf's proof path against it.
+#[allow(clippy::too_many_arguments)]
+pub fn claim_prize_cnft(
+    winner: &Pubkey,
+    raffle: &Pubkey,
+    organizer: &Pubkey,
+    prize: &CompressedPrize,
+    root: [u8; 32],
+    proof: &[Pubkey],
+    ticket_start: u64,
+    tokenized: bool,
+) -> Instruction {
+    let (ticket, holder_ticket_account) = held_ticket(winner, raffle, ticket_start, tokenized);
+    let mut ix = build_ix(
+        rwa_raffle::ID,
+        accounts::ClaimPrize {
+            winner: *winner,
+            raffle: *raffle,
+            prize_mint: None,
+            prize_escrow: None,
+            winner_prize_ata: None,
+            ticket,
+            holder_ticket_account,
+            prize_metadata: None,
+            prize_master_edition: None,
+            token_program: TOKEN_PROGRAM_ID,
+            pnft: programmable_transfer(&Pubkey::default(), &Pubkey::default(), &Pubkey::default(), None),
+            cnft: compressed_transfer(Some(prize), organizer),
+            event_authority: event_authority(),
+            program: rwa_raffle::ID,
+        },
+        instruction::ClaimPrize { cnft_root: Some(root) },
+    );
+    push_proof(&mut ix, proof);
+    ix
+}
+
+/// Accounts for [`claim_prize_mint`].
+#[cfg(feature = "metaplex")]
+#[derive(Clone, Debug)]
+pub struct ClaimPrizeMintKeys {
+    pub winner: Pubkey,
+    pub raffle: Pubkey,
+    pub prize_mint: Pubkey,
+    pub prize_metadata: Pubkey,
+    pub prize_master_edition: Pubkey,
+    pub collection_mint: Pubkey,
+    pub collection_metadata: Pubkey,
+    pub collection_master_edition: Pubkey,
+    pub mint_authority: Pubkey,
+    pub collection_authority: Pubkey,
+    pub token_metadata_program: Pubkey,
+    /// The winning ticket is tokenized and `winner` holds its NFT.
+    pub tokenized_ticket: bool,
+}
+
+/// `claim_prize_mint`: mint the prize NFT on claim (post-mint path).
+#[cfg(feature = "metaplex")]
+pub fn claim_prize_mint(keys: &ClaimPrizeMintKeys, ticket_start: u64) -> Instruction {
+    let (ticket, holder_ticket_account) = held_ticket(&keys.winner, &keys.raffle, ticket_start, keys.tokenized_ticket);
+    build_ix(
+        rwa_raffle::ID,
+        accounts::ClaimPrizeMint {
+            winner: keys.winner,
+            raffle: keys.raffle,
+            prize_mint: keys.prize_mint,
+            prize_metadata: keys.prize_metadata,
+            prize_master_edition: keys.prize_master_edition,
+            collection_mint: keys.collection_mint,
+            collection_metadata: keys.collection_metadata,
+            collection_master_edition: keys.collection_master_edition,
+            mint_authority: keys.mint_authority,
+            collection_authority: keys.collection_authority,
+            ticket,
+            holder_ticket_account,
+            token_metadata_program: keys.token_metadata_program,
+            sysvar_instructions: sysvar::instructions::ID,
+            system_program: system_program::ID,
+            token_program: TOKEN_PROGRAM_ID,
+            rent: sysvar::rent::ID,
+            event_authority: event_authority(),
+            program: rwa_raffle::ID,
+        },
+        instruction::ClaimPrizeMint {},
+    )
+}
+
+/// `collect_proceeds`: organizer sweeps the escrow after completion.
+pub fn collect_proceeds(organizer: &Pubkey, mint: &Pubkey, organizer_ata: &Pubkey, escrow_ata: &Pubkey) -> Instruction {
+    build_ix(
+        rwa_raffle::ID,
+        accounts::CollectProceeds {
+            organizer: *organizer,
+            raffle: find_raffle(mint, organizer).0,
+            mint: *mint,
+            organizer_ata: *organizer_ata,
+            escrow_ata: *escrow_ata,
+            token_program: TOKEN_PROGRAM_ID,
+        },
+        instruction::CollectProceeds {},
+    )
+}
+
+/// `close_ticket`: permissionless once the raffle is settled. `owner` is the ticket's owner
+/// (the ticket mint for tokenized tickets); the rent goes back to the ticket's `payer`.
+pub fn close_ticket(caller: &Pubkey, raffle: &Pubkey, owner: &Pubkey, ticket_start: u64, payer: &Pubkey) -> Instruction {
+    build_ix(
+        rwa_raffle::ID,
+        accounts::CloseTicket {
+            caller: *caller,
+            raffle: *raffle,
+            ticket: find_ticket(raffle, owner, ticket_start).0,
+            payer: *payer,
+        },
+        instruction::CloseTicket {},
+    )
+}
+
+/// `transfer_ticket`: move `ticket` (covering `ticket_start..`) to `recipient` while the raffle is
+/// `Selling`/`Drawing`. `ticket` may be a range-deposit ([`find_ticket`]) or slot-join
+/// ([`crate::pda::find_slot_ticket`]) PDA; the new ticket is at `find_ticket(raffle, recipient, ticket_start)`.
+/// `payer` is the old ticket's `payer`, which gets its rent back.
+pub fn transfer_ticket(
+    owner: &Pubkey,
+    raffle: &Pubkey,
+    ticket: &Pubkey,
+    ticket_start: u64,
+    recipient: &Pubkey,
+    payer: &Pubkey,
+) -> Instruction {
+    build_ix(
+        rwa_raffle::ID,
+        accounts::TransferTicket {
+            owner: *owner,
+            raffle: *raffle,
+            slots: find_slots(raffle).0,
+            ticket: *ticket,
+            payer: *payer,
+            recipient: *recipient,
+            new_ticket: find_ticket(raffle, recipient, ticket_start).0,
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: rwa_raffle::ID,
+        },
+        instruction::TransferTicket {},
+    )
+}
+
+/// `close_slots`: rent goes to the organizer.
+pub fn close_slots(caller: &Pubkey, raffle: &Pubkey, organizer: &Pubkey) -> Instruction {
+    build_ix(
+        rwa_raffle::ID,
+        accounts::CloseSlots {
+            caller: *caller,
+            raffle: *raffle,
+            slots: find_slots(raffle).0,
+            organizer: *organizer,
+        },
+        instruction::CloseSlots {},
+    )
+}
+
+/// `close_raffle`: closes the escrow and the raffle once every ticket and the slots are closed.
+pub fn close_raffle(caller: &Pubkey, mint: &Pubkey, organizer: &Pubkey, escrow_ata: &Pubkey) -> Instruction {
+    build_ix(
+        rwa_raffle::ID,
+        accounts::CloseRaffle {
+            caller: *caller,
+            raffle: find_raffle(mint, organizer).0,
+            escrow_ata: *escrow_ata,
+            organizer: *organizer,
+            token_program: TOKEN_PROGRAM_ID,
+            event_authority: event_authority(),
+            program: rwa_raffle::ID,
+        },
+        instruction::CloseRaffle {},
+    )
+}
+
+/// `sweep_mrft_backing`: any keeper moves the unredeemed MRFT backing of a settled, long
+/// failed MRFT-refund raffle into the refund vault (the vault authority's ATA for `mint`).
+pub fn sweep_mrft_backing(caller: &Pubkey, mint: &Pubkey, organizer: &Pubkey, escrow_ata: &Pubkey) -> Instruction {
+    let vault_authority = crate::pda::find_refund_vault_authority().0;
+    build_ix(
+        rwa_raffle::ID,
+        accounts::SweepMrftBacking {
+            caller: *caller,
+            raffle: find_raffle(mint, organizer).0,
+            mint: *mint,
+            escrow_ata: *escrow_ata,
+            vault_authority,
+            refund_vault: get_associated_token_address_with_program_id(&vault_authority, mint, &TOKEN_PROGRAM_ID),
+            token_program: TOKEN_PROGRAM_ID,
+            event_authority: event_authority(),
+            program: rwa_raffle::ID,
+        },
+        instruction::SweepMrftBacking {},
+    )
+}
+
+/// `audit_raffle`: read-only escrow solvency check; simulate it to monitor a raffle.
+pub fn audit_raffle(mint: &Pubkey, organizer: &Pubkey, escrow_ata: &Pubkey) -> Instruction {
+    build_ix(
+        rwa_raffle::ID,
+        accounts::AuditRaffle {
+            raffle: find_raffle(mint, organizer).0,
+            mint: *mint,
+            escrow_ata: *escrow_ata,
+            event_authority: event_authority(),
+            program: rwa_raffle::ID,
+        },
+        instruction::AuditRaffle {},
+    )
+}
+
+/// `migrate_ticket`: upgrade a legacy ticket account (no `payer`) to the current layout.
+/// Migrate its `raffle` first; legacy refunds are counted there.
+pub fn migrate_ticket(payer: &Pubkey, ticket: &Pubkey, raffle: &Pubkey) -> Instruction {
+    build_ix(
+        rwa_raffle::ID,
+        accounts::MigrateTicket {
+            payer: *payer,
+            ticket: *ticket,
+            raffle: *raffle,
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: rwa_raffle::ID,
+        },
+        instruction::MigrateTicket {},
+    )
+}
+
+/// `migrate_raffle`: upgrade a legacy raffle account to the current layout.
+pub fn migrate_raffle(payer: &Pubkey, raffle: &Pubkey) -> Instruction {
+    build_ix(
+        rwa_raffle::ID,
+        accounts::MigrateRaffle {
+            payer: *payer,
+            raffle: *raffle,
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: rwa_raffle::ID,
+        },
+        instruction::MigrateRaffle {},
+    )
+}
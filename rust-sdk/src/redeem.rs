@@ -0,0 +1,115 @@
+//! `rwa_redeem` instruction builders.
+
+use anchor_lang::solana_program::{instruction::Instruction, pubkey::Pubkey};
+use anchor_lang::system_program;
+#[cfg(not(feature = "test-bypass"))]
+use anchor_lang::solana_program::sysvar;
+use anchor_spl::token::ID as TOKEN_PROGRAM_ID;
+use rwa_redeem::{accounts, instruction};
+
+use crate::build_ix;
+use crate::pda::find_redemption;
+#[cfg(not(feature = "test-bypass"))]
+use crate::Permit;
+
+/// `redeem_nft_with_permit`: burn the NFT and open a redemption request. Must be
+/// preceded by the ed25519 instruction for [`crate::permit::redeem_permit_message`].
+#[cfg(not(feature = "test-bypass"))]
+pub fn redeem_nft_with_permit(
+    holder: &Pubkey,
+    nft_mint: &Pubkey,
+    holder_nft_ata: &Pubkey,
+    redemption_type: u8,
+    permit: &Permit,
+) -> Instruction {
+    build_ix(
+        rwa_redeem::ID,
+        accounts::RedeemNftWithPermit {
+            holder: *holder,
+            nft_mint: *nft_mint,
+            holder_nft_ata: *holder_nft_ata,
+            redemption: find_redemption(nft_mint, holder).0,
+            instructions_sysvar: sysvar::instructions::ID,
+            system_program: system_program::ID,
+            token_program: TOKEN_PROGRAM_ID,
+        },
+        instruction::RedeemNftWithPermit {
+            redemption_type,
+            permit_nonce: permit.nonce,
+            permit_expiry_unix_ts: permit.expiry_unix_ts,
+        },
+    )
+}
+
+/// `redeem_nft_test` (test-bypass builds only).
+#[cfg(feature = "test-bypass")]
+pub fn redeem_nft_test(holder: &Pubkey, nft_mint: &Pubkey, holder_nft_ata: &Pubkey, redemption_type: u8) -> Instruction {
+    build_ix(
+        rwa_redeem::ID,
+        accounts::RedeemNftTest {
+            holder: *holder,
+            nft_mint: *nft_mint,
+            holder_nft_ata: *holder_nft_ata,
+            redemption: find_redemption(nft_mint, holder).0,
+            system_program: system_program::ID,
+            token_program: TOKEN_PROGRAM_ID,
+        },
+        instruction::RedeemNftTest { redemption_type },
+    )
+}
+
+/// Bubblegum accounts for [`redeem_compressed_nft`].
+#[cfg(all(feature = "bubblegum", not(feature = "test-bypass")))]
+#[derive(Clone, Debug)]
+pub struct CompressedNftKeys {
+    pub merkle_tree: Pubkey,
+    pub tree_config: Pubkey,
+    pub log_wrapper: Pubkey,
+    pub compression_program: Pubkey,
+    pub bubblegum_program: Pubkey,
+}
+
+/// `redeem_compressed_nft`. `nft_mint` is the cNFT asset id.
+#[cfg(all(feature = "bubblegum", not(feature = "test-bypass")))]
+pub fn redeem_compressed_nft(
+    holder: &Pubkey,
+    nft_mint: &Pubkey,
+    bubblegum: &CompressedNftKeys,
+    redemption_type: u8,
+    permit: &Permit,
+    nft_proofs: Vec<u8>,
+) -> Instruction {
+    build_ix(
+        rwa_redeem::ID,
+        accounts::RedeemCompressedNft {
+            holder: *holder,
+            nft_mint: *nft_mint,
+            merkle_tree: bubblegum.merkle_tree,
+            tree_config: bubblegum.tree_config,
+            log_wrapper: bubblegum.log_wrapper,
+            compression_program: bubblegum.compression_program,
+            bubblegum_program: bubblegum.bubblegum_program,
+            redemption: find_redemption(nft_mint, holder).0,
+            instructions_sysvar: sysvar::instructions::ID,
+            system_program: system_program::ID,
+        },
+        instruction::RedeemCompressedNft {
+            redemption_type,
+            permit_nonce: permit.nonce,
+            permit_expiry_unix_ts: permit.expiry_unix_ts,
+            nft_proofs,
+        },
+    )
+}
+
+/// `mark_fulfilled`: attach fulfillment data (tracking number, txid, ...).
+pub fn mark_fulfilled(authority: &Pubkey, redemption: &Pubkey, fulfillment_data: Vec<u8>) -> Instruction {
+    build_ix(
+        rwa_redeem::ID,
+        accounts::MarkFulfilled {
+            authority: *authority,
+            redemption: *redemption,
+        },
+        instruction::MarkFulfilled { fulfillment_data },
+    )
+}
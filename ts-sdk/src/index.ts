@@ -9,7 +9,7 @@ export type InitRaffleArgs = {
 
 export type DepositArgs = {
   amount: bigint; // base units; must be whole tokens
-  startIndex: bigint; // read on-chain raffle.next_ticket_index
+  startIndex: bigint; // first ticket of a free range in the raffle slots bitmap (raffle.next_ticket_index unless joins took it)
 };
 
 export const RAFFLE_SEED = "raffle";
@@ -41,6 +41,15 @@ export async function deriveSlotsPda(
   return await getProgramDerivedAddress({ programAddress: programId, seeds });
 }
 
+// Anchor event-CPI signer; every instruction that emits events takes
+// [event_authority, program] as its last two accounts.
+export async function deriveEventAuthorityPda(
+  programId: Address,
+): Promise<[Address, number]> {
+  const seeds = [new TextEncoder().encode("__event_authority")];
+  return await getProgramDerivedAddress({ programAddress: programId, seeds });
+}
+
 export async function deriveTicketPda(
   programId: Address,
   raffle: Address,
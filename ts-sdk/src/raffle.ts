@@ -2,7 +2,7 @@ import { type Address, getAddressEncoder, getAddressDecoder } from "@solana/addr
 import type { IInstruction } from "@solana/instructions";
 import { createHash } from "crypto";
 import { ed25519VerifyIx } from "./solanaKit";
-import { deriveRafflePda, deriveSlotsPda } from "./index";
+import { deriveEventAuthorityPda, deriveRafflePda, deriveSlotsPda } from "./index";
 
 export type CreateRaffleDirectArgs = {
   programId: Address;
@@ -12,7 +12,9 @@ export type CreateRaffleDirectArgs = {
   requiredTickets: bigint;
   deadlineUnixTs: bigint;
   autoDraw: boolean;
-  ticketMode: number; // 0=disabled, 1=require_burn, 2=accept_without_burn
+  ticketMode: number; // 0=disabled, 1=accept_without_burn, 2=require_burn
+  revealTimeUnixTs?: bigint; // optional scheduled reveal; draws are blocked until then
+  crankReserveLamports?: bigint; // keeper bounty reserve moved into the raffle PDA (default 0)
   tokenProgram?: Address; // default Tokenkeg
 };
 
@@ -53,12 +55,15 @@ export async function createDirectRaffleIx(_args: CreateRaffleDirectArgs): Promi
   const tokenProgram = _args.tokenProgram ?? ("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA" as Address);
   const [raffle] = await deriveRafflePda(programId, escrowMint, organizer);
   const [slots] = await deriveSlotsPda(programId, raffle);
+  const [eventAuthority] = await deriveEventAuthorityPda(programId);
 
   const data = Buffer.concat([
     discriminator("initialize_raffle"),
     u64le(requiredTickets),
     i64le(deadlineUnixTs),
     Buffer.from([autoDraw ? 1 : 0, ticketMode & 0xff]),
+    optionI64le(_args.revealTimeUnixTs),
+    u64le(_args.crankReserveLamports ?? 0n),
   ]);
 
   return {
@@ -71,6 +76,8 @@ export async function createDirectRaffleIx(_args: CreateRaffleDirectArgs): Promi
       { address: slots, role: 1 /* writable */ },
       { address: "11111111111111111111111111111111" as Address, role: 0 /* System */ },
       { address: tokenProgram, role: 0 },
+      { address: eventAuthority, role: 0 },
+      { address: programId, role: 0 },
     ],
     data,
   };
@@ -105,6 +112,7 @@ export async function createRaffleWithPermitIxs(_args: CreateRaffleWithPermitArg
   const tokenProgram = _args.tokenProgram ?? ("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA" as Address);
   const [raffle] = await deriveRafflePda(programId, escrowMint, organizer);
   const [slots] = await deriveSlotsPda(programId, raffle);
+  const [eventAuthority] = await deriveEventAuthorityPda(programId);
 
   const encoder = getAddressEncoder();
   const edIx = ed25519VerifyIx({
@@ -136,6 +144,8 @@ export async function createRaffleWithPermitIxs(_args: CreateRaffleWithPermitArg
       { address: instructionsSysvar, role: 0 },
       { address: systemProgram, role: 0 },
       { address: tokenProgram, role: 0 },
+      { address: eventAuthority, role: 0 },
+      { address: programId, role: 0 },
     ],
     data,
   };
@@ -167,6 +177,10 @@ function i64le(v: bigint): Buffer {
   return b;
 }
 
+function optionI64le(v?: bigint): Buffer {
+  return v === undefined ? Buffer.from([0]) : Buffer.concat([Buffer.from([1]), i64le(v)]);
+}
+
 function encodeVecU32(arr: number[]): Buffer {
   const len = Buffer.alloc(4);
   len.writeUInt32LE(arr.length);
@@ -30,7 +30,7 @@ export function ed25519VerifyIx(args: {
   // - public_key: [u8; 32]
   // - message: [u8; message.length]
 
-  const headerSize = 14; // 1 + 1 + 2*6
+  const headerSize = 16; // 1 + 1 + 2*7
   const signatureOffset = headerSize;
   const publicKeyOffset = signatureOffset + 64;
   const messageOffset = publicKeyOffset + 32;